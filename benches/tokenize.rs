@@ -0,0 +1,100 @@
+//! 分词的性能测试，统计每次分词的耗时和内存分配次数
+//!
+//! 运行 `cargo bench --bench tokenize`，通过计数的全局分配器统计分词过程中的内存分配，
+//! 写入的 token 直接丢弃，结果只包含分词器本身的分配
+//!
+//! 没有使用 criterion：替换全局分配器会统计到 criterion 自身的分配，而且项目不额外引入开发依赖，
+//! 这里使用 `harness = false` 的 `main` 直接计时，耗时只用于比较同一台机器上修改前后的结果
+
+use sqlite_simple_tokenizer::tokenizer::jieba_tokenizer::{JiebaGlobal, JiebaTokenizer};
+use sqlite_simple_tokenizer::tokenizer::simple_tokenizer::{SimpleGlobal, SimpleTokenizer};
+use sqlite_simple_tokenizer::tokenizer::unicode_tokenizer::UnicodeWordsTokenizer;
+use sqlite_simple_tokenizer::tokenizer::{TokenizeReason, Tokenizer};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// 统计内存分配次数的全局分配器
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// 每个分词器重复分词的次数
+const ITERATIONS: usize = 20;
+
+/// 中英文混排的长文档
+fn document() -> String {
+    "SQLite 是一个轻量级的嵌入式数据库，全文搜索使用 FTS5 扩展，支持中文和拼音的分词。\
+     The tokenizer splits running sentences into searchable words and stems them. "
+        .repeat(500)
+}
+
+/// 只有英语的长文档，单词都是 ascii 字符
+fn english_document() -> String {
+    "The tokenizer splits running sentences into searchable words and stems them, \
+     SQLite stores the tokens in an inverted index for fast full text queries. "
+        .repeat(500)
+}
+
+/// 使用指定参数分词，输出每次分词的平均耗时和内存分配次数
+fn bench<T: Tokenizer>(name: &str, global: &T::Global, args: &[&str], text: &str) {
+    let args = args.iter().map(|arg| arg.to_string()).collect();
+    let mut tokenizer = T::new(global, args).unwrap();
+    // 第一次分词会加载字典，不计入统计
+    tokenizer
+        .tokenize(TokenizeReason::Document, text.as_bytes(), |_, _, _| Ok(()))
+        .unwrap();
+    let mut tokens = 0;
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        tokenizer
+            .tokenize(
+                TokenizeReason::Document,
+                black_box(text.as_bytes()),
+                |token, _, _| {
+                    black_box(token);
+                    tokens += 1;
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+    let elapsed = start.elapsed() / ITERATIONS as u32;
+    let allocations = (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / ITERATIONS;
+    println!(
+        "{name:<20} {elapsed:>12.2?}/iter {:>8} tokens {allocations:>8} allocations",
+        tokens / ITERATIONS
+    );
+}
+
+fn main() {
+    let text = document();
+    let simple = SimpleGlobal::default();
+    bench::<SimpleTokenizer>("simple", &simple, &[], &text);
+    bench::<SimpleTokenizer>("simple full_pinyin", &simple, &["full_pinyin"], &text);
+    bench::<JiebaTokenizer>("jieba", &JiebaGlobal::default(), &[], &text);
+    let english = english_document();
+    bench::<SimpleTokenizer>("simple english", &simple, &[], &english);
+    bench::<UnicodeWordsTokenizer>("unicode english", &(), &[], &english);
+}
@@ -73,6 +73,31 @@ fn to_plain(input: &str) -> String {
     pinyin
 }
 
+/// 获取第一个读音不带声调的拼音字面量，原始数据中第一个读音是最常用的读音
+fn to_primary(input: &str) -> String {
+    let first = input.split(",").map(str::trim).next().unwrap_or_default();
+    let plain = first
+        .chars()
+        .map(|ch| TONE_TO_PLAIN.get(&ch).copied().unwrap_or(ch))
+        .collect::<String>();
+    format!("{plain:?}")
+}
+
+/// 将带声调的拼音转换成字符串切片的字面量，保持原始的读音顺序并去重
+fn to_tone_slice(input: &str) -> String {
+    let mut values = Vec::<&str>::new();
+    for value in input.split(",").map(str::trim) {
+        if !values.contains(&value) {
+            values.push(value);
+        }
+    }
+    let values = values
+        .iter()
+        .map(|value| format!("{value:?}"))
+        .collect::<Vec<_>>();
+    format!("&[{}]", values.join(", "))
+}
+
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=data/pinyin.txt");
@@ -82,6 +107,8 @@ fn main() {
     let path = Path::new(&env::var("OUT_DIR").unwrap()).join("pinyin_data.rs");
     let mut file = BufWriter::new(File::create(&path).unwrap());
     let mut dirt = phf_codegen::Map::new();
+    let mut tone_dirt = phf_codegen::Map::new();
+    let mut primary_dirt = phf_codegen::Map::new();
     for line in DEFAULT_PINYIN_DATA.split("\n") {
         if line.is_empty() || line.starts_with("#") {
             continue;
@@ -93,12 +120,10 @@ fn main() {
         } else {
             char::default()
         };
-        let pinyin = if let Some(pinyin) = codepoint_and_pinyin.next() {
-            to_plain(pinyin)
-        } else {
-            String::default()
-        };
-        dirt.entry(codepoint, pinyin);
+        let pinyin = codepoint_and_pinyin.next().unwrap_or_default().trim();
+        dirt.entry(codepoint, to_plain(pinyin));
+        tone_dirt.entry(codepoint, to_tone_slice(pinyin));
+        primary_dirt.entry(codepoint, to_primary(pinyin));
     }
 
     write!(
@@ -108,6 +133,20 @@ fn main() {
     )
     .unwrap();
     writeln!(&mut file, ";").unwrap();
+    write!(
+        &mut file,
+        "static PINYIN_TONE_DIRT: phf::Map<char, &'static [&'static str]> = {}",
+        tone_dirt.build()
+    )
+    .unwrap();
+    writeln!(&mut file, ";").unwrap();
+    write!(
+        &mut file,
+        "static PINYIN_PRIMARY_DIRT: phf::Map<char, &'static str> = {}",
+        primary_dirt.build()
+    )
+    .unwrap();
+    writeln!(&mut file, ";").unwrap();
 
     // 构建停词表
     let path = Path::new(&env::var("OUT_DIR").unwrap()).join("stopword_data.rs");
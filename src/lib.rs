@@ -2,9 +2,10 @@
 mod create_extension;
 mod error;
 mod load_extension;
-mod pinyin;
-mod tokenizer;
+pub mod pinyin;
+pub mod tokenizer;
 mod utils;
+pub mod variants;
 
 include!(concat!(env!("OUT_DIR"), "/stopword_data.rs"));
 
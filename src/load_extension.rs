@@ -1,5 +1,4 @@
-use crate::tokenizer::jieba_tokenizer::JiebaTokenizer;
-use crate::tokenizer::register_tokenizer;
+use crate::tokenizer::register_all_tokenizers;
 use crate::tokenizer::simple_tokenizer::SimpleTokenizer;
 use crate::utils::to_rusqlite_error;
 use rusqlite::Connection;
@@ -45,9 +44,7 @@ fn simple_query<'a>(ctx: &FunctionContext) -> Result<ToSqlOutput<'a>, crate::Err
 }
 
 pub fn load_fts5_extension(connection: &Connection) -> Result<(), crate::Error> {
-    // 注册 simple_tokenizer
-    register_tokenizer::<SimpleTokenizer>(connection, ())?;
-    // 注册 jieba_tokenizer
-    register_tokenizer::<JiebaTokenizer>(connection, ())?;
+    // 注册全部内置的 tokenizer
+    register_all_tokenizers(connection)?;
     Ok(())
 }
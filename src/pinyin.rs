@@ -4,21 +4,135 @@ use std::collections::BTreeSet;
 
 include!(concat!(env!("OUT_DIR"), "/pinyin_data.rs"));
 
+/// 模糊音的声母映射，匹配拼音的开头，例如 `zhi` 映射成 `zi`、`lan` 映射成 `nan`
+pub const FUZZY_PINYIN_INITIALS: [(&str, &str); 4] =
+    [("zh", "z"), ("ch", "c"), ("sh", "s"), ("l", "n")];
+
+/// 模糊音的韵母映射，匹配拼音的结尾，前鼻音和后鼻音不做区分，例如 `ying` 映射成 `yin`
+pub const FUZZY_PINYIN_FINALS: [(&str, &str); 3] = [("ang", "an"), ("eng", "en"), ("ing", "in")];
+
 /// 通过给定的字符，判断是否有拼音
+///
+/// 拼音表同时包含简体字和繁体字，例如 `国` 和 `國` 都有拼音 `guo`
 pub fn has_pinyin(ch: &char) -> bool {
     PINYIN_DIRT.contains_key(ch)
 }
 
+/// 通过字符获取带声调的拼音，保持拼音表中的读音顺序，轻声的读音不带声调符号
+///
+/// 例如 `重` 得到 `["zhòng", "chóng", "tóng"]`
+pub fn pinyin_with_tone(ch: char) -> Option<&'static [&'static str]> {
+    PINYIN_TONE_DIRT.get(&ch).copied()
+}
+
 /// 通过字符获取拼音
 pub fn get_pinyin(ch: &char) -> Option<Vec<String>> {
-    let pinyin = PINYIN_DIRT.get(ch)?;
-    let result = pinyin
-        .split(",")
+    let result = pinyin_readings(*ch)?
         .map(str::to_owned)
         .collect::<Vec<String>>();
     Some(result)
 }
 
+/// 通过字符获取全部读音的拼音，和 [`get_pinyin`] 的顺序相同
+///
+/// 返回的拼音借用自静态的拼音表，分词时逐字写入拼音不需要为每个读音分配内存
+pub fn pinyin_readings(ch: char) -> Option<impl Iterator<Item = &'static str> + Clone> {
+    PINYIN_DIRT.get(&ch).map(|pinyin| pinyin.split(','))
+}
+
+/// 获取字符串中每个字符的拼音，没有拼音的字符（例如字母、数字和标点）为 `None`
+///
+/// 多音字只返回最常用的读音，例如 `说` 得到 `shuo`，需要全部读音时使用 [`get_pinyin`]。
+/// 返回的拼音借用自静态的拼音表，不会为每个字符分配内存
+pub fn string_to_pinyin(s: &str) -> Vec<Option<&'static str>> {
+    s.chars().map(primary_pinyin).collect()
+}
+
+/// 获取字符最常用读音的拼音，例如 `家` 得到 `jia`，没有拼音时返回 `None`
+pub fn primary_pinyin(ch: char) -> Option<&'static str> {
+    PINYIN_PRIMARY_DIRT.get(&ch).copied()
+}
+
+/// 获取一个字全部读音的首字母，声母为 `zh`、`ch`、`sh` 时同时保留两个字母的声母
+///
+/// 例如 `["zhong", "chong"]` 得到 `["z", "zh", "c", "ch"]`
+pub fn pinyin_initials<S: AsRef<str>>(pinyin_vec: &[S]) -> Vec<String> {
+    let mut result = Vec::<String>::new();
+    for pinyin in pinyin_vec {
+        let pinyin = pinyin.as_ref();
+        let Some(first) = pinyin.get(..1) else {
+            continue;
+        };
+        let mut initials = vec![first];
+        if let Some(two) = pinyin.get(..2)
+            && matches!(two, "zh" | "ch" | "sh")
+        {
+            initials.push(two);
+        }
+        for initial in initials {
+            if !result.iter().any(|s| s == initial) {
+                result.push(initial.to_owned());
+            }
+        }
+    }
+    result
+}
+
+/// 将一个合法的拼音音节按照模糊音映射成统一的形式，不是合法的拼音音节时返回 `None`
+///
+/// 声母和韵母分别按照 [`FUZZY_PINYIN_INITIALS`] 和 [`FUZZY_PINYIN_FINALS`] 映射，例如 `shang` 得到 `san`
+pub fn fuzzy_pinyin(pinyin: &str) -> Option<String> {
+    if !PINYIN_VALID.contains(pinyin) {
+        return None;
+    }
+    let mut result = pinyin.to_owned();
+    if let Some((from, to)) = FUZZY_PINYIN_INITIALS
+        .iter()
+        .find(|(from, _)| result.starts_with(from))
+    {
+        result.replace_range(..from.len(), to);
+    }
+    if let Some((from, to)) = FUZZY_PINYIN_FINALS
+        .iter()
+        .find(|(from, _)| result.ends_with(from))
+    {
+        result.replace_range(result.len() - from.len().., to);
+    }
+    Some(result)
+}
+
+/// 按顺序拼接每个字的拼音，列举全部读音组合，最多返回 `limit` 个组合
+///
+/// 例如 `[["guo"], ["jia", "gu"]]` 得到 `["guojia", "guogu"]`
+pub fn join_pinyin<S: AsRef<str>>(readings: &[Vec<S>], limit: usize) -> Vec<String> {
+    join_pinyin_with_separator(readings, "", limit)
+}
+
+/// 按顺序拼接每个字的拼音，音节之间插入 `separator`，列举全部读音组合，最多返回 `limit` 个组合
+///
+/// 例如 `[["xi"], ["an"]]` 使用 `'` 得到 `["xi'an"]`
+pub fn join_pinyin_with_separator<S: AsRef<str>>(
+    readings: &[Vec<S>],
+    separator: &str,
+    limit: usize,
+) -> Vec<String> {
+    let mut result = vec![String::new()];
+    for (index, pinyin_vec) in readings.iter().enumerate() {
+        let separator = if index == 0 { "" } else { separator };
+        let mut next = Vec::with_capacity(limit.min(result.len() * pinyin_vec.len()));
+        'outer: for prefix in &result {
+            for pinyin in pinyin_vec {
+                if next.len() >= limit {
+                    break 'outer;
+                }
+                next.push(format!("{prefix}{separator}{}", pinyin.as_ref()));
+            }
+        }
+        result = next;
+    }
+    result
+}
+
 /// 获取这个拼音字符串中全部拼音组合，包含原始输入、全部字母组合、全部合法拼音组合
 ///
 /// 如果提供空串、一个字母的拼音串、超过 20 个字符的拼音串均不处理，原样返回
@@ -148,7 +262,10 @@ static PINYIN_VALID: phf::Set<&'static str> = phf_set! {
 
 #[cfg(test)]
 mod tests {
-    use crate::pinyin::{PINYIN_DIRT, get_pinyin, split_pinyin};
+    use crate::pinyin::{
+        PINYIN_DIRT, fuzzy_pinyin, get_pinyin, has_pinyin, join_pinyin, join_pinyin_with_separator,
+        pinyin_initials, pinyin_with_tone, split_pinyin, string_to_pinyin,
+    };
     use std::collections::BTreeSet;
 
     #[test]
@@ -174,6 +291,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_pinyin_traditional() {
+        // 繁体字和对应的简体字最常用的读音相同，多音字的其他读音可能不同
+        assert_eq!(
+            string_to_pinyin("国说门电学"),
+            string_to_pinyin("國說門電學")
+        );
+        assert_eq!(Some(vec!["guo".to_owned()]), get_pinyin(&'國'));
+    }
+
+    #[test]
+    fn test_get_pinyin_astral() {
+        // 扩展 B 区的汉字在 UTF-8 中占 4 个字节，拼音表同样覆盖
+        let ch = '\u{20001}';
+        assert_eq!(4, ch.len_utf8());
+        assert!(has_pinyin(&ch));
+        assert_eq!(vec!["qi".to_owned()], get_pinyin(&ch).unwrap());
+        assert_eq!(Some(["qī"].as_slice()), pinyin_with_tone(ch));
+        // 扩展 B 区中没有读音数据的码点
+        assert!(!has_pinyin(&'\u{20002}'));
+    }
+
+    #[test]
+    fn test_string_to_pinyin() {
+        assert_eq!(
+            vec![None, Some("guo"), None, Some("jia")],
+            string_to_pinyin("a国b家")
+        );
+        // 多音字只返回最常用的读音，标点和空白没有拼音
+        assert_eq!(
+            vec![Some("shuo"), Some("xing"), None, None],
+            string_to_pinyin("说行， ")
+        );
+        assert!(string_to_pinyin("").is_empty());
+    }
+
+    #[test]
+    fn test_join_pinyin() {
+        let readings = vec![
+            vec!["guo".to_owned()],
+            vec!["jia".to_owned(), "gu".to_owned()],
+        ];
+        assert_eq!(vec!["guojia", "guogu"], join_pinyin(&readings, 8));
+        // 超过上限的组合被丢弃
+        assert_eq!(vec!["guojia"], join_pinyin(&readings, 1));
+    }
+
+    #[test]
+    fn test_join_pinyin_with_separator() {
+        let readings = vec![
+            vec!["xi".to_owned()],
+            vec!["an".to_owned()],
+            vec!["shi".to_owned(), "si".to_owned()],
+        ];
+        assert_eq!(
+            vec!["xi'an'shi", "xi'an'si"],
+            join_pinyin_with_separator(&readings, "'", 8)
+        );
+        assert_eq!(
+            vec!["xianshi"],
+            join_pinyin_with_separator(&readings, "", 1)
+        );
+    }
+
+    #[test]
+    fn test_pinyin_with_tone() {
+        // 声调符号标在 a、e、i、o、u、ü 上的位置
+        assert_eq!(Some(&["mā"][..]), pinyin_with_tone('妈'));
+        assert_eq!(Some(&["è"][..]), pinyin_with_tone('饿'));
+        assert_eq!(Some(&["yī", "yí", "yì"][..]), pinyin_with_tone('一'));
+        assert_eq!(Some(&["wǒ"][..]), pinyin_with_tone('我'));
+        assert_eq!(
+            Some(&["bù", "fǒu", "fōu", "fū", "bú"][..]),
+            pinyin_with_tone('不')
+        );
+        assert_eq!(Some(&["nǚ", "nǜ", "rǔ"][..]), pinyin_with_tone('女'));
+        // 韵母有多个元音时，声调符号的位置
+        assert_eq!(Some(&["hǎo", "hào"][..]), pinyin_with_tone('好'));
+        assert_eq!(Some(&["duō"][..]), pinyin_with_tone('多'));
+        // 轻声不带声调符号，多音字返回全部读音
+        assert_eq!(Some(&["ma", "má", "mǎ"][..]), pinyin_with_tone('吗'));
+        assert_eq!(
+            Some(&["zhòng", "chóng", "tóng"][..]),
+            pinyin_with_tone('重')
+        );
+        assert_eq!(None, pinyin_with_tone('a'));
+    }
+
+    #[test]
+    fn test_fuzzy_pinyin() {
+        assert_eq!(Some("zi".to_owned()), fuzzy_pinyin("zhi"));
+        assert_eq!(Some("si".to_owned()), fuzzy_pinyin("shi"));
+        assert_eq!(Some("si".to_owned()), fuzzy_pinyin("si"));
+        assert_eq!(Some("nan".to_owned()), fuzzy_pinyin("lan"));
+        assert_eq!(Some("san".to_owned()), fuzzy_pinyin("shang"));
+        assert_eq!(Some("nin".to_owned()), fuzzy_pinyin("ling"));
+        assert_eq!(Some("cuan".to_owned()), fuzzy_pinyin("chuang"));
+        assert_eq!(Some("guo".to_owned()), fuzzy_pinyin("guo"));
+        assert_eq!(None, fuzzy_pinyin("sqlite"));
+    }
+
+    #[test]
+    fn test_pinyin_initials() {
+        let initials = pinyin_initials(&get_pinyin(&'重').unwrap());
+        assert_eq!(vec!["c", "ch", "t", "z", "zh"], initials);
+        let initials = pinyin_initials(&get_pinyin(&'国').unwrap());
+        assert_eq!(vec!["g"], initials);
+    }
+
     #[test]
     fn test_split_pinyin() {
         let input = "";
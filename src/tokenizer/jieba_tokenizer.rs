@@ -1,100 +1,2290 @@
-use crate::STOPWORD;
+use crate::pinyin::{join_pinyin, pinyin_readings};
 use crate::tokenizer::{
-    TokenizeReason, Tokenizer,
-    utils::{EN_STEMMER, is_space_or_ascii_punctuation_str, make_lowercase},
+    StemmerLanguage, TokenizeReason, Tokenizer,
+    utils::{
+        ConfigSummary, DEFAULT_MAX_TOKEN_BYTES, InvalidUtf8Policy, Ligatures, LossyText,
+        MAX_PINYIN_COMBINATIONS, NormalizationForm, NumberMode, OffsetTracker, StopwordCounter,
+        SymbolPolicy, TokenLimit, TokenPipeline, TokenTransform, VariationSelectors, dedup_tokens,
+        is_han, is_latin_word, is_punctuation_or_space_str, is_separator_str, is_symbol_str,
+        load_synonyms, merge_grouped_numbers, observe_tokens, parse_positive_arg,
+        parse_stemmer_arg, prefix_end, reject_unknown_args, shingles, skip_long_tokens,
+        symbol_ranges,
+    },
 };
+use crate::variants::fold_traditional;
 use jieba_rs::Jieba;
 use rusqlite::Error;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::ffi::CStr;
+use std::fs::File;
+use std::io::BufReader;
 use std::ops::Range;
-use std::sync::LazyLock;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::SystemTime;
 
-static JIEBA: LazyLock<Jieba> = LazyLock::new(Jieba::new);
+/// 全局共享的 jieba 实例，字典在第一次使用时加载，可以通过 [`release_jieba`] 释放
+static JIEBA: RwLock<Option<Arc<Jieba>>> = RwLock::new(None);
+
+/// 获取全局共享的 jieba 实例，如果字典还未加载或者已经被释放，则重新加载
+fn shared_jieba() -> Arc<Jieba> {
+    if let Some(jieba) = JIEBA
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+    {
+        return jieba.clone();
+    }
+    JIEBA
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get_or_insert_with(|| Arc::new(Jieba::new()))
+        .clone()
+}
+
+/// 预先加载全局共享的 jieba 字典，避免第一次分词时等待字典加载
+///
+/// 默认的字典在第一次使用 `jieba` 分词器时加载，release 构建下耗时约 250ms，常驻内存约 50MB，
+/// 服务在启动时调用这个函数，可以把加载的耗时放在启动阶段。字典已经加载时不做任何事，
+/// 调用 [`release_jieba`] 之后可以再次调用这个函数重新加载
+pub fn warm_jieba() {
+    shared_jieba();
+}
+
+/// 释放全局共享的 jieba 字典占用的内存，同时清空 `dict=` 参数和自定义词语创建的 jieba 实例缓存
+///
+/// 正在分词的 jieba 实例会在分词结束后释放，之后的分词会重新加载字典
+pub fn release_jieba() {
+    JIEBA.write().unwrap_or_else(PoisonError::into_inner).take();
+    JIEBA_DICTS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+}
+
+/// 字典的配置，配置相同的表共享同一个 jieba 实例
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct DictKey {
+    /// 字典文件的绝对路径，为 `None` 时使用 jieba 的默认字典
+    path: Option<PathBuf>,
+    /// 字典文件的修改时间，文件修改之后重新加载
+    modified: Option<SystemTime>,
+    /// 注册时添加的自定义词语，依次是词语、词频和词性
+    words: Vec<(String, Option<usize>, Option<String>)>,
+}
+
+/// 按照字典的配置缓存的 jieba 实例，同样的字典只加载一次，可以通过 [`release_jieba`] 释放
+static JIEBA_DICTS: Mutex<Option<HashMap<DictKey, Arc<Jieba>>>> = Mutex::new(None);
+
+/// 获取使用字典文件和自定义词语创建的 jieba 实例，配置相同时返回缓存的实例
+///
+/// 字典文件无法读取或者格式错误时返回 `None`，失败的结果不缓存
+fn cached_jieba_dict(
+    path: &str,
+    words: &[(String, Option<usize>, Option<String>)],
+) -> Option<Arc<Jieba>> {
+    let key = DictKey {
+        path: Some(std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))),
+        modified: std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok(),
+        words: words.to_vec(),
+    };
+    cached_jieba(key, || load_jieba_dict(path))
+}
+
+/// 按照字典的配置获取缓存的 jieba 实例，没有缓存时通过 `load` 加载字典，再添加自定义的词语
+///
+/// 加载失败时返回 `None`，失败的结果不缓存。字典文件修改之后，这个文件修改之前的实例从缓存中移除
+fn cached_jieba(key: DictKey, load: impl FnOnce() -> Option<Jieba>) -> Option<Arc<Jieba>> {
+    // 加载字典时持有锁，同时创建的多个表不会重复加载同一个字典
+    let mut dicts = JIEBA_DICTS.lock().unwrap_or_else(PoisonError::into_inner);
+    let dicts = dicts.get_or_insert_with(HashMap::new);
+    if let Some(jieba) = dicts.get(&key) {
+        return Some(jieba.clone());
+    }
+    let mut jieba = load()?;
+    for (word, freq, tag) in &key.words {
+        jieba.add_word(word, *freq, tag.as_deref());
+    }
+    dicts.retain(|cached, _| cached.path != key.path || cached.modified == key.modified);
+    let jieba = Arc::new(jieba);
+    dicts.insert(key, jieba.clone());
+    Some(jieba)
+}
+
+/// 使用全局共享的 jieba 实例分词，返回每个词语和 jieba 给出的词频，用于自动补全、统计等不需要写入索引的场景
+///
+/// 词频是 jieba 为词语建议的词频（`Jieba::suggest_freq`），字典中的词语和字典中记录的词频基本一致，
+/// 不在字典中的词语（例如 HMM 识别出的新词）词频为 0。空白和标点不返回，词语保持原样，不做归一化
+pub fn jieba_tokens_with_weight(text: &str) -> Vec<(String, usize)> {
+    let jieba = shared_jieba();
+    jieba
+        .cut(text, true)
+        .into_iter()
+        .filter(|word| !is_separator_str(word))
+        .map(|word| {
+            let weight = if jieba.has_word(word) {
+                jieba.suggest_freq(word)
+            } else {
+                0
+            };
+            (word.to_owned(), weight)
+        })
+        .collect()
+}
+
+/// 注册 jieba 分词器时提供的全局数据
+///
+/// 可以添加自定义的词语，例如产品名称、品牌名称，添加了词语时，会在第一次使用时创建一个包含这些词语的 jieba 实例，
+/// 自定义词语相同的注册共享同一个实例，可以通过 [`release_jieba`] 释放
+#[derive(Default)]
+pub struct JiebaGlobal {
+    /// 自定义的词语，依次是词语、词频和词性
+    words: Vec<(String, Option<usize>, Option<String>)>,
+    /// 自定义的 token 转换
+    transform: Option<TokenTransform>,
+}
+
+impl JiebaGlobal {
+    /// 使用自定义的词语创建全局数据，词频为 `None` 时由 jieba 计算一个能保证词语不被切分的词频
+    pub fn new(words: Vec<(String, Option<usize>, Option<String>)>) -> Self {
+        Self {
+            words,
+            transform: None,
+        }
+    }
+
+    /// 添加一个自定义的词语
+    pub fn add_word(&mut self, word: &str, freq: Option<usize>, tag: Option<&str>) {
+        self.words
+            .push((word.to_owned(), freq, tag.map(str::to_owned)));
+    }
+
+    /// 设置自定义的 token 转换，在归一化之后、停词和词干提取之前调用，返回 `None` 时不写入这个单词
+    pub fn set_transform(&mut self, transform: TokenTransform) {
+        self.transform = Some(transform);
+    }
+
+    /// 获取使用默认字典和自定义词语创建的 jieba 实例，没有自定义的词语时返回 `None`
+    fn jieba(&self) -> Option<Arc<Jieba>> {
+        if self.words.is_empty() {
+            return None;
+        }
+        let key = DictKey {
+            path: None,
+            modified: None,
+            words: self.words.clone(),
+        };
+        cached_jieba(key, || Some(Jieba::new()))
+    }
+}
+
+/// 从字典文件创建 jieba 实例，字典文件无法读取或者格式错误时返回 `None`，使用默认的字典
+fn load_jieba_dict(path: &str) -> Option<Jieba> {
+    let result = File::open(path)
+        .map_err(jieba_rs::Error::from)
+        .and_then(|file| Jieba::with_dict(&mut BufReader::new(file)));
+    match result {
+        Ok(jieba) => Some(jieba),
+        Err(error) => {
+            log::warn!(
+                "[sqlite-simple-tokenizer] failed to load jieba dictionary {path}, fall back to the default dictionary: {error}"
+            );
+            None
+        }
+    }
+}
+
+/// jieba 的分词模式
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CutMode {
+    /// 精确模式，每个字只属于一个词语
+    #[default]
+    Default,
+    /// 搜索引擎模式，在精确模式的基础上，将长词语中包含的短词语也切分出来
+    Search,
+}
+
+impl CutMode {
+    /// 从 `mode=` 参数的值中解析分词模式
+    fn from_arg(value: &str) -> Result<Self, Error> {
+        match value {
+            "default" => Ok(Self::Default),
+            "search" => Ok(Self::Search),
+            _ => Err(Error::ModuleError(format!(
+                "mode must be one of default, search, got {value}"
+            ))),
+        }
+    }
+}
 
 /// 使用 jieba 分词器
+///
+/// 默认使用精确模式分词，可以通过 `mode=search` 使用搜索引擎模式：长词语中包含的短词语也会写入，
+/// 例如 `中华人民共和国` 还会写入 `中华`、`人民`、`共和国` 等，查询短词语时可以匹配长词语，召回率更高，
+/// 但是索引体积更大，并且查询短词语时会匹配到更多不相关的文档，准确率更低
 pub struct JiebaTokenizer {
-    /// 是否启用停词表, 默认启用
-    enable_stopword: bool,
+    /// 单词的归一化、停词和词干提取
+    pipeline: TokenPipeline,
+    /// 本次分词中被停词表过滤的单词数量
+    stopword_hits: StopwordCounter,
+    /// 本次分词是前缀查询时，最后一个单词的结束位置，这个单词不检查停词
+    prefix_end: Option<usize>,
+    /// 写入文档时，是否在词干之外额外写入原本的单词，默认关闭
+    keep_original: bool,
+    /// 是否为包含繁体字的单词额外写入简体字形式，默认关闭
+    s2t_fold: bool,
+    /// 写入文档时，是否为汉字词语额外写入拼音，默认关闭
+    pinyin: bool,
+    /// 拉丁字母单词的 n-gram 片段长度，默认不生成片段
+    shingle: Option<usize>,
+    /// 同义词表，写入文档时为匹配的单词额外写入同一组中的其余同义词，默认为空
+    synonyms: HashMap<String, Vec<String>>,
+    /// 是否只分词，不做任何转换，默认关闭
+    raw_mode: bool,
+    /// 写入文档时，是否对整个文档中的 token 去重，默认关闭
+    dedup_document: bool,
+    /// token 的最大字节数，超过的 token 不写入，默认为 1024
+    max_token_bytes: usize,
+    /// 写入文档时最多写入的 token 数量，超过的部分不写入，默认不限制
+    max_tokens: Option<usize>,
+    /// 数字单词的处理方式，默认原样写入
+    numbers: NumberMode,
+    /// emoji 等符号的处理方式，默认写入
+    symbols: SymbolPolicy,
+    /// 文本中无效的 UTF-8 字节的处理方式，默认替换成 U+FFFD
+    invalid_utf8: InvalidUtf8Policy,
+    /// 连续汉字交给 jieba 分词的最大字符数，超出的部分逐字分词，默认不限制
+    char_budget: Option<usize>,
+    /// 这个分词器独立使用的 jieba 实例，为 `None` 时使用全局共享的 jieba 实例
+    jieba: Option<Arc<Jieba>>,
+    /// 是否使用 HMM 识别字典中没有的词语，默认启用
+    hmm: bool,
+    /// 分词模式，默认使用精确模式
+    mode: CutMode,
+    /// 允许写入的词性，为 `None` 时不按照词性过滤，默认不过滤
+    pos_filter: Option<HashSet<String>>,
 }
 
 impl Default for JiebaTokenizer {
     fn default() -> Self {
         Self {
-            enable_stopword: true,
+            pipeline: TokenPipeline::default(),
+            stopword_hits: StopwordCounter::default(),
+            prefix_end: None,
+            keep_original: false,
+            s2t_fold: false,
+            pinyin: false,
+            shingle: None,
+            synonyms: HashMap::new(),
+            raw_mode: false,
+            dedup_document: false,
+            max_token_bytes: DEFAULT_MAX_TOKEN_BYTES,
+            max_tokens: None,
+            numbers: NumberMode::Keep,
+            symbols: SymbolPolicy::Keep,
+            invalid_utf8: InvalidUtf8Policy::Lossy,
+            char_budget: None,
+            jieba: None,
+            hmm: true,
+            mode: CutMode::Default,
+            pos_filter: None,
         }
     }
 }
 
+impl std::fmt::Debug for JiebaTokenizer {
+    /// 输出 [`Tokenizer::config_summary`] 的配置摘要
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.config_summary())
+    }
+}
+
 impl JiebaTokenizer {
     /// 不启用停词表
     pub fn disable_stopword(&mut self) {
-        self.enable_stopword = false;
+        self.pipeline.stopword = false;
+    }
+    /// 使用停词表文件代替默认的停词表，只影响这个分词器
+    ///
+    /// 文件使用 UTF-8 编码，每行一个停词，忽略空行和以 `#` 开头的注释行，
+    /// 文件无法读取时，记录一条警告日志，继续使用默认的停词表
+    pub fn load_stopwords(&mut self, path: &str) {
+        self.pipeline.load_stopwords(path);
+    }
+    /// 在当前的停词表上添加停词，只影响这个分词器
+    ///
+    /// 不启用停词表时，只使用添加的停词，不再使用默认的停词表
+    pub fn add_stopwords<S: AsRef<str>>(&mut self, words: impl IntoIterator<Item = S>) {
+        self.pipeline.add_stopwords(words);
+    }
+    /// 当前生效的停词表，包括 `stopword=` 文件、`stopword_add=` 添加的停词以及作为基础的全局停词表
+    ///
+    /// 用于排查某个单词为什么没有写入索引，不启用停词表时为空
+    pub fn effective_stopwords(&self) -> Cow<'_, HashSet<String>> {
+        self.pipeline.effective_stopwords()
+    }
+    /// 不对英文单词做词干提取，只写入小写的单词，查询时只匹配相同的单词
+    pub fn disable_stemming(&mut self) {
+        self.pipeline.stemming = false;
+    }
+    /// 只在写入文档时做词干提取，查询时使用原本的单词
+    ///
+    /// 文档中的单词会被提取词干，查询的单词不会，因此只有查询的单词刚好是词干时才能匹配，
+    /// 例如文档中的 `liking` 写入 `like`，查询 `like` 可以匹配，查询 `liking` 反而不能匹配
+    pub fn enable_stem_documents_only(&mut self) {
+        self.pipeline.stem_documents_only = true;
+    }
+    /// 对词干提取的结果检查停词，默认在词干提取之前检查
+    ///
+    /// 词干提取可能把不是停词的单词变成停词，例如 `running` 的词干 `run`，
+    /// 开启后 `running` 不再写入，而停词 `does` 的词干 `doe` 不是停词，会被写入
+    pub fn enable_stopword_after_stem(&mut self) {
+        self.pipeline.stopword_after_stem = true;
+    }
+    /// 写入文档时，单词做了词干提取的，额外写入原本的小写单词，作为 colocated token 写入
+    ///
+    /// 和 `stem_documents_only` 一起使用时，查询原本的单词也可以完全匹配，例如查询 `running` 可以匹配 `running`
+    pub fn enable_keep_original(&mut self) {
+        self.keep_original = true;
+    }
+    /// 单词包含繁体字时，额外写入转换成简体字的单词，作为 colocated token 写入，繁体和简体可以互相匹配
+    ///
+    /// 使用 [`TRADITIONAL_TO_SIMPLIFIED`](crate::variants::TRADITIONAL_TO_SIMPLIFIED) 逐字转换，不做 `軟體` 和 `软件` 这样词语级别的转换
+    pub fn enable_s2t_fold(&mut self) {
+        self.s2t_fold = true;
+    }
+    /// 写入文档时，为汉字词语额外写入拼接的全拼和每个字的拼音，作为 colocated token 写入
+    ///
+    /// 例如 `国家` 额外写入 `guojia`、`guo`、`jia` 等拼音，多音字会列举全部读音，
+    /// 查询时不生成拼音，使用 `guojia`、`guo` 或者 `guo jia` 查询都可以匹配 `国家`。
+    /// 每个字的拼音和词语写入同一个位置，短语查询 `"guo jia"` 不能匹配，需要按照音节的顺序匹配时使用 `simple`
+    pub fn enable_pinyin(&mut self) {
+        self.pinyin = true;
+    }
+    /// 设置词干提取使用的语言，默认使用英语
+    pub fn set_stemmer(&mut self, language: StemmerLanguage) {
+        self.pipeline.stemmer = language;
+    }
+    /// 从文件加载同义词表，写入文档时为匹配的单词额外写入同一组中的其余同义词，作为 colocated token 写入
+    ///
+    /// 文件使用 UTF-8 编码，每行是一组用逗号分隔的同义词，忽略空行和以 `#` 开头的注释行，
+    /// 同义词和归一化后的单词比较，写入时和单词一样做词干提取，因此查询任意一个同义词都可以匹配其余的同义词。
+    /// 同义词应当是分词后的单个单词，文件无法读取时，记录一条警告日志，不扩展同义词
+    pub fn load_synonyms(&mut self, path: &str) {
+        self.synonyms = load_synonyms(path).unwrap_or_default();
+    }
+    /// 为拉丁字母单词额外生成 n 个字符的片段，作为 colocated token 写入，用于容错匹配
+    ///
+    /// 会显著增大索引体积
+    pub fn enable_shingle(&mut self, n: usize) {
+        self.shingle = Some(n);
+    }
+    /// 不使用 HMM 识别字典中没有的词语，只按照字典分词，分词结果只取决于字典
+    pub fn disable_hmm(&mut self) {
+        self.hmm = false;
+    }
+    /// 设置分词模式，默认使用精确模式
+    pub fn set_cut_mode(&mut self, mode: CutMode) {
+        self.mode = mode;
+    }
+    /// 只写入指定词性的单词，例如 `n`、`v`，用于只索引名词、动词这些实词，去掉助词、语气词
+    ///
+    /// 词性使用 jieba 的标注，以指定的词性开头的标注都可以写入，例如 `n` 包括 `nr`、`ns`，
+    /// 英文单词的词性是 `eng`，数字的词性是 `m`。开启后使用 jieba 的词性标注分词，总是使用精确模式
+    pub fn set_pos_filter<S: AsRef<str>>(&mut self, tags: impl IntoIterator<Item = S>) {
+        let tags = tags
+            .into_iter()
+            .map(|tag| tag.as_ref().trim().to_owned())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        self.pos_filter = Some(tags);
+    }
+    /// 只分词，原样写入 jieba 的分词结果，不做归一化、停词和词干提取，其余配置均不生效
+    pub fn enable_raw_mode(&mut self) {
+        self.raw_mode = true;
+    }
+    /// 写入文档时，每个不同的 token 只写入一次，用于减小 `detail=none` 索引的体积
+    ///
+    /// 重复的 token 不再写入，token 的位置随之改变，短语查询和 NEAR 查询的结果将不再准确
+    pub fn enable_dedup_document(&mut self) {
+        self.dedup_document = true;
+    }
+    /// 设置 token 的最大字节数，超过的 token 不写入，避免超长的 token 增大索引体积
+    pub fn set_max_token_bytes(&mut self, max_bytes: usize) {
+        self.max_token_bytes = max_bytes;
+    }
+    /// 设置写入文档时最多写入的 token 数量，避免超长的文档产生大量的 token，拖慢写入
+    ///
+    /// 达到上限后停止分词并记录一条警告日志，文档仍然可以写入，只有前面的 token 可以查询，
+    /// 同一位置上的拼音等 token 不计入数量，查询的文本不受限制
+    pub fn set_max_tokens(&mut self, max_tokens: usize) {
+        self.max_tokens = Some(max_tokens);
+    }
+    /// 设置 token 的最少字符数，归一化后不足的单词不写入，用于过滤 `can't` 中的 `t` 这样的片段
+    ///
+    /// 单个汉字也有独立的含义，包含汉字的单词不受影响
+    pub fn set_min_token_chars(&mut self, min_chars: usize) {
+        self.pipeline.min_token_chars = min_chars;
+    }
+    /// 设置数字单词的处理方式，默认原样写入
+    ///
+    /// 数字是只包含数字、千位分隔符和一个小数点的单词，例如 `32.3`、`1,000`，`1990s` 这样的单词不是数字
+    pub fn set_numbers(&mut self, mode: NumberMode) {
+        self.numbers = mode;
+    }
+    /// 设置 emoji、`©`、`★` 等符号的处理方式，默认写入
+    ///
+    /// jieba 会把零宽连接符组合的 emoji 序列切分成单个的 emoji 和连接符，写入之前按照扩展字素簇重新合并，
+    /// `👨‍👩‍👧` 作为一个 token 写入，[`SymbolPolicy::Drop`] 时和标点一样不写入
+    pub fn set_symbols(&mut self, symbols: SymbolPolicy) {
+        self.symbols = symbols;
+    }
+    /// 设置文本中无效的 UTF-8 字节的处理方式，默认替换成 U+FFFD
+    ///
+    /// `skip` 跳过无效的字节，`error` 使包含无效字节的写入和查询失败，适用于不应该出现二进制数据的字段
+    pub fn set_invalid_utf8(&mut self, policy: InvalidUtf8Policy) {
+        self.invalid_utf8 = policy;
+    }
+    /// 设置 Unicode 归一化的形式，默认为 NFKC
+    pub fn set_normalization(&mut self, form: NormalizationForm) {
+        self.pipeline.set_normalization(form);
+    }
+    /// 设置连字的处理方式，连字在归一化之前处理，默认不单独处理
+    pub fn set_ligatures(&mut self, ligatures: Ligatures) {
+        self.pipeline.set_ligatures(ligatures);
+    }
+    /// 保留组合附加符号，例如分解形式 `e\u{301}` 中的 `\u{301}`，默认去掉
+    ///
+    /// 默认的 NFKC 归一化会把能组合的附加符号合并成 `é` 这样的字符，合并后的字符不受影响，
+    /// 这个选项主要影响 `normalization=none` 时的分解形式，以及没有组合字符的附加符号
+    pub fn enable_keep_diacritics(&mut self) {
+        self.pipeline.enable_keep_diacritics();
+    }
+    /// 使用完整的 Unicode 大小写折叠代替小写转换，例如 `ß` 折叠成 `ss`、`ς` 折叠成 `σ`
+    ///
+    /// jieba 只把连续的 ascii 字母作为一个单词，`Straße` 会被切分成 `Stra`、`ß`、`e`，
+    /// 折叠只作用于切分后的每个片段，需要 `Straße` 匹配 `strasse` 时使用 `simple casefold`
+    pub fn enable_casefold(&mut self) {
+        self.pipeline.enable_casefold();
+    }
+    /// 设置汉字异体字选择符的处理方式，默认去掉异体字选择符
+    pub fn set_variation_selectors(&mut self, ivs: VariationSelectors) {
+        self.pipeline.set_variation_selectors(ivs);
+    }
+    /// 使用字典文件创建独立的 jieba 实例，只使用这个字典分词，不影响其他的表
+    ///
+    /// 字典文件的格式和 jieba 的默认字典相同，每行是 `词语 词频 词性`，
+    /// 字典文件无法读取或者格式错误时，记录一条警告日志，继续使用默认的字典。
+    /// 使用同一个字典文件的表共享同一个 jieba 实例，字典只加载一次
+    pub fn load_dict(&mut self, path: &str) {
+        self.jieba = cached_jieba_dict(path, &[]);
+    }
+    /// 限制连续汉字交给 jieba 分词的字符数，用于限制超长的汉字文本的分词耗时
+    ///
+    /// 连续汉字超过 `budget` 个字符时，超出的部分不再使用 jieba 分词，而是逐字写入，
+    /// 同时将相邻两个字组成的片段作为 colocated token 写入
+    pub fn set_char_budget(&mut self, budget: usize) {
+        self.char_budget = Some(budget);
+    }
+
+    /// 对文本分词，并将每个 token 交给 `push_token` 处理
+    fn tokenize_words<TKF>(
+        &self,
+        reason: TokenizeReason,
+        text: &str,
+        mut push_token: TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        let mut word_buf = String::new();
+        let jieba = self.jieba.clone().unwrap_or_else(shared_jieba);
+        let segments = match self.char_budget {
+            Some(budget) => budget_segments(text, budget),
+            None => vec![(0..text.len(), false)],
+        };
+        for (segment, overflow) in segments {
+            if overflow {
+                self.push_overflow(
+                    reason.clone(),
+                    text,
+                    segment,
+                    &mut word_buf,
+                    &mut push_token,
+                )?;
+                continue;
+            }
+            // 使用 jieba 进行分词，sqlite 要求的是 byte 偏移量
+            let segment_text = &text[segment.clone()];
+            let words = match (&self.pos_filter, self.mode) {
+                (Some(pos_filter), _) => jieba
+                    .tag(segment_text, self.hmm)
+                    .into_iter()
+                    .filter(|tag| pos_allowed(pos_filter, tag.tag))
+                    .map(|tag| tag.word)
+                    .collect(),
+                (None, CutMode::Default) => jieba.cut(segment_text, self.hmm),
+                (None, CutMode::Search) => jieba.cut_for_search(segment_text, self.hmm),
+            };
+            // 搜索引擎模式的结果互相重叠，不能依次累加长度，jieba 返回的是原始文本的切片，
+            // 通过切片的地址计算它在片段中的位置
+            let mut tracker = OffsetTracker::new(segment_text);
+            let mut ranges = words
+                .iter()
+                .filter_map(|word| tracker.range_of(word))
+                .map(|range| segment.start + range.start..segment.start + range.end)
+                .collect::<Vec<_>>();
+            if self.numbers == NumberMode::Normalize {
+                // jieba 会在千位分隔符处切分数字，归一化之前先合并
+                ranges = merge_grouped_numbers(text, ranges);
+            }
+            let ranges = symbol_words(text, ranges, self.symbols);
+            let groups = word_groups(&ranges);
+            // 同一个词语切分出的 token 写入同一个位置，第一个写入的 token 之后的 token 作为 colocated token 写入
+            let mut pushed = false;
+            for (index, range) in ranges.into_iter().enumerate() {
+                let word = &text[range.clone()];
+                if index > 0 && groups[index] != groups[index - 1] {
+                    pushed = false;
+                }
+                pushed |= self.push_word(
+                    reason.clone(),
+                    word,
+                    range,
+                    pushed,
+                    &mut word_buf,
+                    &mut push_token,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 逐字写入超出字符数限制的汉字，并将相邻两个字组成的片段作为 colocated token 写入
+    fn push_overflow<TKF>(
+        &self,
+        reason: TokenizeReason,
+        text: &str,
+        segment: Range<usize>,
+        word_buf: &mut String,
+        push_token: &mut TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        let mut chars = text[segment.clone()].char_indices().peekable();
+        while let Some((offset, ch)) = chars.next() {
+            let start = segment.start + offset;
+            let range = start..start + ch.len_utf8();
+            // 这个字没有写入时（停词、字符数不足、转换后不写入），两个字组成的片段写入新的位置
+            let colocated = self.push_word(
+                reason.clone(),
+                &text[range.clone()],
+                range.clone(),
+                false,
+                word_buf,
+                push_token,
+            )?;
+            let Some(&(_, next)) = chars.peek() else {
+                continue;
+            };
+            let range = start..range.end + next.len_utf8();
+            let bigram = &text[range.clone()];
+            if self.raw_mode {
+                (push_token)(bigram.as_bytes(), range, colocated)?;
+                continue;
+            }
+            self.pipeline.normalize(bigram, word_buf);
+            if !word_buf.is_empty() && !self.pipeline.is_stopword(word_buf) {
+                (push_token)(word_buf.as_bytes(), range, colocated)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 对 jieba 切分出的单词做归一化、停词、词干提取等处理后写入
+    ///
+    /// `colocated` 为 `true` 时，表示这个单词和前一个 token 写入同一个位置，返回是否写入了 token
+    fn push_word<TKF>(
+        &self,
+        reason: TokenizeReason,
+        word: &str,
+        range: Range<usize>,
+        colocated: bool,
+        word_buf: &mut String,
+        push_token: &mut TKF,
+    ) -> Result<bool, Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        if self.raw_mode {
+            // 符号已经按照 symbols 的设置处理过，这里只去掉空白和标点
+            if is_punctuation_or_space_str(word) && !is_symbol_str(word) {
+                return Ok(false);
+            }
+            (push_token)(word.as_bytes(), range, colocated)?;
+            return Ok(true);
+        }
+        // 如果是空字符、控制字符、标点字符组成的字符串，也不处理，包括 jieba 单独切分出的全角标点
+        if is_separator_str(word) {
+            return Ok(false);
+        }
+        // 对单词做归一化处理，并且将单词转换成小写
+        let mut ascii = self.pipeline.normalize(word, word_buf);
+        if word_buf.is_empty() {
+            // 归一化后为空串，例如只包含方向控制字符
+            return Ok(false);
+        }
+        if !self.pipeline.transform(word_buf, &mut ascii) {
+            // 自定义的转换不写入这个单词
+            return Ok(false);
+        }
+        let need_stem = self.pipeline.need_stem(&reason, ascii, word_buf);
+        if self.pipeline.is_too_short(word_buf) {
+            // 少于 min_token_chars 个字符的单词不写入
+            return Ok(false);
+        }
+        if !self.numbers.apply(word_buf) {
+            // 开启 numbers=drop 时不写入数字
+            return Ok(false);
+        }
+        // 前缀查询的最后一个单词不完整，不检查停词
+        if self.prefix_end != Some(range.end)
+            && self
+                .stopword_hits
+                .record(self.pipeline.is_stemmed_stopword(word_buf, need_stem))
+        {
+            // 不处理停词
+            return Ok(false);
+        }
+        if need_stem {
+            // 词干提取没有改变单词时借用 word_buf，不需要分配内存
+            let stemmed = self.pipeline.stem(word_buf);
+            (push_token)(stemmed.as_bytes(), range.clone(), colocated)?;
+            if self.keep_original
+                && stemmed != *word_buf
+                && !matches!(reason, TokenizeReason::Query { .. })
+            {
+                (push_token)(word_buf.as_bytes(), range.clone(), true)?;
+            }
+        } else {
+            (push_token)(word_buf.as_bytes(), range.clone(), colocated)?;
+        }
+        if self.s2t_fold
+            && let Some(simplified) = fold_traditional(word_buf)
+        {
+            // 查询时也写入简体字，作为单词的另一种形式，查询繁体字可以匹配简体字的文档
+            (push_token)(simplified.as_bytes(), range.clone(), true)?;
+        }
+        if !matches!(reason, TokenizeReason::Query { .. })
+            && let Some(synonyms) = self.synonyms.get(word_buf.as_str())
+        {
+            for synonym in synonyms {
+                // 查询的同义词会和单词一样做词干提取，这里保持一致
+                if self
+                    .pipeline
+                    .need_stem(&reason, synonym.is_ascii(), synonym)
+                {
+                    let stemmed = self.pipeline.stem(synonym);
+                    (push_token)(stemmed.as_bytes(), range.clone(), true)?;
+                } else {
+                    (push_token)(synonym.as_bytes(), range.clone(), true)?;
+                }
+            }
+        }
+        if let Some(n) = self.shingle
+            && is_latin_word(word_buf)
+        {
+            for shingle in shingles(word_buf, n) {
+                (push_token)(shingle.as_bytes(), range.clone(), true)?;
+            }
+        }
+        if self.pinyin && !matches!(reason, TokenizeReason::Query { .. }) {
+            self.push_pinyin(word_buf, range, push_token)?;
+        }
+        Ok(true)
+    }
+
+    /// 为每个字都有拼音的汉字词语写入拼接的全拼和每个字的拼音，作为 colocated token 写入
+    fn push_pinyin<TKF>(
+        &self,
+        word: &str,
+        range: Range<usize>,
+        push_token: &mut TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        let Some(readings) = word
+            .chars()
+            .map(|ch| pinyin_readings(ch).map(Iterator::collect::<Vec<_>>))
+            .collect::<Option<Vec<_>>>()
+        else {
+            // 包含没有拼音的字符，例如拉丁字母和数字
+            return Ok(());
+        };
+        if readings.len() > 1 {
+            for pinyin in join_pinyin(&readings, MAX_PINYIN_COMBINATIONS) {
+                (push_token)(pinyin.as_bytes(), range.clone(), true)?;
+            }
+        }
+        // 多个字的读音相同时只写入一次，例如 `家家`
+        let mut seen = HashSet::new();
+        for pinyin in readings.iter().flatten() {
+            if seen.insert(*pinyin) {
+                (push_token)(pinyin.as_bytes(), range.clone(), true)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 词性标注是否以允许的词性开头，例如允许 `n` 时，`nr`、`ns` 都可以写入
+fn pos_allowed(pos_filter: &HashSet<String>, tag: &str) -> bool {
+    pos_filter
+        .iter()
+        .any(|allowed| tag.starts_with(allowed.as_str()))
+}
+
+/// 按照符号的处理方式调整 jieba 的分词结果
+///
+/// 相邻的只包含符号的单词先合并，再按照扩展字素簇切分，零宽连接符组合的 emoji 序列成为一个单词，
+/// [`SymbolPolicy::Drop`] 时去掉这些单词，其余单词保持原样
+fn symbol_words(text: &str, ranges: Vec<Range<usize>>, symbols: SymbolPolicy) -> Vec<Range<usize>> {
+    let mut words = Vec::with_capacity(ranges.len());
+    let mut run: Option<Range<usize>> = None;
+    for range in ranges {
+        if is_symbol_str(&text[range.clone()]) {
+            match &mut run {
+                Some(run) if run.end == range.start => run.end = range.end,
+                _ => {
+                    if let Some(run) = run.replace(range) {
+                        words.extend(symbol_run(text, run, symbols));
+                    }
+                }
+            }
+            continue;
+        }
+        if let Some(run) = run.take() {
+            words.extend(symbol_run(text, run, symbols));
+        }
+        words.push(range);
+    }
+    if let Some(run) = run {
+        words.extend(symbol_run(text, run, symbols));
+    }
+    words
+}
+
+/// 按照符号的处理方式切分连续的符号
+fn symbol_run(text: &str, run: Range<usize>, symbols: SymbolPolicy) -> Vec<Range<usize>> {
+    match symbols {
+        SymbolPolicy::Keep => symbol_ranges(text, run),
+        SymbolPolicy::Drop => Vec::new(),
+    }
+}
+
+/// 将 jieba 的分词结果按照所属的词语分组，返回每个 token 的组号
+///
+/// 搜索引擎模式先返回长词语中包含的短词语，最后返回长词语本身，因此从后往前遍历，
+/// 被当前长词语包含的 token 属于同一组，精确模式的 token 互不重叠，每个 token 单独一组
+fn word_groups(ranges: &[Range<usize>]) -> Vec<usize> {
+    let mut groups = vec![0; ranges.len()];
+    let mut group = 0;
+    let mut current: Option<&Range<usize>> = None;
+    for (index, range) in ranges.iter().enumerate().rev() {
+        match current {
+            Some(word) if word.start <= range.start && range.end <= word.end => {}
+            _ => {
+                group += 1;
+                current = Some(range);
+            }
+        }
+        groups[index] = group;
+    }
+    groups
+}
+
+/// 按照字符数限制切分文本，返回每个片段的字节范围，以及这个片段是否是超出限制的汉字
+///
+/// 连续汉字的前 `budget` 个字符和其余文本一起交给 jieba 分词，超出的部分单独成为一个片段，
+/// 片段首尾相接，覆盖整个文本，因此每个片段内的字节偏移量加上片段的起始位置就是原始文本中的位置
+fn budget_segments(text: &str, budget: usize) -> Vec<(Range<usize>, bool)> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut run_len = 0;
+    let mut overflow = false;
+    for (index, ch) in text.char_indices() {
+        if is_han(ch) {
+            run_len += 1;
+            if run_len == budget + 1 {
+                if index > start {
+                    segments.push((start..index, false));
+                }
+                start = index;
+                overflow = true;
+            }
+        } else {
+            run_len = 0;
+            if overflow {
+                segments.push((start..index, true));
+                start = index;
+                overflow = false;
+            }
+        }
     }
+    if text.len() > start {
+        segments.push((start..text.len(), overflow));
+    }
+    segments
 }
 
 impl Tokenizer for JiebaTokenizer {
-    type Global = ();
+    type Global = JiebaGlobal;
 
     fn name() -> &'static CStr {
         c"jieba"
     }
 
-    fn new(_global: &Self::Global, args: Vec<String>) -> Result<Self, Error> {
+    /// 总是包含停词和词干提取的开关，其余参数只在和默认值不同时出现
+    fn config_summary(&self) -> String {
+        let mut summary = ConfigSummary::new(Self::name());
+        self.pipeline.summarize(&mut summary);
+        summary
+            .flag("hmm", self.hmm, true)
+            .option("mode", self.mode, CutMode::Default)
+            .flag("dict", self.jieba.is_some(), false);
+        if let Some(tags) = &self.pos_filter {
+            // 参数中的词性使用逗号分隔，摘要中改用 `|` 分隔，避免和摘要的分隔符混淆
+            let mut tags = tags.iter().map(String::as_str).collect::<Vec<_>>();
+            tags.sort_unstable();
+            summary.field("pos_filter", tags.join("|"));
+        }
+        if let Some(budget) = self.char_budget {
+            summary.field("jieba_char_budget", budget);
+        }
+        summary
+            .flag("keep_original", self.keep_original, false)
+            .flag("s2t_fold", self.s2t_fold, false)
+            .flag("pinyin", self.pinyin, false);
+        if let Some(n) = self.shingle {
+            summary.field("shingle", n);
+        }
+        if !self.synonyms.is_empty() {
+            summary.field("synonyms", self.synonyms.len());
+        }
+        summary
+            .flag("raw_mode", self.raw_mode, false)
+            .flag("dedup_document", self.dedup_document, false)
+            .option("numbers", self.numbers, NumberMode::Keep)
+            .option("symbols", self.symbols, SymbolPolicy::Keep)
+            .option("invalid_utf8", self.invalid_utf8, InvalidUtf8Policy::Lossy);
+        if self.max_token_bytes != DEFAULT_MAX_TOKEN_BYTES {
+            summary.field("max_token_bytes", self.max_token_bytes);
+        }
+        if let Some(n) = self.max_tokens {
+            summary.field("max_tokens", n);
+        }
+        summary.finish()
+    }
+
+    fn new(global: &Self::Global, args: Vec<String>) -> Result<Self, Error> {
         let mut tokenizer = Self::default();
+        // 全局停词表在创建时获取一次，之后替换不影响这个实例，重新分词已经写入的文档时结果一致
+        tokenizer.pipeline.refresh_stopwords();
+        tokenizer.pipeline.set_transform(global.transform.clone());
+        let mut stopword_add = Vec::new();
+        let mut unknown = Vec::new();
+        let mut dict = None;
         for arg in args {
-            if arg.as_str() == "disable_stopword" {
-                tokenizer.disable_stopword();
+            match arg.split_once('=') {
+                Some(("stemmer", language)) => {
+                    tokenizer.set_stemmer(parse_stemmer_arg(language)?);
+                }
+                Some(("shingle", n)) => {
+                    tokenizer.enable_shingle(parse_positive_arg("shingle", n)?);
+                }
+                Some(("max_token_bytes", n)) => {
+                    tokenizer.set_max_token_bytes(parse_positive_arg("max_token_bytes", n)?);
+                }
+                Some(("max_tokens", n)) => {
+                    tokenizer.set_max_tokens(parse_positive_arg("max_tokens", n)?);
+                }
+                Some(("min_token_chars", n)) => {
+                    tokenizer.set_min_token_chars(parse_positive_arg("min_token_chars", n)?);
+                }
+                Some(("normalization", form)) => {
+                    tokenizer.set_normalization(NormalizationForm::from_arg(form)?);
+                }
+                Some(("numbers", mode)) => {
+                    tokenizer.set_numbers(NumberMode::from_arg(mode)?);
+                }
+                Some(("symbols", symbols)) => {
+                    tokenizer.set_symbols(SymbolPolicy::from_arg(symbols)?);
+                }
+                Some(("invalid_utf8", policy)) => {
+                    tokenizer.set_invalid_utf8(InvalidUtf8Policy::from_arg(policy)?);
+                }
+                Some(("ligatures", ligatures)) => {
+                    tokenizer.set_ligatures(Ligatures::from_arg(ligatures)?);
+                }
+                Some(("ivs", ivs)) => {
+                    tokenizer.set_variation_selectors(VariationSelectors::from_arg(ivs)?);
+                }
+                Some(("stopword", path)) => {
+                    tokenizer.load_stopwords(path);
+                }
+                Some(("stopword_add", word)) => {
+                    stopword_add.push(word.to_owned());
+                }
+                Some(("synonyms", path)) => {
+                    tokenizer.load_synonyms(path);
+                }
+                Some(("dict", path)) => {
+                    dict = Some(path.to_owned());
+                }
+                Some(("mode", mode)) => {
+                    tokenizer.set_cut_mode(CutMode::from_arg(mode)?);
+                }
+                Some(("pos_filter", tags)) => {
+                    if tags.trim().is_empty() {
+                        return Err(Error::ModuleError(
+                            "pos_filter must not be empty".to_owned(),
+                        ));
+                    }
+                    tokenizer.set_pos_filter(tags.split(','));
+                }
+                Some(("jieba_char_budget", n)) => {
+                    tokenizer.set_char_budget(parse_positive_arg("jieba_char_budget", n)?);
+                }
+                _ => match arg.as_str() {
+                    "disable_stopword" => {
+                        tokenizer.disable_stopword();
+                    }
+                    "disable_stemming" => {
+                        tokenizer.disable_stemming();
+                    }
+                    "keep_diacritics" => {
+                        tokenizer.enable_keep_diacritics();
+                    }
+                    "casefold" => {
+                        tokenizer.enable_casefold();
+                    }
+                    "stem_documents_only" => {
+                        tokenizer.enable_stem_documents_only();
+                    }
+                    "stopword_after_stem" => {
+                        tokenizer.enable_stopword_after_stem();
+                    }
+                    "keep_original" => {
+                        tokenizer.enable_keep_original();
+                    }
+                    "s2t_fold" => {
+                        tokenizer.enable_s2t_fold();
+                    }
+                    "pinyin" => {
+                        tokenizer.enable_pinyin();
+                    }
+                    "raw_mode" => {
+                        tokenizer.enable_raw_mode();
+                    }
+                    "disable_hmm" => {
+                        tokenizer.disable_hmm();
+                    }
+                    "dedup_document" => {
+                        tokenizer.enable_dedup_document();
+                    }
+                    _ => unknown.push(arg.clone()),
+                },
             }
         }
+        reject_unknown_args("jieba", &unknown)?;
+        if !stopword_add.is_empty() {
+            // 所有参数处理完之后再合并，结果和参数的顺序无关
+            tokenizer.add_stopwords(stopword_add);
+        }
+        // 使用自定义字典时，同样添加自定义的词语，字典无法加载时使用默认的字典
+        tokenizer.jieba = dict
+            .and_then(|path| cached_jieba_dict(&path, &global.words))
+            .or_else(|| global.jieba());
         Ok(tokenizer)
     }
 
     fn tokenize<TKF>(
         &mut self,
-        _reason: TokenizeReason,
+        reason: TokenizeReason,
         text: &[u8],
-        mut push_token: TKF,
+        push_token: TKF,
     ) -> Result<(), Error>
     where
         TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
     {
-        let text = String::from_utf8_lossy(text);
-        // 使用 jieba 进行分词
-        let mut word_buf = String::new();
-        let mut index = 0_usize;
-        for word in JIEBA.cut(text.as_ref(), true) {
-            // sqlite 要求的是 byte 偏移量
-            let range = index..index + word.len();
-            index += word.len();
-            // 如果是空字符、控制字符、ascii标点字符组成组成的字符串，也不处理
-            if is_space_or_ascii_punctuation_str(word) {
-                continue;
-            }
-            // 对单词做归一化处理，并且将单词转换成小写
-            let need_stem = make_lowercase(word, &mut word_buf);
-            if self.enable_stopword && STOPWORD.contains(word_buf.as_str()) {
-                // 不处理停词
-                continue;
-            }
-            if need_stem {
-                let stemmed = EN_STEMMER.stem(word_buf.as_str()).into_owned();
-                (push_token)(stemmed.as_bytes(), range, false)?;
-            } else {
-                (push_token)(word_buf.as_bytes(), range, false)?;
-            }
-        }
-        Ok(())
+        let mut push_token = observe_tokens("jieba", push_token);
+        self.stopword_hits.reset();
+        // 分词得到的是转换后字符串中的位置，写入之前转换成原始字节中的位置
+        let text = LossyText::with_policy(text, self.invalid_utf8)?;
+        self.prefix_end = prefix_end(&reason, text.as_str());
+        // 达到 token 数量的上限后停止分词，已经写入的 token 仍然有效
+        let limit = TokenLimit::new(self.max_tokens, &reason);
+        let push_token = skip_long_tokens(
+            self.max_token_bytes,
+            limit.wrap(|token: &[u8], range: Range<usize>, colocated: bool| {
+                (push_token)(token, text.original_range(range), colocated)
+            }),
+        );
+        let result = if self.dedup_document && reason == TokenizeReason::Document {
+            // 同一个 token 在文档中只写入一次
+            self.tokenize_words(reason, text.as_str(), dedup_tokens(push_token))
+        } else {
+            self.tokenize_words(reason, text.as_str(), push_token)
+        };
+        limit.finish("jieba", result)
+    }
+
+    fn stopwords_filtered(&self) -> usize {
+        self.stopword_hits.get()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::JIEBA;
+    use super::{
+        JIEBA, JIEBA_DICTS, budget_segments, jieba_tokens_with_weight, release_jieba, shared_jieba,
+        warm_jieba,
+    };
+    use crate::tokenizer::jieba_tokenizer::{JiebaGlobal, JiebaTokenizer};
+    use crate::tokenizer::standalone::tokenize_with_positions;
+    use crate::tokenizer::tests::query_texts;
+    use crate::tokenizer::{TokenizeReason, Tokenizer, register_tokenizer};
+    use rusqlite::Connection;
+    use std::sync::{Arc, Mutex, PoisonError};
+    use unicode_normalization::UnicodeNormalization;
+
+    /// 调用 [`release_jieba`] 会清空字典缓存，检查缓存的测试需要和释放的测试互斥
+    static RELEASE_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_jieba_tokenizer_with_shingle() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = \"jieba 'shingle=3'\");",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            r#"INSERT INTO t1(text) VALUES ('I''m making a sqlite tokenizer'),('社会主义国家');"#,
+            [],
+        )
+        .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'tokenizr';");
+        assert_eq!(["I'm making a sqlite tokenizer"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_budget_segments() {
+        // 每个汉字 3 个字节，第 4 个汉字开始超出限制，非汉字的字符结束超出的部分
+        assert_eq!(
+            vec![(0..9, false), (9..21, true), (21..31, false)],
+            budget_segments("中华人民共和国 abc中国", 3)
+        );
+        assert_eq!(vec![(0..6, false)], budget_segments("中国", 3));
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_char_budget() {
+        let tokenize = |args: Vec<String>, text: &str| {
+            tokenize_with_positions::<JiebaTokenizer>(
+                &JiebaGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+        };
+        let args = vec![
+            "jieba_char_budget=2".to_owned(),
+            "disable_stopword".to_owned(),
+        ];
+        // 前两个字交给 jieba 分词，其余的字逐字写入，相邻两个字组成 colocated token
+        let tokens = tokenize(args, "中国人民银行")
+            .into_iter()
+            .map(|t| (String::from_utf8(t.token).unwrap(), t.range, t.colocated))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("中国".to_owned(), 0..6, false),
+                ("人".to_owned(), 6..9, false),
+                ("人民".to_owned(), 6..12, true),
+                ("民".to_owned(), 9..12, false),
+                ("民银".to_owned(), 9..15, true),
+                ("银".to_owned(), 12..15, false),
+                ("银行".to_owned(), 12..18, true),
+                ("行".to_owned(), 15..18, false),
+            ],
+            tokens
+        );
+        // 停词没有写入时，两个字组成的片段写入新的位置，第一个 token 不是 colocated token
+        let args = vec![
+            "jieba_char_budget=2".to_owned(),
+            "disable_stopword".to_owned(),
+            "stopword_add=中国".to_owned(),
+            "stopword_add=人".to_owned(),
+        ];
+        let tokens = tokenize(args, "中国人民")
+            .into_iter()
+            .map(|t| (String::from_utf8(t.token).unwrap(), t.position, t.colocated))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![("人民".to_owned(), 0, false), ("民".to_owned(), 1, false),],
+            tokens
+        );
+        // 超长的汉字文本，超出的部分不再交给 jieba，偏移量仍然对应原始文本
+        let text = format!("{}sqlite", "社会主义国家".repeat(50_000));
+        let args = vec![
+            "jieba_char_budget=1000".to_owned(),
+            "disable_stopword".to_owned(),
+        ];
+        let tokens = tokenize(args, &text);
+        for token in &tokens {
+            assert_eq!(token.token, text[token.range.clone()].as_bytes());
+        }
+        let last = tokens.last().unwrap();
+        assert_eq!(b"sqlite", last.token.as_slice());
+        assert_eq!(text.len() - 6..text.len(), last.range);
+        // 超出限制的每个字都逐字写入
+        let overflow = tokens.iter().filter(|t| t.range.len() == 3).count();
+        assert!(overflow >= 300_000 - 1000);
+        let result = tokenize_with_positions::<JiebaTokenizer>(
+            &JiebaGlobal::default(),
+            vec!["jieba_char_budget=0".to_owned()],
+            TokenizeReason::Document,
+            b"",
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_stopword() {
+        let dir = std::env::temp_dir().join(format!("jieba_stopword_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let stopword = dir.join("stopword.txt");
+        std::fs::write(&stopword, "# 自定义停词表\n国家\n").unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        for (table, tokenize) in [
+            ("t1", "jieba".to_owned()),
+            ("t2", format!("jieba 'stopword={}'", stopword.display())),
+        ] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = \"{tokenize}\");"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('伟大的国家');"),
+                [],
+            )
+            .unwrap();
+        }
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '国家';");
+        assert_eq!(["伟大的国家"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '国家';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '伟大';");
+        assert_eq!(["伟大的国家"], vec.as_slice());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_synonyms() {
+        let dir = std::env::temp_dir().join(format!("jieba_synonyms_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let synonyms = dir.join("synonyms.txt");
+        std::fs::write(&synonyms, "美国,USA,America\n").unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        conn.execute(
+            &format!(
+                "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = \"jieba 'synonyms={}'\");",
+                synonyms.display()
+            ),
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('美国的首都'),('USA'),('中国');",
+            [],
+        )
+        .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'america';");
+        assert_eq!(["美国的首都", "USA"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '美国';");
+        assert_eq!(["美国的首都", "USA"], vec.as_slice());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_stem_documents_only() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'jieba disable_stopword stem_documents_only');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('like'),('liking'),('liked');",
+            [],
+        )
+        .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'like';");
+        assert_eq!(["like", "liking", "liked"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'liking';");
+        assert_eq!(0, vec.len());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_stopword_after_stem() {
+        let tokenize = |args: Vec<String>| {
+            tokenize_with_positions::<JiebaTokenizer>(
+                &JiebaGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                "running does".as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| String::from_utf8(t.token).unwrap())
+            .collect::<Vec<_>>()
+        };
+        // 默认在词干提取之前检查停词，running 不是停词，写入词干 run，停词 does 不写入
+        assert_eq!(["run"], tokenize(vec![]).as_slice());
+        // 检查词干提取的结果时，run 是停词，does 的词干 doe 不是停词
+        assert_eq!(
+            ["doe"],
+            tokenize(vec!["stopword_after_stem".to_owned()]).as_slice()
+        );
+        // 不做词干提取时和默认相同
+        assert_eq!(
+            ["running"],
+            tokenize(vec![
+                "stopword_after_stem".to_owned(),
+                "disable_stemming".to_owned()
+            ])
+            .as_slice()
+        );
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_s2t_fold() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        for (table, tokenize) in [("t1", "jieba"), ("t2", "jieba s2t_fold")] {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('我愛我的國家'),('國 家'),('国家');"),
+                [],
+            )
+            .unwrap();
+        }
+        // 不开启时，繁体字和简体字是不同的 token
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '国';");
+        assert_eq!(0, vec.len());
+        // 繁体字的单词额外写入简体字，查询时同样转换，简体和繁体可以互相匹配
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '国';");
+        assert_eq!(["國 家"], vec.as_slice());
+        for query in ["国家", "國家"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t2 WHERE text MATCH '{query}';"),
+            );
+            assert_eq!(["我愛我的國家", "国家"], vec.as_slice(), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_pinyin() {
+        let tokens = tokenize_with_positions::<JiebaTokenizer>(
+            &JiebaGlobal::default(),
+            vec!["pinyin".to_owned()],
+            TokenizeReason::Document,
+            "国家sqlite".as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|t| (String::from_utf8(t.token).unwrap(), t.position))
+        .collect::<Vec<_>>();
+        // 拼音和词语在同一个位置，拉丁字母单词不写入拼音
+        assert_eq!(
+            [
+                ("国家", 0),
+                ("guogu", 0),
+                ("guojia", 0),
+                ("guojie", 0),
+                ("guo", 0),
+                ("gu", 0),
+                ("jia", 0),
+                ("jie", 0),
+                ("sqlite", 1),
+            ]
+            .map(|(token, position)| (token.to_owned(), position))
+            .as_slice(),
+            tokens.as_slice()
+        );
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        for (table, tokenize) in [("t1", "jieba"), ("t2", "jieba pinyin")] {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('国家'),('快乐'),('kuaile');"),
+                [],
+            )
+            .unwrap();
+        }
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'guojia';");
+        assert_eq!(0, vec.len());
+        for query in ["国家", "guojia", "guo", "guo jia"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t2 WHERE text MATCH '{query}';"),
+            );
+            assert_eq!(["国家"], vec.as_slice(), "{query}");
+        }
+        // 每个字的拼音和词语写入同一个位置，空格分隔的拼音不能作为短语匹配
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '\"guo jia\"';");
+        assert_eq!(0, vec.len());
+        // 查询的汉字词语不生成拼音，不匹配拉丁字母写成的拼音
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '快乐';");
+        assert_eq!(["快乐"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_keep_original() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'jieba disable_stopword stem_documents_only keep_original');",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO t1(text) VALUES ('running 测试');", [])
+            .unwrap();
+        for query in ["run", "running"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t1 WHERE text MATCH '{query}';"),
+            );
+            assert_eq!(["running 测试"], vec.as_slice(), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_prefix_query() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'jieba');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('golang 国家'),('sqlite');",
+            [],
+        )
+        .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'go*';");
+        assert_eq!(["golang 国家"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"the sq\"*';");
+        assert_eq!(["sqlite"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_stemmer() {
+        let tokenize = |args: Vec<String>| {
+            tokenize_with_positions::<JiebaTokenizer>(
+                &JiebaGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                "法语 chevaux majestueusement".as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| String::from_utf8(t.token).unwrap())
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ["法语", "chevaux", "majestueus"],
+            tokenize(vec![]).as_slice()
+        );
+        assert_eq!(
+            ["法语", "cheval", "majestu"],
+            tokenize(vec!["stemmer=french".to_owned()]).as_slice()
+        );
+        assert!(
+            JiebaTokenizer::new(&JiebaGlobal::default(), vec!["stemmer=klingon".to_owned()])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_without_stemming() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'jieba disable_stemming disable_stopword');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('like'),('liking'),('liked');",
+            [],
+        )
+        .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'like';");
+        assert_eq!(["like"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'liked';");
+        assert_eq!(["liked"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_stopword_add() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        for (table, tokenize) in [("t1", "jieba"), ("t2", "jieba 'stopword_add=测试'")] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = \"{tokenize}\");"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('测试伟大的国家');"),
+                [],
+            )
+            .unwrap();
+        }
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '测试';");
+        assert_eq!(["测试伟大的国家"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '测试';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '国家';");
+        assert_eq!(["测试伟大的国家"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_cached_dict() {
+        let _lock = RELEASE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        let dir = std::env::temp_dir().join(format!("jieba_cached_dict_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dict = dir.join("dict.txt");
+        std::fs::write(&dict, "社会 100 n\n主义 100 n\n国家 100 n\n").unwrap();
+        let path = dict.display().to_string();
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        // 字典相同的两个表共享同一个 jieba 实例，字典只加载一次
+        for table in ["t1", "t2"] {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = \"jieba 'dict={path}'\");"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('社会主义国家');"),
+                [],
+            )
+            .unwrap();
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM {table} WHERE text MATCH '主义';"),
+            );
+            assert_eq!(["社会主义国家"], vec.as_slice());
+        }
+        let args = vec![format!("dict={path}")];
+        let first = JiebaTokenizer::new(&JiebaGlobal::default(), args.clone()).unwrap();
+        let second = JiebaTokenizer::new(&JiebaGlobal::default(), args.clone()).unwrap();
+        let cached = first.jieba.clone().unwrap();
+        assert!(Arc::ptr_eq(&cached, second.jieba.as_ref().unwrap()));
+        // 自定义的词语不同时是不同的配置，重新加载字典
+        let mut global = JiebaGlobal::default();
+        global.add_word("义国", None, None);
+        let first = JiebaTokenizer::new(&global, args.clone()).unwrap();
+        let second = JiebaTokenizer::new(&global, args.clone()).unwrap();
+        let with_words = first.jieba.clone().unwrap();
+        assert!(Arc::ptr_eq(&with_words, second.jieba.as_ref().unwrap()));
+        assert!(!Arc::ptr_eq(&cached, &with_words));
+        // 字典文件的修改时间改变之后重新加载
+        let modified = std::fs::metadata(&dict).unwrap().modified().unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&dict)
+            .unwrap()
+            .set_modified(modified + std::time::Duration::from_secs(60))
+            .unwrap();
+        let reloaded = JiebaTokenizer::new(&JiebaGlobal::default(), args.clone())
+            .unwrap()
+            .jieba
+            .unwrap();
+        assert!(!Arc::ptr_eq(&cached, &reloaded));
+        // 修改之前的实例从缓存中移除，不会一直累积
+        let canonical = std::fs::canonicalize(&dict).unwrap();
+        let entries = JIEBA_DICTS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .unwrap()
+            .keys()
+            .filter(|key| key.path.as_ref() == Some(&canonical))
+            .count();
+        assert_eq!(1, entries);
+        // 释放之后重新加载
+        release_jieba();
+        let released = JiebaTokenizer::new(&JiebaGlobal::default(), args)
+            .unwrap()
+            .jieba
+            .unwrap();
+        assert!(!Arc::ptr_eq(&reloaded, &released));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_dict() {
+        let dir = std::env::temp_dir().join(format!("jieba_dict_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dict = dir.join("dict.txt");
+        std::fs::write(&dict, "社会 100 n\n主义 100 n\n国家 100 n\n").unwrap();
+        let invalid = dir.join("invalid.txt");
+        std::fs::write(&invalid, "社会 many n\n").unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        for (table, tokenize) in [
+            ("t1", "jieba".to_owned()),
+            ("t2", format!("jieba 'dict={}'", dict.display())),
+            ("t3", format!("jieba 'dict={}'", invalid.display())),
+            (
+                "t4",
+                format!("jieba 'dict={}'", dir.join("missing.txt").display()),
+            ),
+        ] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = \"{tokenize}\");"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('社会主义国家');"),
+                [],
+            )
+            .unwrap();
+        }
+        // 默认字典将 社会主义 作为一个词
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '主义';");
+        assert_eq!(0, vec.len());
+        // 自定义字典中只有 社会 和 主义，没有 社会主义，不影响使用默认字典的表
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '主义';");
+        assert_eq!(["社会主义国家"], vec.as_slice());
+        // 字典格式错误或者不存在时，使用默认字典
+        for table in ["t3", "t4"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM {table} WHERE text MATCH '主义';"),
+            );
+            assert_eq!(0, vec.len());
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM {table} WHERE text MATCH '社会主义';"),
+            );
+            assert_eq!(["社会主义国家"], vec.as_slice());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_user_words() {
+        let tokenize = |global: &JiebaGlobal, args: Vec<String>| {
+            tokenize_with_positions::<JiebaTokenizer>(
+                global,
+                args,
+                TokenizeReason::Document,
+                "我将星海点燃".as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| String::from_utf8(t.token).unwrap())
+            .collect::<Vec<_>>()
+        };
+        let _lock = RELEASE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        let tokens = tokenize(&JiebaGlobal::default(), Vec::new());
+        assert!(!tokens.contains(&"星海点燃".to_owned()));
+        let mut global = JiebaGlobal::default();
+        global.add_word("星海点燃", None, None);
+        let tokens = tokenize(&global, Vec::new());
+        assert!(tokens.contains(&"星海点燃".to_owned()));
+        // 自定义词语相同的全局数据共享同一个 jieba 实例，释放之后重新创建
+        let first = global.jieba().unwrap();
+        assert!(Arc::ptr_eq(&first, &global.jieba().unwrap()));
+        let same_words = JiebaGlobal::new(vec![("星海点燃".to_owned(), None, None)]);
+        assert!(Arc::ptr_eq(&first, &same_words.jieba().unwrap()));
+        release_jieba();
+        assert!(!Arc::ptr_eq(&first, &global.jieba().unwrap()));
+
+        let conn = Connection::open_in_memory().unwrap();
+        let global = JiebaGlobal::new(vec![("星海点燃".to_owned(), None, None)]);
+        register_tokenizer::<JiebaTokenizer>(&conn, global).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'jieba');",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO t1(text) VALUES ('我将星海点燃');", [])
+            .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '星海点燃';");
+        assert_eq!(["我将星海点燃"], vec.as_slice());
+        // 星海点燃 作为一个词写入，无法单独匹配 星海
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '星海';");
+        assert_eq!(0, vec.len());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_disable_hmm() {
+        let tokenize = |args: Vec<String>| {
+            tokenize_with_positions::<JiebaTokenizer>(
+                &JiebaGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                "他来到了网易杭研大厦".as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| String::from_utf8(t.token).unwrap())
+            .collect::<Vec<_>>()
+        };
+        let args = vec!["disable_stopword".to_owned()];
+        // HMM 识别出字典中没有的 杭研
+        assert_eq!(
+            ["他", "来到", "了", "网易", "杭研", "大厦"],
+            tokenize(args).as_slice()
+        );
+        let args = vec!["disable_stopword".to_owned(), "disable_hmm".to_owned()];
+        assert_eq!(
+            ["他", "来到", "了", "网易", "杭", "研", "大厦"],
+            tokenize(args).as_slice()
+        );
+    }
+
+    #[test]
+    fn test_jieba_tokens_with_weight() {
+        let tokens = jieba_tokens_with_weight("我们的电脑，quokka");
+        let words = tokens
+            .iter()
+            .map(|(word, _)| word.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(["我们", "的", "电脑", "quokka"], words.as_slice());
+        // 常用词在字典中有词频，高频词的词频更大，不在字典中的单词词频为 0
+        let weight = |word: &str| tokens.iter().find(|(w, _)| w == word).unwrap().1;
+        assert!(weight("电脑") > 0);
+        assert!(weight("我们") > weight("电脑"));
+        assert_eq!(0, weight("quokka"));
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_pos_filter() {
+        let tokenize = |args: Vec<String>, text: &str| {
+            tokenize_with_positions::<JiebaTokenizer>(
+                &JiebaGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| (String::from_utf8(t.token).unwrap(), t.range))
+            .collect::<Vec<_>>()
+        };
+        let text = "我的电脑在北京 sqlite";
+        assert_eq!(
+            [
+                ("我", 0..3),
+                ("的", 3..6),
+                ("电脑", 6..12),
+                ("在", 12..15),
+                ("北京", 15..21),
+                ("sqlite", 22..28),
+            ]
+            .map(|(token, range)| (token.to_owned(), range))
+            .as_slice(),
+            tokenize(vec!["disable_stopword".to_owned()], text).as_slice()
+        );
+        // 只写入名词，北京的词性是 ns，同样属于名词，助词 的 和英文单词不写入
+        assert_eq!(
+            [("电脑", 6..12), ("北京", 15..21)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(
+                vec!["disable_stopword".to_owned(), "pos_filter=n".to_owned()],
+                text
+            )
+            .as_slice()
+        );
+        assert_eq!(
+            [("电脑", 6..12), ("北京", 15..21), ("sqlite", 22..28)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(
+                vec!["disable_stopword".to_owned(), "pos_filter=n,eng".to_owned()],
+                text
+            )
+            .as_slice()
+        );
+        assert!(
+            JiebaTokenizer::new(&JiebaGlobal::default(), vec!["pos_filter=".to_owned()]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_search_mode() {
+        let text = "中华人民共和国成立于一九四九年 小明硕士毕业于中国科学院计算所";
+        let tokenize = |args: Vec<String>| {
+            tokenize_with_positions::<JiebaTokenizer>(
+                &JiebaGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+        };
+        let default = tokenize(vec!["disable_stopword".to_owned()]);
+        let search = tokenize(vec![
+            "disable_stopword".to_owned(),
+            "mode=search".to_owned(),
+        ]);
+        // 搜索引擎模式切分出更多重叠的短词语
+        assert!(search.len() > default.len());
+        for token in &search {
+            assert_eq!(token.token, text[token.range.clone()].as_bytes());
+        }
+        let words = search
+            .iter()
+            .map(|t| String::from_utf8(t.token.clone()).unwrap())
+            .collect::<Vec<_>>();
+        for word in ["中华", "共和国", "中华人民共和国", "科学院", "中国科学院"]
+        {
+            assert!(words.contains(&word.to_owned()));
+        }
+        // 长词语中包含的短词语和长词语写入同一个位置，位置和精确模式一致
+        let last = default.last().unwrap().position;
+        assert_eq!(last, search.last().unwrap().position);
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        for (table, tokenize) in [("t1", "jieba"), ("t2", "jieba 'mode=search'")] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = \"{tokenize}\");"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(&format!("INSERT INTO {table}(text) VALUES (?1);"), [text])
+                .unwrap();
+        }
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '科学院';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '科学院';");
+        assert_eq!([text], vec.as_slice());
+        // 查询使用相同的模式，长词语的短语查询仍然可以匹配
+        let vec = query_texts(
+            &conn,
+            "SELECT * FROM t2 WHERE text MATCH '\"中华人民共和国成立\"';",
+        );
+        assert_eq!([text], vec.as_slice());
+        let result = conn.execute(
+            "CREATE VIRTUAL TABLE t3 USING fts5(text, tokenize = \"jieba 'mode=full'\");",
+            [],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_normalized_offsets() {
+        // 全角字符和连字归一化后长度改变，范围仍然对应原始的字节
+        let text = "ＳＱＬｉｔｅ ３ ﬁle oﬃce";
+        let tokens = tokenize_with_positions::<JiebaTokenizer>(
+            &JiebaGlobal::default(),
+            vec!["disable_stopword".to_owned()],
+            TokenizeReason::Document,
+            text.as_bytes(),
+        )
+        .unwrap();
+        assert!(!tokens.is_empty());
+        for token in &tokens {
+            let original = text[token.range.clone()].nfkc().collect::<String>();
+            assert_eq!(original.to_lowercase().as_bytes(), token.token);
+        }
+        let covered = tokens
+            .iter()
+            .map(|t| &text[t.range.clone()])
+            .collect::<String>();
+        assert_eq!("ＳＱＬｉｔｅ３ﬁleoﬃce", covered);
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_invalid_utf8() {
+        // 无效的 UTF-8 字节被替换成 U+FFFD，范围仍然对应原始的字节
+        let text = b"\xff\xfe sqlite \xe5\x9b tokenizer \xe5\x9b\xbd\xe5\xae\xb6\xe5";
+        let tokens = tokenize_with_positions::<JiebaTokenizer>(
+            &JiebaGlobal::default(),
+            vec!["disable_stopword".to_owned(), "raw_mode".to_owned()],
+            TokenizeReason::Document,
+            text,
+        )
+        .unwrap();
+        assert!(!tokens.is_empty());
+        for token in &tokens {
+            assert!(token.range.end <= text.len());
+            if token.token.is_ascii() {
+                assert_eq!(token.token, text[token.range.clone()]);
+            }
+        }
+        let ranges = tokens
+            .iter()
+            .filter(|t| t.token == b"sqlite" || t.token == b"tokenizer")
+            .map(|t| t.range.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![3..9, 13..22], ranges);
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_max_token_bytes() {
+        let blob = "QUJDREVGR0hJSktMTU5PUFFSU1RVVldYWVo".repeat(1500);
+        let tokens = tokenize_with_positions::<JiebaTokenizer>(
+            &JiebaGlobal::default(),
+            vec![],
+            TokenizeReason::Document,
+            format!("测试{blob}").as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|t| String::from_utf8(t.token).unwrap())
+        .collect::<Vec<_>>();
+        assert_eq!(["测试"], tokens.as_slice());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_max_tokens() {
+        let tokens = tokenize_with_positions::<JiebaTokenizer>(
+            &JiebaGlobal::default(),
+            vec!["max_tokens=3".to_owned()],
+            TokenizeReason::Document,
+            "国家".repeat(10_000).as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .filter(|t| !t.colocated)
+        .map(|t| String::from_utf8(t.token).unwrap())
+        .collect::<Vec<_>>();
+        assert_eq!(["国家", "国家", "国家"], tokens.as_slice());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_transform() {
+        let mut global = JiebaGlobal::default();
+        // 转换成大写，丢弃 quokka
+        global.set_transform(Arc::new(|word: &str| {
+            (word != "quokka").then(|| word.to_uppercase())
+        }));
+        let tokens = tokenize_with_positions::<JiebaTokenizer>(
+            &global,
+            vec![],
+            TokenizeReason::Document,
+            "SQLite quokka learning 电脑".as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|t| String::from_utf8(t.token).unwrap())
+        .collect::<Vec<_>>();
+        // 大写的单词不被词干提取改变
+        assert_eq!(["SQLITE", "LEARNING", "电脑"], tokens.as_slice());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_min_token_chars() {
+        let tokenize = |args: Vec<String>, text: &str| {
+            tokenize_with_positions::<JiebaTokenizer>(
+                &JiebaGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| String::from_utf8(t.token).unwrap())
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ["i", "can", "t", "imagin", "国"],
+            tokenize(vec!["disable_stopword".to_owned()], "I can't imagine 国").as_slice()
+        );
+        // can't 切分出的 t 不写入，单个汉字不受影响
+        assert_eq!(
+            ["can", "imagin", "国"],
+            tokenize(
+                vec![
+                    "disable_stopword".to_owned(),
+                    "min_token_chars=2".to_owned()
+                ],
+                "I can't imagine 国"
+            )
+            .as_slice()
+        );
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_invalid_utf8_policy() {
+        let tokenize = |args: Vec<String>| {
+            tokenize_with_positions::<JiebaTokenizer>(
+                &JiebaGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                b"sqlite\xffquokka",
+            )
+            .map(|tokens| {
+                tokens
+                    .into_iter()
+                    .map(|t| (String::from_utf8(t.token).unwrap(), t.range))
+                    .collect::<Vec<_>>()
+            })
+        };
+        // 默认替换成 U+FFFD，jieba 将替换字符单独切分出来写入
+        assert_eq!(
+            [("sqlite", 0..6), ("\u{fffd}", 6..7), ("quokka", 7..13)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(vec![]).unwrap().as_slice()
+        );
+        assert_eq!(
+            [("sqlite", 0..6), ("quokka", 7..13)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(vec!["invalid_utf8=skip".to_owned()])
+                .unwrap()
+                .as_slice()
+        );
+        assert!(matches!(
+            tokenize(vec!["invalid_utf8=error".to_owned()]),
+            Err(rusqlite::Error::Utf8Error(_))
+        ));
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_numbers() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        for (table, numbers) in [("t1", "keep"), ("t2", "drop"), ("t3", "normalize")] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = \"jieba 'numbers={numbers}'\");"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('jump 32.3 feet'),('1,000 rust'),('code 0042');"),
+                [],
+            )
+            .unwrap();
+        }
+        let count = |table: &str, query: &str| {
+            query_texts(
+                &conn,
+                &format!("SELECT * FROM {table} WHERE text MATCH '{query}';"),
+            )
+            .len()
+        };
+        assert_eq!(1, count("t1", "\"32.3\""));
+        assert_eq!(1, count("t1", "\"1,000\""));
+        assert_eq!(0, count("t1", "1000"));
+        assert_eq!(0, count("t1", "42"));
+        // 不写入数字，数字两边的单词相邻
+        assert_eq!(0, count("t2", "\"32.3\""));
+        assert_eq!(0, count("t2", "\"1,000\""));
+        assert_eq!(1, count("t2", "\"jump feet\""));
+        assert_eq!(1, count("t2", "rust"));
+        // 去掉千位分隔符和开头的 0，查询时同样归一化
+        assert_eq!(1, count("t3", "\"32.3\""));
+        assert_eq!(1, count("t3", "1000"));
+        assert_eq!(1, count("t3", "\"1,000\""));
+        assert_eq!(1, count("t3", "42"));
+        assert_eq!(1, count("t3", "0042"));
+        let result = conn.execute(
+            "CREATE VIRTUAL TABLE t4 USING fts5(text, tokenize = \"jieba 'numbers=round'\");",
+            [],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_dedup_document() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'jieba dedup_document');",
+            [],
+        )
+        .unwrap();
+        conn.execute("CREATE VIRTUAL TABLE v1 USING fts5vocab(t1, 'row');", [])
+            .unwrap();
+        conn.execute("INSERT INTO t1(text) VALUES (?1);", ["国家 ".repeat(100)])
+            .unwrap();
+        let cnt: i64 = conn
+            .query_row("SELECT cnt FROM v1 WHERE term = '国家';", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(1, cnt);
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '国家';");
+        assert_eq!(1, vec.len());
+        // tokenizers 的词干 token 已经写入，原本的单词写入 tokenizers 的位置，不依附到前面的 tokenizer
+        let tokens = tokenize_with_positions::<JiebaTokenizer>(
+            &JiebaGlobal::default(),
+            vec!["dedup_document".to_owned(), "keep_original".to_owned()],
+            TokenizeReason::Document,
+            "tokenizer tokenizers".as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|t| (String::from_utf8(t.token).unwrap(), t.range, t.position))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            [
+                ("token", 0..9, 0),
+                ("tokenizer", 0..9, 0),
+                ("tokenizers", 10..20, 1)
+            ]
+            .map(|(token, range, position)| (token.to_owned(), range, position))
+            .as_slice(),
+            tokens.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_symbols() {
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let text = format!("sqlite 👍, {family}!");
+        let tokenize = |args: Vec<String>| {
+            tokenize_with_positions::<JiebaTokenizer>(
+                &JiebaGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| (String::from_utf8(t.token).unwrap(), t.range))
+            .collect::<Vec<_>>()
+        };
+        // 默认写入符号，jieba 切分开的 emoji 序列合并成一个 token
+        assert_eq!(
+            [
+                ("sqlite".to_owned(), 0..6),
+                ("👍".to_owned(), 7..11),
+                (family.to_owned(), 13..31)
+            ]
+            .as_slice(),
+            tokenize(vec![]).as_slice()
+        );
+        assert_eq!(
+            [("sqlite".to_owned(), 0..6)].as_slice(),
+            tokenize(vec!["symbols=drop".to_owned()]).as_slice()
+        );
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_meaningful_symbols() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'jieba');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('盐度 30‰'),('盐度 30'),('夹角 5′');",
+            [],
+        )
+        .unwrap();
+        // ‰ 和 ′ 不是标点，作为符号写入，可以区分 30‰ 和 30
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"30‰\"';");
+        assert_eq!(["盐度 30‰"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '‰';");
+        assert_eq!(["盐度 30‰"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"5′\"';");
+        assert_eq!(["夹角 5′"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_with_raw_mode() {
+        let text = "The quick (\"brown\") fox can't jump 32.3 feet, right? 我将点燃星海！天上的stars全部都是 eye，不要凝视";
+        let tokens = tokenize_with_positions::<JiebaTokenizer>(
+            &JiebaGlobal::default(),
+            vec!["raw_mode".to_owned()],
+            TokenizeReason::Document,
+            text.as_bytes(),
+        )
+        .unwrap();
+        let tokens = tokens
+            .into_iter()
+            .map(|t| String::from_utf8(t.token).unwrap())
+            .collect::<Vec<_>>();
+        let expected = shared_jieba()
+            .cut(text, true)
+            .into_iter()
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, tokens);
+        // 原样写入，不做小写转换、停词和词干提取
+        assert_eq!(
+            [
+                "The",
+                "quick",
+                "brown",
+                "fox",
+                "can",
+                "t",
+                "jump",
+                "32.3",
+                "feet",
+                "right",
+                "我",
+                "将",
+                "点燃",
+                "星海",
+                "天上",
+                "的",
+                "stars",
+                "全部都是",
+                "eye",
+                "不要",
+                "凝视"
+            ],
+            tokens.as_slice()
+        );
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'jieba raw_mode');",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO t1(text) VALUES (?1);", [text])
+            .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'The';");
+        assert_eq!(1, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'the';");
+        assert_eq!(0, vec.len());
+    }
+
+    #[test]
+    fn test_jieba_tokenizer_skip_full_width_punctuation() {
+        let tokens = tokenize_with_positions::<JiebaTokenizer>(
+            &JiebaGlobal::default(),
+            Vec::new(),
+            TokenizeReason::Document,
+            "我将点燃星海！天上的stars全部都是 eye，不要凝视".as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|t| String::from_utf8(t.token).unwrap())
+        .collect::<Vec<_>>();
+        // jieba 单独切分出的 ！ 和 ， 不写入索引
+        for punctuation in ["!", ",", "！", "，"] {
+            assert!(
+                !tokens.iter().any(|token| token == punctuation),
+                "{punctuation}"
+            );
+        }
+        assert!(tokens.iter().any(|token| token == "星海"));
+        assert!(tokens.iter().any(|token| token == "eye"));
+    }
+
+    #[test]
+    fn test_warm_jieba() {
+        warm_jieba();
+        // 重复调用不会出错
+        warm_jieba();
+        let tokens = tokenize_with_positions::<JiebaTokenizer>(
+            &JiebaGlobal::default(),
+            Vec::new(),
+            TokenizeReason::Document,
+            "社会主义国家".as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|t| String::from_utf8(t.token).unwrap())
+        .collect::<Vec<_>>();
+        assert_eq!(["社会主义", "国家"], tokens.as_slice());
+    }
+
+    #[test]
+    fn test_release_jieba() {
+        let _lock = RELEASE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        let tokenize = || {
+            tokenize_with_positions::<JiebaTokenizer>(
+                &JiebaGlobal::default(),
+                Vec::new(),
+                TokenizeReason::Document,
+                "社会主义国家".as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| String::from_utf8(t.token).unwrap())
+            .collect::<Vec<_>>()
+        };
+        let is_loaded = || {
+            JIEBA
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .is_some()
+        };
+        assert_eq!(["社会主义", "国家"], tokenize().as_slice());
+        assert!(is_loaded());
+        release_jieba();
+        assert!(!is_loaded());
+        // 释放后再次分词，重新加载字典
+        assert_eq!(["社会主义", "国家"], tokenize().as_slice());
+        assert!(is_loaded());
+        release_jieba();
+        release_jieba();
+        assert!(!is_loaded());
+        assert_eq!(["社会主义", "国家"], tokenize().as_slice());
+        assert!(is_loaded());
+    }
 
     #[test]
     fn test_tokenize_by_jieba_cut() {
         let text = "The quick (\"brown\") fox can't jump 32.3 feet, right? 我将点燃星海！天上的stars全部都是 eye，不要凝视";
-        let words = JIEBA.cut(text, false);
+        let words = shared_jieba().cut(text, false);
         let vec = [
             "The",
             " ",
@@ -140,7 +2330,7 @@ mod tests {
             "凝视",
         ];
         assert_eq!(words, vec);
-        let words = JIEBA.cut(text, true);
+        let words = shared_jieba().cut(text, true);
         let vec = [
             "The",
             " ",
@@ -189,7 +2379,7 @@ mod tests {
     #[test]
     fn test_tokenize_by_jieba_cut2() {
         let text = "社会主义国家";
-        let words = JIEBA.cut(text, true);
+        let words = shared_jieba().cut(text, true);
         let vec = ["社会主义", "国家"];
         assert_eq!(words, vec);
     }
@@ -0,0 +1,164 @@
+use crate::tokenizer::{
+    TokenizeReason, Tokenizer,
+    utils::{ConfigSummary, LossyText, reject_unknown_args},
+};
+use rusqlite::Error;
+use std::ffi::CStr;
+use std::ops::Range;
+
+/// 将整个字段作为一个 token 的分词器，适用于编码、UUID、标签等需要完全匹配的字段
+///
+/// 只去掉字段首尾的空白字符，不做切分、停词和词干提取，查询时需要使用双引号包裹完整的值，
+/// 例如 `MATCH '"019976ba-beff-7311-96c9-d0ba0bce1e1b"'`
+#[derive(Default)]
+pub struct KeywordTokenizer {
+    /// 是否将字段转换成小写，默认关闭，区分大小写
+    lowercase: bool,
+}
+
+impl std::fmt::Debug for KeywordTokenizer {
+    /// 输出 [`Tokenizer::config_summary`] 的配置摘要
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.config_summary())
+    }
+}
+
+impl KeywordTokenizer {
+    /// 将字段转换成小写，匹配时不区分大小写
+    pub fn enable_lowercase(&mut self) {
+        self.lowercase = true;
+    }
+}
+
+impl Tokenizer for KeywordTokenizer {
+    type Global = ();
+
+    fn name() -> &'static CStr {
+        c"keyword"
+    }
+
+    fn config_summary(&self) -> String {
+        ConfigSummary::new(Self::name())
+            .switch("lowercase", self.lowercase)
+            .finish()
+    }
+
+    fn new(_global: &Self::Global, args: Vec<String>) -> Result<Self, Error> {
+        let mut tokenizer = Self::default();
+        let mut unknown = Vec::new();
+        for arg in args {
+            match arg.as_str() {
+                "lowercase" => {
+                    tokenizer.enable_lowercase();
+                }
+                _ => unknown.push(arg),
+            }
+        }
+        reject_unknown_args("keyword", &unknown)?;
+        Ok(tokenizer)
+    }
+
+    fn tokenize<TKF>(
+        &mut self,
+        _reason: TokenizeReason,
+        text: &[u8],
+        mut push_token: TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        // 两种模式都去掉首尾的 Unicode 空白字符，写入的范围不包含去掉的空白
+        let lossy = LossyText::new(text);
+        let field = lossy.as_str();
+        let start = field.len() - field.trim_start().len();
+        let token = field.trim();
+        if token.is_empty() {
+            return Ok(());
+        }
+        let range = lossy.original_range(start..start + token.len());
+        if self.lowercase {
+            (push_token)(token.to_lowercase().as_bytes(), range, false)
+        } else {
+            // 不转换成小写时写入原始的字节
+            (push_token)(&text[range.clone()], range, false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::keyword_tokenizer::KeywordTokenizer;
+    use crate::tokenizer::standalone::tokenize_to_vec;
+    use crate::tokenizer::tests::query_texts;
+    use crate::tokenizer::{TokenizeReason, register_tokenizer};
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_keyword_tokenizer() {
+        let tokenize = |args: Vec<String>, text: &str| {
+            tokenize_to_vec::<KeywordTokenizer>(
+                &(),
+                args,
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(token, range, _)| (String::from_utf8(token).unwrap(), range))
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            [("Tag-A 1".to_owned(), 2..9)].as_slice(),
+            tokenize(vec![], "  Tag-A 1 ").as_slice()
+        );
+        assert_eq!(
+            [("tag-a 1".to_owned(), 2..9)].as_slice(),
+            tokenize(vec!["lowercase".to_owned()], "  Tag-A 1 ").as_slice()
+        );
+        // 全角空格同样作为首尾的空白去掉，两种模式写入相同的范围
+        for args in [vec![], vec!["lowercase".to_owned()]] {
+            assert_eq!(
+                [("tag".to_owned(), 3..6)].as_slice(),
+                tokenize(args, "\u{3000}tag\u{3000}").as_slice()
+            );
+        }
+        assert!(tokenize(vec![], " \t ").is_empty());
+        assert!(tokenize(vec![], "\u{3000}").is_empty());
+    }
+
+    #[test]
+    fn test_register_keyword_tokenizer() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<KeywordTokenizer>(&conn, ()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(id, tokenize = 'keyword lowercase');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(id) VALUES ('019976ba-beff-7311-96c9-d0ba0bce1e1b'),('019976ba-beff-7311-96c9-d0ba0bce1e1c');",
+            [],
+        )
+        .unwrap();
+        // 完整的值可以匹配，不区分大小写
+        let vec = query_texts(
+            &conn,
+            "SELECT * FROM t1 WHERE id MATCH '\"019976BA-BEFF-7311-96C9-D0BA0BCE1E1B\"';",
+        );
+        assert_eq!(["019976ba-beff-7311-96c9-d0ba0bce1e1b"], vec.as_slice());
+        // 部分的值不能匹配
+        for query in ["019976ba", "\"019976ba-beff\"", "d0ba0bce1e1b"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t1 WHERE id MATCH '{query}';"),
+            );
+            assert_eq!(0, vec.len(), "{query}");
+        }
+        // 无法识别的参数
+        let result = conn.execute(
+            "CREATE VIRTUAL TABLE t2 USING fts5(id, tokenize = 'keyword lowercas');",
+            [],
+        );
+        assert!(result.is_err());
+    }
+}
@@ -2,25 +2,45 @@
 // 针对 Rust 和 rusqlite 的新版本做了一些调整
 
 pub mod jieba_tokenizer;
+pub mod keyword_tokenizer;
+pub mod ngram_tokenizer;
 pub mod simple_tokenizer;
+pub mod standalone;
+pub mod unicode_tokenizer;
 mod utils;
 
+pub use utils::{
+    InvalidUtf8Policy, Ligatures, NormalizationForm, NumberMode, PinyinPrimary, PipelineOutcome,
+    SymbolPolicy, TokenPipeline, TokenTransform, VariationSelectors, reset_global_stopwords,
+    set_global_stopwords,
+};
+#[cfg(feature = "token_observer")]
+pub use utils::{TokenObserver, set_token_observer};
+/// 词干提取使用的语言
+pub use waken_snowball::Algorithm as StemmerLanguage;
+
 use rusqlite::Connection;
 use rusqlite::ffi::{
     FTS5_TOKEN_COLOCATED, FTS5_TOKENIZE_AUX, FTS5_TOKENIZE_DOCUMENT, FTS5_TOKENIZE_PREFIX,
-    FTS5_TOKENIZE_QUERY, Fts5Tokenizer, SQLITE_ERROR, SQLITE_OK, SQLITE_PREPARE_PERSISTENT,
-    fts5_api, fts5_tokenizer_v2, sqlite3_bind_pointer, sqlite3_finalize, sqlite3_prepare_v3,
-    sqlite3_step, sqlite3_stmt,
+    FTS5_TOKENIZE_QUERY, Fts5Tokenizer, SQLITE_DONE, SQLITE_ERROR, SQLITE_OK,
+    SQLITE_PREPARE_PERSISTENT, SQLITE_ROW, SQLITE_TOOBIG, fts5_api, fts5_tokenizer,
+    fts5_tokenizer_v2, sqlite3, sqlite3_bind_pointer, sqlite3_errmsg, sqlite3_finalize,
+    sqlite3_prepare_v3, sqlite3_step, sqlite3_stmt,
 };
 use std::ffi::{CStr, c_char, c_int, c_void};
 use std::fmt::Formatter;
 use std::ops::Range;
 use std::panic::AssertUnwindSafe;
 
-/// fts5_api 的版本，要求最低版本不能低于 3
-const FTS5_API_VERSION: c_int = 3;
-/// 设置 fts5_tokenizer 的版本，设置为 2，使用 v2 接口
-const FTS5_TOKENIZER_VERSION: c_int = 2;
+/// 使用 v2 分词器接口需要的 fts5_api 版本，运行时的版本不低于 3 时使用 v2 接口，否则退回 v1 接口
+///
+/// 更高版本的 fts5_api 只在结构体末尾追加新的接口，仍然可以使用 v2 接口注册
+pub const FTS5_API_VERSION: i32 = 3;
+/// 可以注册分词器的最低 fts5_api 版本，运行时的版本低于这个值时返回
+/// [`RegisterTokenizerError::Fts5ApiVersionUnsupported`]，不读取结构体中的任何接口
+pub const MIN_FTS5_API_VERSION: i32 = 2;
+/// 使用 v2 接口注册时，fts5_tokenizer_v2 结构体的版本
+pub const FTS5_TOKENIZER_VERSION: i32 = 2;
 
 /// FTS5 请求对所提供的文本进行标记化的原因
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -53,12 +73,27 @@ impl std::fmt::Display for IntoTokenizeReasonError {
 
 impl std::error::Error for IntoTokenizeReasonError {}
 
+/// 针对 FTS 索引执行 MATCH 查询时，在查询字符串后带上 * 的特殊值
+const FTS5_TOKENIZE_QUERY_PREFIX: c_int = FTS5_TOKENIZE_QUERY | FTS5_TOKENIZE_PREFIX;
+
+impl TokenizeReason {
+    /// 转换成 FTS5 传给 xTokenize 的 flags 参数，和 [`TokenizeReason::try_from`] 互为逆操作
+    ///
+    /// 可以用于在独立分词时模拟 FTS5 的调用，例如 `TokenizeReason::try_from(flags)` 得到的分词原因
+    pub fn to_flag(&self) -> c_int {
+        match self {
+            Self::Document => FTS5_TOKENIZE_DOCUMENT,
+            Self::Query { prefix: false } => FTS5_TOKENIZE_QUERY,
+            Self::Query { prefix: true } => FTS5_TOKENIZE_QUERY_PREFIX,
+            Self::Aux => FTS5_TOKENIZE_AUX,
+        }
+    }
+}
+
 impl TryFrom<c_int> for TokenizeReason {
     type Error = IntoTokenizeReasonError;
 
     fn try_from(value: c_int) -> Result<Self, Self::Error> {
-        /// 这个值是针对 FTS 索引执行 MATCH 查询时，在查询字符串后带上 * 的特殊值
-        const FTS5_TOKENIZE_QUERY_PREFIX: c_int = FTS5_TOKENIZE_QUERY | FTS5_TOKENIZE_PREFIX;
         match value {
             FTS5_TOKENIZE_DOCUMENT => Ok(Self::Document),
             FTS5_TOKENIZE_QUERY => Ok(Self::Query { prefix: false }),
@@ -81,6 +116,18 @@ pub trait Tokenizer: Sized + Send + 'static {
     ///
     /// 在 xCreate 中被调用，xCreate 的 azArg 参数转换成 Vec<String>，并以此提供给 new方法使用
     fn new(global: &Self::Global, args: Vec<String>) -> Result<Self, rusqlite::Error>;
+    /// 不指定参数时实际生效的默认参数，用于在界面和文档中展示
+    ///
+    /// 返回的参数传给 [`Tokenizer::new`] 时和不指定参数的效果相同，默认返回空的列表
+    fn default_args() -> Vec<String> {
+        Vec::new()
+    }
+    /// 当前实例生效的配置摘要，用于日志输出，例如 `simple(pinyin=on,stopword=on,stemming=on)`
+    ///
+    /// 默认只包含分词器名称，内置的分词器列出主要的开关和与默认值不同的参数
+    fn config_summary(&self) -> String {
+        format!("{}()", Self::name().to_string_lossy())
+    }
     /// 分词的具体实现
     ///
     /// 应该检查 `text` 对象，并且对每个 `token` 调用 `push_token` 这个回调方法
@@ -98,6 +145,29 @@ pub trait Tokenizer: Sized + Send + 'static {
     ) -> Result<(), rusqlite::Error>
     where
         TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>;
+    /// 带有 locale 的分词
+    ///
+    /// 在 xTokenize 中被调用，`locale` 是写入或者查询时通过 `fts5_locale()` 指定的 locale，
+    /// 没有指定时为 `None`，需要区分 locale 的分词器可以实现这个方法，默认忽略 locale，调用 [`Tokenizer::tokenize`]
+    fn tokenize_with_locale<TKF>(
+        &mut self,
+        reason: TokenizeReason,
+        locale: Option<&str>,
+        text: &[u8],
+        push_token: TKF,
+    ) -> Result<(), rusqlite::Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>,
+    {
+        let _ = locale;
+        self.tokenize(reason, text, push_token)
+    }
+    /// 上一次分词中被停词表过滤的单词数量，用于 [`standalone::tokenize_stats`]
+    ///
+    /// 默认返回 0，内置的 `simple`、`jieba` 和 `unicode` 在分词的同时统计
+    fn stopwords_filtered(&self) -> usize {
+        0
+    }
 }
 
 unsafe extern "C" fn x_create<T: Tokenizer>(
@@ -121,7 +191,14 @@ unsafe extern "C" fn x_create<T: Tokenizer>(
             SQLITE_OK
         }
         Ok(Err(rusqlite::Error::SqliteFailure(e, _))) => e.extended_code,
-        Ok(Err(_)) => SQLITE_ERROR,
+        Ok(Err(error)) => {
+            // FTS5 只报告创建分词器失败，错误信息（例如无法识别的参数）只能通过日志输出
+            log::error!(
+                "<{} as Tokenizer>::new failed: {error}",
+                std::any::type_name::<T>()
+            );
+            SQLITE_ERROR
+        }
         Err(msg) => {
             log::error!(
                 "<{} as Tokenizer>::new panic: {}",
@@ -161,15 +238,14 @@ unsafe extern "C" fn x_destroy<T: Tokenizer>(v: *mut c_void) {
     }
 }
 
-/// 忽略 locale 配置
 unsafe extern "C" fn x_tokenize<T: Tokenizer>(
     this: *mut Fts5Tokenizer,
     ctx: *mut c_void,
     flag: c_int,
     data: *const c_char,
     data_len: c_int,
-    _locale: *const c_char,
-    _locale_len: c_int,
+    locale: *const c_char,
+    locale_len: c_int,
     push_token: Option<
         unsafe extern "C" fn(*mut c_void, c_int, *const c_char, c_int, c_int, c_int) -> c_int,
     >,
@@ -183,19 +259,50 @@ unsafe extern "C" fn x_tokenize<T: Tokenizer>(
         }
     };
 
-    let data = unsafe { std::slice::from_raw_parts(data.cast::<u8>(), data_len as usize) };
+    // 文本为空指针或者长度不是正数时按照空文本分词，不能用空指针创建切片
+    let data = if data.is_null() || data_len <= 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(data.cast::<u8>(), data_len as usize) }
+    };
+    // 没有指定 locale 时，locale 为空指针或者空串
+    let locale = if locale.is_null() || locale_len <= 0 {
+        None
+    } else {
+        let locale =
+            unsafe { std::slice::from_raw_parts(locale.cast::<u8>(), locale_len as usize) };
+        Some(String::from_utf8_lossy(locale))
+    };
 
     let push_token = push_token.expect("No provide push token function");
     let push_token = |token: &[u8],
                       Range { start, end }: Range<usize>,
                       colocated: bool|
      -> Result<(), rusqlite::Error> {
-        let token_len: c_int = token.len().try_into().expect("Token is too long");
-        assert!(
-            start <= data.len() && end <= data.len(),
-            "Token range is invalid. Range is [{start}..{end}], data length is {}",
-            data.len(),
-        );
+        let Ok(token_len) = c_int::try_from(token.len()) else {
+            return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(SQLITE_TOOBIG),
+                Some(format!(
+                    "Token is too long, token length is {}",
+                    token.len()
+                )),
+            ));
+        };
+        if start > end || end > data.len() {
+            // 分词器计算出错误的位置时返回错误，不再依赖 catch_unwind 处理 panic
+            let message = format!(
+                "Token range is invalid. Range is [{start}..{end}], data length is {}",
+                data.len(),
+            );
+            log::error!(
+                "<{} as Tokenizer>::tokenize {message}",
+                std::any::type_name::<T>()
+            );
+            return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(SQLITE_ERROR),
+                Some(message),
+            ));
+        }
         let flags = if colocated { FTS5_TOKEN_COLOCATED } else { 0 };
 
         let res = unsafe {
@@ -218,10 +325,18 @@ unsafe extern "C" fn x_tokenize<T: Tokenizer>(
         }
     };
 
-    match std::panic::catch_unwind(AssertUnwindSafe(|| this.tokenize(reason, data, push_token))) {
+    match std::panic::catch_unwind(AssertUnwindSafe(|| {
+        this.tokenize_with_locale(reason, locale.as_deref(), data, push_token)
+    })) {
         Ok(Ok(())) => SQLITE_OK,
         Ok(Err(rusqlite::Error::SqliteFailure(e, _))) => e.extended_code,
-        Ok(Err(_)) => SQLITE_ERROR,
+        Ok(Err(error)) => {
+            log::error!(
+                "<{} as Tokenizer>::tokenize failed: {error}",
+                std::any::type_name::<T>()
+            );
+            SQLITE_ERROR
+        }
         Err(msg) => {
             log::error!(
                 "<{} as Tokenizer>::tokenize panic: {}",
@@ -233,6 +348,31 @@ unsafe extern "C" fn x_tokenize<T: Tokenizer>(
     }
 }
 
+/// v1 分词器接口的 xTokenize，没有 locale 参数，按照没有指定 locale 分词
+unsafe extern "C" fn x_tokenize_v1<T: Tokenizer>(
+    this: *mut Fts5Tokenizer,
+    ctx: *mut c_void,
+    flag: c_int,
+    data: *const c_char,
+    data_len: c_int,
+    push_token: Option<
+        unsafe extern "C" fn(*mut c_void, c_int, *const c_char, c_int, c_int, c_int) -> c_int,
+    >,
+) -> c_int {
+    unsafe {
+        x_tokenize::<T>(
+            this,
+            ctx,
+            flag,
+            data,
+            data_len,
+            std::ptr::null(),
+            0,
+            push_token,
+        )
+    }
+}
+
 fn panic_err_to_str(msg: &Box<dyn std::any::Any + Send>) -> &str {
     if let Some(msg) = msg.downcast_ref::<String>() {
         msg.as_str()
@@ -245,9 +385,12 @@ fn panic_err_to_str(msg: &Box<dyn std::any::Any + Send>) -> &str {
 
 #[derive(Debug)]
 pub enum RegisterTokenizerError {
-    SelectFts5Failed,
+    /// 执行 `SELECT fts5(?1)` 失败，包含 SQLite 返回的错误码和错误信息，通常是 SQLite 没有启用 FTS5
+    SelectFts5Failed(rusqlite::Error),
     Fts5ApiNul,
     Fts5ApiVersionTooLow,
+    /// 运行时 fts5_api 的版本低于 [`MIN_FTS5_API_VERSION`]，无法确定结构体的布局，包含运行时的版本
+    Fts5ApiVersionUnsupported(i32),
     Fts5xCreateTokenizerV2Nul,
     Fts5xCreateTokenizerFailed(i32),
 }
@@ -255,8 +398,8 @@ pub enum RegisterTokenizerError {
 impl std::fmt::Display for RegisterTokenizerError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            RegisterTokenizerError::SelectFts5Failed => {
-                write!(f, "SELECT fts5(?1) failed.")
+            RegisterTokenizerError::SelectFts5Failed(err) => {
+                write!(f, "SELECT fts5(?1) failed: {err}.")
             }
             RegisterTokenizerError::Fts5ApiNul => {
                 write!(f, "Could not get fts5 api.")
@@ -264,6 +407,14 @@ impl std::fmt::Display for RegisterTokenizerError {
             RegisterTokenizerError::Fts5ApiVersionTooLow => {
                 write!(f, "The version of fts5 api is too low.")
             }
+            RegisterTokenizerError::Fts5ApiVersionUnsupported(version) => {
+                write!(
+                    f,
+                    "The version of fts5 api is {version}, which is not supported, \
+                     expected version {MIN_FTS5_API_VERSION} or later \
+                     (version {FTS5_API_VERSION} or later for fts5_tokenizer_v2 version {FTS5_TOKENIZER_VERSION})."
+                )
+            }
             RegisterTokenizerError::Fts5xCreateTokenizerV2Nul => {
                 write!(f, "Fts5 api xCreateTokenizer_v2 ptr is null.")
             }
@@ -277,7 +428,25 @@ impl std::fmt::Display for RegisterTokenizerError {
     }
 }
 
-impl std::error::Error for RegisterTokenizerError {}
+impl std::error::Error for RegisterTokenizerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RegisterTokenizerError::SelectFts5Failed(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// 使用连接上最近一次的错误信息，将 SQLite 返回的错误码转换成 [`rusqlite::Error`]
+unsafe fn last_sqlite_error(dbp: *mut sqlite3, rc: c_int) -> rusqlite::Error {
+    let message = unsafe { sqlite3_errmsg(dbp) };
+    let message = (!message.is_null()).then(|| {
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    });
+    rusqlite::Error::SqliteFailure(rusqlite::ffi::Error::new(rc), message)
+}
 
 /// 内部获取 fts5_api 指针的方法
 unsafe fn get_fts5_api(db: &Connection) -> Result<*mut fts5_api, RegisterTokenizerError> {
@@ -289,16 +458,18 @@ unsafe fn get_fts5_api(db: &Connection) -> Result<*mut fts5_api, RegisterTokeniz
     const FTS5_QUERY_STATEMENT: &CStr = c"SELECT fts5(?1)";
     const FTS5_QUERY_STATEMENT_LEN: c_int = FTS5_QUERY_STATEMENT.count_bytes() as c_int;
     unsafe {
-        if sqlite3_prepare_v3(
+        let rc = sqlite3_prepare_v3(
             dbp,
             FTS5_QUERY_STATEMENT.as_ptr(),
             FTS5_QUERY_STATEMENT_LEN,
             SQLITE_PREPARE_PERSISTENT,
             &mut stmt,
             std::ptr::null_mut(),
-        ) != SQLITE_OK
-        {
-            return Err(RegisterTokenizerError::SelectFts5Failed);
+        );
+        if rc != SQLITE_OK {
+            return Err(RegisterTokenizerError::SelectFts5Failed(last_sqlite_error(
+                dbp, rc,
+            )));
         }
         sqlite3_bind_pointer(
             stmt,
@@ -307,8 +478,13 @@ unsafe fn get_fts5_api(db: &Connection) -> Result<*mut fts5_api, RegisterTokeniz
             c"fts5_api_ptr".as_ptr(),
             None,
         );
-        sqlite3_step(stmt);
+        let rc = sqlite3_step(stmt);
+        // 先取得错误信息，再释放语句
+        let error = (rc != SQLITE_ROW && rc != SQLITE_DONE).then(|| last_sqlite_error(dbp, rc));
         sqlite3_finalize(stmt);
+        if let Some(error) = error {
+            return Err(RegisterTokenizerError::SelectFts5Failed(error));
+        }
     }
     if api.is_null() {
         return Err(RegisterTokenizerError::Fts5ApiNul);
@@ -316,34 +492,158 @@ unsafe fn get_fts5_api(db: &Connection) -> Result<*mut fts5_api, RegisterTokeniz
     Ok(api)
 }
 
+/// 使用默认的全局数据注册全部内置的 Tokenizer：`simple`、`jieba`、`keyword`、`ngram`、`unicode`
+///
+/// 依次注册，某一个注册失败时立即返回错误，已经注册成功的 Tokenizer 仍然保留，不会被撤销
+pub fn register_all_tokenizers(db: &Connection) -> Result<(), RegisterTokenizerError> {
+    register_tokenizer::<simple_tokenizer::SimpleTokenizer>(
+        db,
+        simple_tokenizer::SimpleGlobal::default(),
+    )?;
+    register_tokenizer::<jieba_tokenizer::JiebaTokenizer>(
+        db,
+        jieba_tokenizer::JiebaGlobal::default(),
+    )?;
+    register_tokenizer::<keyword_tokenizer::KeywordTokenizer>(db, ())?;
+    register_tokenizer::<ngram_tokenizer::NgramTokenizer>(db, ())?;
+    register_tokenizer::<unicode_tokenizer::UnicodeWordsTokenizer>(db, ())?;
+    Ok(())
+}
+
+/// 获取运行时 fts5_api 的版本，版本低于 3 时使用 v1 接口注册 Tokenizer，分词时无法获取 locale
+///
+/// 可以在注册之前和 [`FTS5_API_VERSION`]、[`MIN_FTS5_API_VERSION`] 比较，排查
+/// [`RegisterTokenizerError::Fts5ApiVersionTooLow`] 和 [`RegisterTokenizerError::Fts5ApiVersionUnsupported`] 错误
+pub fn fts5_api_version(db: &Connection) -> Result<i32, RegisterTokenizerError> {
+    unsafe {
+        let api: *mut fts5_api = get_fts5_api(db)?;
+        Ok((*api).iVersion)
+    }
+}
+
+/// 注册分词器使用的 fts5_api 接口
+enum CreateTokenizer {
+    /// `xCreateTokenizer_v2`，分词时可以获取 locale
+    V2(
+        unsafe extern "C" fn(
+            *mut fts5_api,
+            *const c_char,
+            *mut c_void,
+            *mut fts5_tokenizer_v2,
+            Option<unsafe extern "C" fn(*mut c_void)>,
+        ) -> c_int,
+    ),
+    /// `xCreateTokenizer`，旧版本的 SQLite 只有这个接口，分词时没有 locale
+    V1(
+        unsafe extern "C" fn(
+            *mut fts5_api,
+            *const c_char,
+            *mut c_void,
+            *mut fts5_tokenizer,
+            Option<unsafe extern "C" fn(*mut c_void)>,
+        ) -> c_int,
+    ),
+}
+
+impl CreateTokenizer {
+    /// 优先使用 v2 接口，fts5_api 的版本低于 3 或者 v2 接口为空时退回 v1 接口
+    ///
+    /// 版本低于 [`MIN_FTS5_API_VERSION`] 时结构体的布局未知，直接返回错误
+    unsafe fn detect(api: *mut fts5_api) -> Result<Self, RegisterTokenizerError> {
+        let version = unsafe { (*api).iVersion };
+        if version < MIN_FTS5_API_VERSION {
+            return Err(RegisterTokenizerError::Fts5ApiVersionUnsupported(version));
+        }
+        if version >= FTS5_API_VERSION
+            && let Some(create_tokenizer) = unsafe { (*api).xCreateTokenizer_v2 }
+        {
+            return Ok(Self::V2(create_tokenizer));
+        }
+        match unsafe { (*api).xCreateTokenizer } {
+            Some(create_tokenizer) => Ok(Self::V1(create_tokenizer)),
+            None if version < FTS5_API_VERSION => Err(RegisterTokenizerError::Fts5ApiVersionTooLow),
+            None => Err(RegisterTokenizerError::Fts5xCreateTokenizerV2Nul),
+        }
+    }
+}
+
+/// 检查连接上是否已经注册了指定名称的分词器，包括 fts5 内置的 `unicode61`、`ascii`、`porter`、`trigram`
+///
+/// 通过 `fts5_api.xFindTokenizer_v2` 查找，无法获取 fts5_api 或者版本过低时返回 `false`。
+/// fts5 不支持注销分词器，需要替换时使用相同的名称重新注册，参考 [`register_tokenizer_named`]
+pub fn is_tokenizer_registered(db: &Connection, name: &CStr) -> bool {
+    unsafe {
+        let Ok(api) = get_fts5_api(db) else {
+            return false;
+        };
+        if (*api).iVersion < FTS5_API_VERSION {
+            return false;
+        }
+        let Some(find_tokenizer) = (*api).xFindTokenizer_v2 else {
+            return false;
+        };
+        let mut user_data: *mut c_void = std::ptr::null_mut();
+        let mut tokenizer: *mut fts5_tokenizer_v2 = std::ptr::null_mut();
+        find_tokenizer(api, name.as_ptr(), &mut user_data, &mut tokenizer) == SQLITE_OK
+    }
+}
+
 /// 注册 Tokenizer
+///
+/// 同一个连接可以重复注册，规则和 [`register_tokenizer_named`] 相同
 pub fn register_tokenizer<T: Tokenizer>(
     db: &Connection,
     global_data: T::Global,
+) -> Result<(), RegisterTokenizerError> {
+    register_tokenizer_named::<T>(db, T::name(), global_data)
+}
+
+/// 使用指定的名称注册 Tokenizer，代替 [`Tokenizer::name`] 返回的名称
+///
+/// 可以将同一个 Tokenizer 使用不同的全局数据注册多次，或者避免和其他扩展注册的分词器重名。
+///
+/// 名称已经存在时，新注册的分词器替换原来的分词器，之后创建或者打开的表使用新的分词器，
+/// 已经打开的表继续使用原来的分词器。原来的全局数据不会被立即释放，而是和新的全局数据一样，
+/// 在连接关闭时释放，因此重复注册不会造成重复释放。注册失败时，全局数据在返回错误之前释放
+pub fn register_tokenizer_named<T: Tokenizer>(
+    db: &Connection,
+    name: &CStr,
+    global_data: T::Global,
 ) -> Result<(), RegisterTokenizerError> {
     unsafe {
         let api: *mut fts5_api = get_fts5_api(db)?;
+        let create_tokenizer = CreateTokenizer::detect(api)?;
+        // 全局数据的所有权交给 fts5，注册成功后由 x_destroy 释放
         let global_data = Box::into_raw(Box::new(global_data));
-        if (*api).iVersion < FTS5_API_VERSION {
-            return Err(RegisterTokenizerError::Fts5ApiVersionTooLow);
-        }
         // 注册tokenizer
-        let rc = ((*api)
-            .xCreateTokenizer_v2
-            .as_ref()
-            .ok_or(RegisterTokenizerError::Fts5xCreateTokenizerV2Nul)?)(
-            api,
-            T::name().as_ptr(),
-            global_data.cast::<c_void>(),
-            &mut fts5_tokenizer_v2 {
-                iVersion: FTS5_TOKENIZER_VERSION,
-                xCreate: Some(x_create::<T>),
-                xDelete: Some(x_delete::<T>),
-                xTokenize: Some(x_tokenize::<T>),
-            },
-            Some(x_destroy::<T>),
-        );
+        let rc = match create_tokenizer {
+            CreateTokenizer::V2(create_tokenizer) => create_tokenizer(
+                api,
+                name.as_ptr(),
+                global_data.cast::<c_void>(),
+                &mut fts5_tokenizer_v2 {
+                    iVersion: FTS5_TOKENIZER_VERSION,
+                    xCreate: Some(x_create::<T>),
+                    xDelete: Some(x_delete::<T>),
+                    xTokenize: Some(x_tokenize::<T>),
+                },
+                Some(x_destroy::<T>),
+            ),
+            CreateTokenizer::V1(create_tokenizer) => create_tokenizer(
+                api,
+                name.as_ptr(),
+                global_data.cast::<c_void>(),
+                &mut fts5_tokenizer {
+                    xCreate: Some(x_create::<T>),
+                    xDelete: Some(x_delete::<T>),
+                    xTokenize: Some(x_tokenize_v1::<T>),
+                },
+                Some(x_destroy::<T>),
+            ),
+        };
         if rc != SQLITE_OK {
+            // 注册失败时 fts5 不会调用 x_destroy，需要在这里释放
+            drop(Box::from_raw(global_data));
             return Err(RegisterTokenizerError::Fts5xCreateTokenizerFailed(rc));
         }
         Ok(())
@@ -352,15 +652,97 @@ pub fn register_tokenizer<T: Tokenizer>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tokenizer::jieba_tokenizer::JiebaTokenizer;
-    use crate::tokenizer::register_tokenizer;
-    use crate::tokenizer::simple_tokenizer::SimpleTokenizer;
+    use crate::tokenizer::jieba_tokenizer::{JiebaGlobal, JiebaTokenizer};
+    use crate::tokenizer::keyword_tokenizer::KeywordTokenizer;
+    use crate::tokenizer::ngram_tokenizer::NgramTokenizer;
+    use crate::tokenizer::simple_tokenizer::{SimpleGlobal, SimpleTokenizer};
+    use crate::tokenizer::standalone::tokenize_with_positions;
+    use crate::tokenizer::unicode_tokenizer::UnicodeWordsTokenizer;
+    use crate::tokenizer::x_tokenize;
+    use crate::tokenizer::{
+        FTS5_API_VERSION, IntoTokenizeReasonError, RegisterTokenizerError, TokenizeReason,
+        Tokenizer, fts5_api_version, get_fts5_api, is_tokenizer_registered,
+        register_all_tokenizers, register_tokenizer, register_tokenizer_named,
+        reset_global_stopwords, set_global_stopwords,
+    };
     use rusqlite::Connection;
+    use rusqlite::ffi::{
+        FTS5_TOKENIZE_AUX, FTS5_TOKENIZE_DOCUMENT, FTS5_TOKENIZE_PREFIX, FTS5_TOKENIZE_QUERY,
+        SQLITE_OK,
+    };
+    use rusqlite::functions::FunctionFlags;
+    use std::ffi::{CStr, c_char, c_int, c_void};
+    use std::ops::Range;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, PoisonError};
+
+    /// 替换全局停词表的测试依次执行，避免互相影响新建的表使用的停词表
+    static GLOBAL_STOPWORDS_LOCK: Mutex<()> = Mutex::new(());
+
+    /// 执行查询语句，收集每一行第一列的文本
+    pub(super) fn query_texts(conn: &Connection, sql: &str) -> Vec<String> {
+        let mut stmt = conn.prepare(sql).unwrap();
+        let result = stmt
+            .query_map([], |row| Ok(row.get::<_, String>(0).unwrap()))
+            .unwrap();
+        let mut vec = Vec::new();
+        for row in result {
+            let row = row.unwrap();
+            vec.push(row)
+        }
+        vec
+    }
+
+    #[test]
+    fn test_tokenize_reason_try_from() {
+        for (flag, reason) in [
+            (FTS5_TOKENIZE_DOCUMENT, TokenizeReason::Document),
+            (FTS5_TOKENIZE_QUERY, TokenizeReason::Query { prefix: false }),
+            (
+                FTS5_TOKENIZE_QUERY | FTS5_TOKENIZE_PREFIX,
+                TokenizeReason::Query { prefix: true },
+            ),
+            (FTS5_TOKENIZE_AUX, TokenizeReason::Aux),
+        ] {
+            assert_eq!(reason, TokenizeReason::try_from(flag).unwrap(), "{flag}");
+        }
+        // 单独的 FTS5_TOKENIZE_PREFIX 和其他组合都不是 FTS5 会传入的值
+        for flag in [
+            FTS5_TOKENIZE_PREFIX,
+            FTS5_TOKENIZE_DOCUMENT | FTS5_TOKENIZE_PREFIX,
+            FTS5_TOKENIZE_QUERY | FTS5_TOKENIZE_AUX,
+            -1,
+            0x100,
+        ] {
+            let result = TokenizeReason::try_from(flag);
+            assert!(
+                matches!(result, Err(IntoTokenizeReasonError::UnrecognizedValue(value)) if value == flag),
+                "{flag}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_tokenize_reason_to_flag() {
+        assert_eq!(FTS5_TOKENIZE_DOCUMENT, TokenizeReason::Document.to_flag());
+        assert_eq!(
+            FTS5_TOKENIZE_QUERY | FTS5_TOKENIZE_PREFIX,
+            TokenizeReason::Query { prefix: true }.to_flag()
+        );
+        for reason in [
+            TokenizeReason::Document,
+            TokenizeReason::Query { prefix: false },
+            TokenizeReason::Query { prefix: true },
+            TokenizeReason::Aux,
+        ] {
+            assert_eq!(reason, TokenizeReason::try_from(reason.to_flag()).unwrap());
+        }
+    }
 
     #[test]
     fn test_register_simple_tokenizer_with_pinyin() {
         let conn = Connection::open_in_memory().unwrap();
-        register_tokenizer::<SimpleTokenizer>(&conn, ()).unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
         // 创建一个测试表
         conn.execute(
             "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple');",
@@ -388,10 +770,294 @@ mod tests {
         assert_eq!(["中华人民共和国国歌", "国家", "铁锅"], vec.as_slice());
     }
 
+    #[test]
+    fn test_register_simple_tokenizer_with_keep_han() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple keep_han');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('锅铲'),('国家'),('quokka');",
+            [],
+        )
+        .unwrap();
+        // 查询汉字只匹配相同的汉字，查询拼音匹配全部拼音相同的汉字
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '国';");
+        assert_eq!(["国家"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'guo';");
+        assert_eq!(["锅铲", "国家"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"国家\"';");
+        assert_eq!(["国家"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '国*';");
+        assert_eq!(["国家"], vec.as_slice());
+        // 汉字相同的文档同时匹配两个短语，bm25 的排名高于只有拼音相同的文档
+        let mut stmt = conn
+            .prepare("SELECT text, bm25(t1) FROM t1 WHERE text MATCH '国 OR guo' ORDER BY rank;")
+            .unwrap();
+        let ranked = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+            })
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ["国家", "锅铲"],
+            ranked
+                .iter()
+                .map(|(text, _)| text.as_str())
+                .collect::<Vec<_>>()
+                .as_slice()
+        );
+        assert!(ranked[0].1 < ranked[1].1, "{ranked:?}");
+        // 汉字本身是主要的 token，拼音作为 colocated token 写入同一个位置
+        let tokens = tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["keep_han".to_owned()],
+            TokenizeReason::Document,
+            "行".as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|t| (String::from_utf8(t.token).unwrap(), t.colocated))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            [
+                ("行", false),
+                ("hang", true),
+                ("heng", true),
+                ("xing", true)
+            ]
+            .map(|(token, colocated)| (token.to_owned(), colocated))
+            .as_slice(),
+            tokens.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_polyphonic_pinyin() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            r#"INSERT INTO t1(text) VALUES ('银行'),('行走'),('国家');"#,
+            [],
+        )
+        .unwrap();
+        // 多音字的每个读音都可以匹配
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'hang';");
+        assert_eq!(["银行", "行走"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'xing';");
+        assert_eq!(["银行", "行走"], vec.as_slice());
+        // 多个读音在同一个位置上，短语查询可以匹配相邻的字
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"yin hang\"';");
+        assert_eq!(["银行"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"xing zou\"';");
+        assert_eq!(["行走"], vec.as_slice());
+        // 查询多音字时，任意一个读音都可以匹配
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"银行\"';");
+        assert_eq!(["银行"], vec.as_slice());
+        let tokens = tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            Vec::new(),
+            TokenizeReason::Document,
+            "行".as_bytes(),
+        )
+        .unwrap();
+        assert!(tokens.len() > 1);
+        assert!(tokens.iter().all(|t| t.range == (0..3) && t.position == 0));
+        assert!(tokens.iter().skip(1).all(|t| t.colocated));
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_fuzzy_pinyin() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [
+            ("t1", "simple disable_stopword"),
+            ("t2", "simple fuzzy_pinyin"),
+        ] {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('诗歌'),('蓝天'),('英雄');"),
+                [],
+            )
+            .unwrap();
+        }
+        // 不开启模糊音时，sh 和 s 不同
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'si';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'shi';");
+        assert_eq!(["诗歌"], vec.as_slice());
+        // 开启模糊音后，写入和查询使用相同的映射
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'si';");
+        assert_eq!(["诗歌"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'shi';");
+        assert_eq!(["诗歌"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'nan';");
+        assert_eq!(["蓝天"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'yin';");
+        assert_eq!(["英雄"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_full_pinyin() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple full_pinyin');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            r#"INSERT INTO t1(text) VALUES ('中华人民共和国国歌'),('静夜思'),('国家'),('铁锅'),('国 家'),('举头望明月');"#,
+            [],
+        )
+        .unwrap();
+        // 连续的汉字额外写入拼接的全拼，被空格隔开的汉字不拼接
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'guojia';");
+        assert_eq!(["国家"], vec.as_slice());
+        // 逐字的拼音仍然可以匹配
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '国';");
+        assert_eq!(
+            ["中华人民共和国国歌", "国家", "铁锅", "国 家"],
+            vec.as_slice()
+        );
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'jingyesi';");
+        assert_eq!(["静夜思"], vec.as_slice());
+        // 拼接的全拼覆盖整个汉字片段
+        let tokens = tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["full_pinyin".to_owned()],
+            TokenizeReason::Document,
+            "静夜思".as_bytes(),
+        )
+        .unwrap();
+        let full = tokens.iter().find(|t| t.token == b"jingyesi").unwrap();
+        assert_eq!(0..9, full.range);
+        assert!(full.colocated);
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_pinyin_sep() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [
+            ("t1", r#"'simple full_pinyin'"#),
+            ("t2", r#""simple 'pinyin_sep='''""#),
+        ] {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = {tokenize});"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('西安'),('先');"),
+                [],
+            )
+            .unwrap();
+        }
+        // 不使用分隔符时，xian 有歧义，同时匹配 西安 和 先
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'xian';");
+        assert_eq!(["西安", "先"], vec.as_slice());
+        // 使用分隔符时，xi'an 只匹配 西安，xian 只匹配 先
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '\"xi''an\"';");
+        assert_eq!(["西安"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'xian';");
+        assert_eq!(["先"], vec.as_slice());
+        // 会切分单词的分隔符
+        for separator in ["-", " "] {
+            let result = conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE t3 USING fts5(text, tokenize = \"simple 'pinyin_sep={separator}'\");"
+                ),
+                [],
+            );
+            assert!(result.is_err(), "{separator}");
+        }
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_pinyin_initials() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple pinyin_initials');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            r#"INSERT INTO t1(text) VALUES ('中华人民共和国'),('重庆'),('长城'),('国家');"#,
+            [],
+        )
+        .unwrap();
+        // 两个字母的声母和一个字母的首字母都可以匹配，停词 人、和 仍然参与拼接
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'zhhrmghg';");
+        assert_eq!(["中华人民共和国"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'zhrmghg';");
+        assert_eq!(["中华人民共和国"], vec.as_slice());
+        // 多音字的每个读音都生成首字母
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'cq';");
+        assert_eq!(["重庆"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'zhq';");
+        assert_eq!(["重庆"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'chc OR zc';");
+        assert_eq!(["长城"], vec.as_slice());
+        // 首字母的范围仍然是原始文本中的字节范围
+        let tokens = tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["pinyin_initials".to_owned()],
+            TokenizeReason::Document,
+            "a重庆".as_bytes(),
+        )
+        .unwrap();
+        let initials = tokens.iter().find(|t| t.token == b"cq").unwrap();
+        assert_eq!(1..7, initials.range);
+        let initial = tokens.iter().find(|t| t.token == b"q").unwrap();
+        assert_eq!(4..7, initial.range);
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_pinyin_tone() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple pinyin_tone');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            r#"INSERT INTO t1(text) VALUES ('国家'),('铁锅'),('马');"#,
+            [],
+        )
+        .unwrap();
+        // 带声调的拼音区分同音字
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'guó';");
+        assert_eq!(["国家"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'guō';");
+        assert_eq!(["铁锅"], vec.as_slice());
+        // 不带声调的拼音仍然可以匹配
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'guo';");
+        assert_eq!(["国家", "铁锅"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'mǎ';");
+        assert_eq!(["马"], vec.as_slice());
+    }
+
     #[test]
     fn test_register_simple_tokenizer_no_with_pinyin() {
         let conn = Connection::open_in_memory().unwrap();
-        register_tokenizer::<SimpleTokenizer>(&conn, ()).unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
         // 创建一个测试表, simple 不开启 pinyin 分词
         conn.execute(
             "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple disable_pinyin');",
@@ -447,7 +1113,7 @@ mod tests {
     #[test]
     fn test_register_simple_tokenizer_no_with_stopword() {
         let conn = Connection::open_in_memory().unwrap();
-        register_tokenizer::<SimpleTokenizer>(&conn, ()).unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
         // 创建一个测试表, simple 不启用停词表
         conn.execute(
             "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple disable_stopword');",
@@ -475,37 +1141,1063 @@ mod tests {
     }
 
     #[test]
-    fn test_register_jieba_tokenizer() {
+    fn test_register_simple_tokenizer_with_custom_stopword() {
+        let dir = std::env::temp_dir().join(format!("simple_stopword_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let stopword = dir.join("stopword.txt");
+        std::fs::write(&stopword, "# 自定义停词表\n\n  SQLite  \n").unwrap();
+
         let conn = Connection::open_in_memory().unwrap();
-        register_tokenizer::<JiebaTokenizer>(&conn, ()).unwrap();
-        // 创建一个测试表, simple 不开启 pinyin 分词
-        conn.execute(
-            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'jieba');",
-            [],
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [
+            ("t1", "simple".to_owned()),
+            ("t2", format!("simple 'stopword={}'", stopword.display())),
+            (
+                "t3",
+                format!("simple 'stopword={}'", dir.join("missing.txt").display()),
+            ),
+        ] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = \"{tokenize}\");"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('hello sqlite');"),
+                [],
+            )
+            .unwrap();
+        }
+        // 默认停词表中有 hello，没有 sqlite
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'sqlite';");
+        assert_eq!(["hello sqlite"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'hello';");
+        assert_eq!(0, vec.len());
+        // 自定义停词表代替默认停词表
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'sqlite';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'hello';");
+        assert_eq!(["hello sqlite"], vec.as_slice());
+        // 停词表文件不存在时，使用默认停词表
+        let vec = query_texts(&conn, "SELECT * FROM t3 WHERE text MATCH 'sqlite';");
+        assert_eq!(["hello sqlite"], vec.as_slice());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_synonyms() {
+        let dir = std::env::temp_dir().join(format!("simple_synonyms_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let synonyms = dir.join("synonyms.txt");
+        std::fs::write(
+            &synonyms,
+            "# 同义词表\nUSA,America\ncar, automobile, vehicle\n",
         )
         .unwrap();
-        // 插入数据
-        conn.execute(
-            "INSERT INTO t1(text) VALUES ('中华人民共和国国歌'),('静夜思'),('国家'),('举头望明月'),('like'),('liking'),('liked');",
-            [],
-        )
-            .unwrap();
-        let mut stmt = conn
-            .prepare("SELECT * FROM t1 WHERE text MATCH '国家';")
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [
+            ("t1", "simple".to_owned()),
+            ("t2", format!("simple 'synonyms={}'", synonyms.display())),
+        ] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = \"{tokenize}\");"
+                ),
+                [],
+            )
             .unwrap();
-        let result = stmt
-            .query_map([], |row| Ok(row.get::<_, String>(0).unwrap()))
+            conn.execute(
+                &format!(
+                    "INSERT INTO {table}(text) VALUES ('USA'),('automobile'),('car'),('cars');"
+                ),
+                [],
+            )
             .unwrap();
-        for row in result {
-            let row = row.unwrap();
-            assert_eq!("国家".to_owned(), row);
+        }
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'america';");
+        assert_eq!(0, vec.len());
+        // 查询任意一个同义词都可以匹配其余的同义词
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'america';");
+        assert_eq!(["USA"], vec.as_slice());
+        // 查询的同义词做词干提取后匹配
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'vehicles';");
+        assert_eq!(["automobile", "car"], vec.as_slice());
+        // 同义词和归一化后的单词比较，不和词干比较
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'automobile';");
+        assert_eq!(["automobile", "car"], vec.as_slice());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_stopword_add() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [
+            (
+                "t1",
+                "simple 'stopword_add=sqlite' 'stopword_add=Tokenizer'",
+            ),
+            ("t2", "simple 'stopword_add=sqlite' disable_stopword"),
+        ] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = \"{tokenize}\");"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('hello sqlite tokenizer learning');"),
+                [],
+            )
+            .unwrap();
+        }
+        // 添加的停词和默认停词表合并
+        for query in ["sqlite", "tokenizer", "hello"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t1 WHERE text MATCH '{query}';"),
+            );
+            assert_eq!(0, vec.len(), "{query}");
+        }
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'learning';");
+        assert_eq!(["hello sqlite tokenizer learning"], vec.as_slice());
+        // 不启用停词表时，只使用添加的停词
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'sqlite';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'hello';");
+        assert_eq!(["hello sqlite tokenizer learning"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_effective_stopwords() {
+        let new = |args: &[&str]| {
+            let args = args.iter().map(|arg| (*arg).to_owned()).collect();
+            SimpleTokenizer::new(&SimpleGlobal::default(), args).unwrap()
+        };
+        let stopwords = new(&[]).effective_stopwords().into_owned();
+        assert!(stopwords.contains("the") && stopwords.contains("的"));
+        // 添加的停词和默认的停词表一起生效，其他测试可能临时替换全局停词表，只检查包含内置的停词
+        let tokenizer = new(&["stopword_add=quokka", "stopword_add=Zephyr"]);
+        let effective = tokenizer.effective_stopwords();
+        assert!(effective.contains("quokka") && effective.contains("zephyr"));
+        assert!(effective.is_superset(&crate::STOPWORD.iter().map(|w| (*w).to_owned()).collect()));
+        // 不启用停词表时只有添加的停词
+        let tokenizer = new(&["disable_stopword", "stopword_add=quokka"]);
+        assert_eq!(
+            ["quokka".to_owned()]
+                .into_iter()
+                .collect::<std::collections::HashSet<_>>(),
+            tokenizer.effective_stopwords().into_owned()
+        );
+        assert!(new(&["disable_stopword"]).effective_stopwords().is_empty());
+    }
+
+    #[test]
+    fn test_config_summary() {
+        let args = |args: &[&str]| args.iter().map(|arg| (*arg).to_owned()).collect();
+        let simple =
+            |list: &[&str]| SimpleTokenizer::new(&SimpleGlobal::default(), args(list)).unwrap();
+        assert_eq!(
+            "simple(pinyin=on,stopword=on,stemming=on)",
+            simple(&[]).config_summary()
+        );
+        // 默认参数不改变摘要
+        assert_eq!(
+            "simple(pinyin=on,stopword=on,stemming=on)",
+            simple(
+                &SimpleTokenizer::default_args()
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+            )
+            .config_summary()
+        );
+        let tokenizer = simple(&[
+            "disable_pinyin",
+            "stemmer=french",
+            "stopword_add=quokka",
+            "ligatures=all",
+            "raw_mode=whitespace,lowercase",
+            "numbers=drop",
+            "max_tokens=8",
+        ]);
+        let summary = "simple(pinyin=off,stopword=custom,stemming=on,stemmer=french,ligatures=all,raw_mode=whitespace|lowercase,numbers=drop,max_tokens=8)";
+        assert_eq!(summary, tokenizer.config_summary());
+        assert_eq!(summary, format!("{tokenizer:?}"));
+        let jieba =
+            |list: &[&str]| JiebaTokenizer::new(&JiebaGlobal::default(), args(list)).unwrap();
+        assert_eq!(
+            "jieba(stopword=on,stemming=on)",
+            jieba(&[]).config_summary()
+        );
+        assert_eq!(
+            "jieba(stopword=off,stemming=on,hmm=off,mode=search,pos_filter=n|v,symbols=drop)",
+            format!(
+                "{:?}",
+                jieba(&[
+                    "disable_stopword",
+                    "disable_hmm",
+                    "mode=search",
+                    "pos_filter=v,n",
+                    "symbols=drop"
+                ])
+            )
+        );
+        let tokenizer =
+            UnicodeWordsTokenizer::new(&(), args(&["disable_stemming", "keep_diacritics"]))
+                .unwrap();
+        assert_eq!(
+            "unicode(stopword=on,stemming=off,keep_diacritics=on)",
+            format!("{tokenizer:?}")
+        );
+        let tokenizer = NgramTokenizer::new(&(), args(&["n=3"])).unwrap();
+        assert_eq!("ngram(n=3)", format!("{tokenizer:?}"));
+        let tokenizer = KeywordTokenizer::new(&(), args(&["lowercase"])).unwrap();
+        assert_eq!("keyword(lowercase=on)", format!("{tokenizer:?}"));
+    }
+
+    #[test]
+    fn test_set_global_stopwords() {
+        let _lock = GLOBAL_STOPWORDS_LOCK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple');",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO t1(text) VALUES ('ephemeral sqlite');", [])
+            .unwrap();
+        // 其他测试同时在使用全局停词表，替换成内置停词表加上一个其他测试不会用到的单词
+        let stopwords = crate::STOPWORD
+            .iter()
+            .map(|word| (*word).to_owned())
+            .chain(["Ephemeral".to_owned()]);
+        set_global_stopwords(stopwords);
+        conn.execute("INSERT INTO t1(text) VALUES ('ephemeral tokenizer');", [])
+            .unwrap();
+        // 替换之后新建的表使用新的停词表
+        conn.execute(
+            "CREATE VIRTUAL TABLE t2 USING fts5(text, tokenize = 'simple');",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO t2(text) VALUES ('ephemeral tokenizer');", [])
+            .unwrap();
+        // 添加的停词以当前的全局停词表为基础
+        let tokenizer = SimpleTokenizer::new(
+            &SimpleGlobal::default(),
+            vec!["stopword_add=Quokka".to_owned()],
+        )
+        .unwrap();
+        reset_global_stopwords();
+        let stopwords = tokenizer.effective_stopwords();
+        for word in ["ephemeral", "quokka", "the"] {
+            assert!(stopwords.contains(word), "{word}");
+        }
+        // 已经打开的表继续使用创建分词器时的停词表，替换之后写入的文档同样写入 ephemeral
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'ephemeral';");
+        assert_eq!(["ephemeral sqlite", "ephemeral tokenizer"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'tokenizer';");
+        assert_eq!(["ephemeral tokenizer"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'ephemeral';");
+        assert_eq!(0, vec.len());
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_without_stemming() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple disable_stemming disable_stopword');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('like'),('liking'),('liked'),('国家');",
+            [],
+        )
+        .unwrap();
+        // 不做词干提取时，只匹配相同的单词
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'like';");
+        assert_eq!(["like"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'liking';");
+        assert_eq!(["liking"], vec.as_slice());
+        // 拼音不受影响
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'guo';");
+        assert_eq!(["国家"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_stem_documents_only() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [
+            ("t1", "simple disable_stopword"),
+            ("t2", "simple disable_stopword stem_documents_only"),
+        ] {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('like'),('liking'),('liked');"),
+                [],
+            )
+            .unwrap();
+        }
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'liking';");
+        assert_eq!(["like", "liking", "liked"], vec.as_slice());
+        // 文档写入的是词干，查询词干可以匹配全部文档
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'like';");
+        assert_eq!(["like", "liking", "liked"], vec.as_slice());
+        // 查询的单词不做词干提取，不是词干时匹配不到任何文档
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'liking';");
+        assert_eq!(0, vec.len());
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_keep_original() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [
+            ("t1", "simple disable_stopword stem_documents_only"),
+            (
+                "t2",
+                "simple disable_stopword stem_documents_only keep_original",
+            ),
+            ("t3", "simple disable_stopword keep_original"),
+        ] {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('running');"),
+                [],
+            )
+            .unwrap();
+        }
+        // 只写入词干时，原本的单词无法完全匹配
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'running';");
+        assert_eq!(0, vec.len());
+        for table in ["t2", "t3"] {
+            for query in ["run", "running"] {
+                let vec = query_texts(
+                    &conn,
+                    &format!("SELECT * FROM {table} WHERE text MATCH '{query}';"),
+                );
+                assert_eq!(["running"], vec.as_slice(), "{table} {query}");
+            }
+        }
+        // 原本的单词和词干写入同一个位置
+        let tokens = tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["keep_original".to_owned()],
+            TokenizeReason::Document,
+            "running sqlite".as_bytes(),
+        )
+        .unwrap();
+        let tokens = tokens
+            .iter()
+            .map(|t| (String::from_utf8(t.token.clone()).unwrap(), t.colocated))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            [("run", false), ("running", true), ("sqlite", false)]
+                .map(|(token, colocated)| (token.to_owned(), colocated))
+                .as_slice(),
+            tokens.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_prefix_query() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('国家'),('sqlite tokenizer'),('golang');",
+            [],
+        )
+        .unwrap();
+        // gu 和 go 是停词，作为前缀查询时仍然需要保留
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'gu*';");
+        assert_eq!(["国家"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'go*';");
+        assert_eq!(["golang"], vec.as_slice());
+        // 只有最后一个单词是前缀，前面的停词仍然被过滤
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"sqlite to\"*';");
+        assert_eq!(["sqlite tokenizer"], vec.as_slice());
+        let vec = query_texts(
+            &conn,
+            "SELECT * FROM t1 WHERE text MATCH '\"the sqlite\"*';",
+        );
+        assert_eq!(["sqlite tokenizer"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_pinyin_prefix_query() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple full_pinyin');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('国家'),('过去'),('谷歌'),('人民');",
+            [],
+        )
+        .unwrap();
+        // 拉丁字母的前缀匹配拼音的前缀
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'gu*';");
+        assert_eq!(["国家", "过去", "谷歌"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'guoj*';");
+        assert_eq!(["国家"], vec.as_slice());
+        // 汉字作为前缀时使用拼音作为前缀，过 是停词，作为前缀查询时仍然保留
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '国*';");
+        assert_eq!(["国家", "过去"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '过*';");
+        assert_eq!(["国家", "过去"], vec.as_slice());
+        // 多音字只使用最常用的读音，家 的 gu 不会匹配 谷歌
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '家*';");
+        assert_eq!(["国家"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"国 家\"*';");
+        assert_eq!(["国家"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_stemmer() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [("t1", "simple"), ("t2", "simple 'stemmer=french'")] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = \"{tokenize}\");"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('les chevaux'),('généralement');"),
+                [],
+            )
+            .unwrap();
+        }
+        // 英语的词干提取不认识法语单词，也不处理带重音的单词
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'cheval';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'général';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'cheval';");
+        assert_eq!(["les chevaux"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'général';");
+        assert_eq!(["généralement"], vec.as_slice());
+        // 不支持的语言
+        let result = conn.execute(
+            "CREATE VIRTUAL TABLE t3 USING fts5(text, tokenize = \"simple 'stemmer=klingon'\");",
+            [],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_register_tokenizer_with_unknown_args() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        // 拼写错误的参数导致建表失败
+        for tokenize in ["simple disable_stopwrd", "jieba disable_stopwrd"] {
+            let result = conn.execute(
+                &format!("CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            );
+            assert!(result.is_err(), "{tokenize}");
+        }
+        // 错误信息中列出全部无法识别的参数
+        let args = ["disable_stopwrd", "disable_pinyin", "shingel=3"].map(str::to_owned);
+        let error = match SimpleTokenizer::new(&SimpleGlobal::default(), args.to_vec()) {
+            Ok(_) => panic!("unknown arguments should be rejected"),
+            Err(error) => error.to_string(),
+        };
+        assert!(error.contains("disable_stopwrd, shingel=3"), "{error}");
+        // jieba 不支持拼音相关的参数
+        assert!(JiebaTokenizer::new(&JiebaGlobal::default(), args.to_vec()).is_err());
+    }
+
+    /// 释放时计数的全局数据，用于检查全局数据是否被释放以及释放的次数
+    struct CountedGlobal(Arc<AtomicUsize>);
+
+    impl Drop for CountedGlobal {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// 将整个文本作为一个 token 写入，使用 [`CountedGlobal`] 作为全局数据的分词器
+    struct CountedTokenizer;
+
+    impl Tokenizer for CountedTokenizer {
+        type Global = CountedGlobal;
+
+        fn name() -> &'static CStr {
+            c"counted"
+        }
+
+        fn new(_global: &Self::Global, _args: Vec<String>) -> Result<Self, rusqlite::Error> {
+            Ok(Self)
+        }
+
+        fn tokenize<TKF>(
+            &mut self,
+            _reason: TokenizeReason,
+            text: &[u8],
+            mut push_token: TKF,
+        ) -> Result<(), rusqlite::Error>
+        where
+            TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>,
+        {
+            (push_token)(text, 0..text.len(), false)
+        }
+    }
+
+    /// 分词器和全局数据的创建、释放次数，用于检查 FFI 生命周期中是否有泄漏或者重复释放
+    #[derive(Default)]
+    struct LifecycleCounters {
+        /// 成功创建的分词器数量
+        created: AtomicUsize,
+        /// 释放的分词器数量
+        deleted: AtomicUsize,
+        /// 释放的全局数据数量
+        destroyed: AtomicUsize,
+    }
+
+    /// 释放时计数的全局数据，和 [`LifecycleTokenizer`] 共享计数器
+    struct LifecycleGlobal(Arc<LifecycleCounters>);
+
+    impl Drop for LifecycleGlobal {
+        fn drop(&mut self) {
+            self.0.destroyed.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// 创建和释放时计数的分词器，参数为 `fail` 时创建失败
+    struct LifecycleTokenizer(Arc<LifecycleCounters>);
+
+    impl Drop for LifecycleTokenizer {
+        fn drop(&mut self) {
+            self.0.deleted.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    impl Tokenizer for LifecycleTokenizer {
+        type Global = LifecycleGlobal;
+
+        fn name() -> &'static CStr {
+            c"lifecycle"
+        }
+
+        fn new(global: &Self::Global, args: Vec<String>) -> Result<Self, rusqlite::Error> {
+            if args.iter().any(|arg| arg == "fail") {
+                return Err(rusqlite::Error::ModuleError("fail".to_owned()));
+            }
+            global.0.created.fetch_add(1, Ordering::SeqCst);
+            Ok(Self(global.0.clone()))
+        }
+
+        fn tokenize<TKF>(
+            &mut self,
+            _reason: TokenizeReason,
+            text: &[u8],
+            mut push_token: TKF,
+        ) -> Result<(), rusqlite::Error>
+        where
+            TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>,
+        {
+            (push_token)(text, 0..text.len(), false)
+        }
+    }
+
+    /// 写入的位置超出文本范围的分词器
+    struct InvalidRangeTokenizer;
+
+    impl Tokenizer for InvalidRangeTokenizer {
+        type Global = ();
+
+        fn name() -> &'static CStr {
+            c"invalid_range"
+        }
+
+        fn new(_global: &Self::Global, _args: Vec<String>) -> Result<Self, rusqlite::Error> {
+            Ok(Self)
+        }
+
+        fn tokenize<TKF>(
+            &mut self,
+            _reason: TokenizeReason,
+            text: &[u8],
+            mut push_token: TKF,
+        ) -> Result<(), rusqlite::Error>
+        where
+            TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>,
+        {
+            (push_token)(text, 0..text.len() + 10, false)
+        }
+    }
+
+    #[test]
+    fn test_tokenize_with_invalid_range() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<InvalidRangeTokenizer>(&conn, ()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'invalid_range');",
+            [],
+        )
+        .unwrap();
+        let result = conn.execute("INSERT INTO t1(text) VALUES ('sqlite');", []);
+        assert!(matches!(
+            result,
+            Err(rusqlite::Error::SqliteFailure(error, _)) if error.code == rusqlite::ErrorCode::Unknown
+        ));
+        // 写入失败不影响连接的后续使用
+        let vec = query_texts(&conn, "SELECT count(*) || '' FROM t1;");
+        assert_eq!(["0"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_tokenizer_lifecycle_balanced() {
+        let counters = Arc::new(LifecycleCounters::default());
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<LifecycleTokenizer>(&conn, LifecycleGlobal(counters.clone())).unwrap();
+        for index in 0..32 {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE t{index} USING fts5(text, tokenize = 'lifecycle');"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO t{index}(text) VALUES ('sqlite');"),
+                [],
+            )
+            .unwrap();
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t{index} WHERE text MATCH 'sqlite';"),
+            );
+            assert_eq!(["sqlite"], vec.as_slice());
+            if index % 2 == 0 {
+                conn.execute(&format!("DROP TABLE t{index};"), []).unwrap();
+            }
+        }
+        // 创建失败的分词器不计入，也不会被释放
+        let result = conn.execute(
+            "CREATE VIRTUAL TABLE failed USING fts5(text, tokenize = 'lifecycle fail');",
+            [],
+        );
+        assert!(result.is_err());
+        assert!(counters.created.load(Ordering::SeqCst) >= 32);
+        assert_eq!(0, counters.destroyed.load(Ordering::SeqCst));
+        drop(conn);
+        // 关闭连接后，每个创建的分词器都被释放一次，全局数据只释放一次
+        assert_eq!(
+            counters.created.load(Ordering::SeqCst),
+            counters.deleted.load(Ordering::SeqCst)
+        );
+        assert_eq!(1, counters.destroyed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_register_tokenizer_twice() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<CountedTokenizer>(&conn, CountedGlobal(dropped.clone())).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'counted');",
+            [],
+        )
+        .unwrap();
+        // 重复注册替换原来的分词器，原来的全局数据不会被立即释放，已经创建的表仍然可以使用
+        register_tokenizer::<CountedTokenizer>(&conn, CountedGlobal(dropped.clone())).unwrap();
+        assert_eq!(0, dropped.load(Ordering::SeqCst));
+        conn.execute(
+            "CREATE VIRTUAL TABLE t2 USING fts5(text, tokenize = 'counted');",
+            [],
+        )
+        .unwrap();
+        for table in ["t1", "t2"] {
+            conn.execute(&format!("INSERT INTO {table}(text) VALUES ('sqlite');"), [])
+                .unwrap();
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM {table} WHERE text MATCH 'sqlite';"),
+            );
+            assert_eq!(["sqlite"], vec.as_slice());
+        }
+        // 关闭连接时，两次注册的全局数据各释放一次
+        drop(conn);
+        assert_eq!(2, dropped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_register_tokenizer_select_fts5_failed() {
+        let conn = Connection::open_in_memory().unwrap();
+        // 覆盖 fts5 函数，模拟无法获取 fts5_api 的连接
+        conn.create_scalar_function("fts5", 1, FunctionFlags::SQLITE_UTF8, |_| {
+            Err::<i32, _>(rusqlite::Error::UserFunctionError(
+                "fts5 is unavailable".into(),
+            ))
+        })
+        .unwrap();
+        let result = register_all_tokenizers(&conn);
+        let Err(RegisterTokenizerError::SelectFts5Failed(err)) = &result else {
+            panic!("unexpected result {result:?}");
+        };
+        assert!(err.to_string().contains("fts5 is unavailable"), "{err}");
+        let err = result.unwrap_err();
+        assert!(
+            err.to_string().starts_with("SELECT fts5(?1) failed: "),
+            "{err}"
+        );
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn test_register_tokenizer_with_v1_api() {
+        let conn = Connection::open_in_memory().unwrap();
+        // 临时修改连接上 fts5_api 的版本，模拟只有 v1 接口的 SQLite
+        let api = unsafe { get_fts5_api(&conn).unwrap() };
+        let version = unsafe { (*api).iVersion };
+        unsafe { (*api).iVersion = 2 };
+        let result = register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default());
+        unsafe { (*api).iVersion = version };
+        result.unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('国家 sqlite'),('quokka');",
+            [],
+        )
+        .unwrap();
+        for query in ["guo", "sqlite", "国家"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t1 WHERE text MATCH '{query}';"),
+            );
+            assert_eq!(["国家 sqlite"], vec.as_slice(), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_register_tokenizer_with_unsupported_api_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        let api = unsafe { get_fts5_api(&conn).unwrap() };
+        let version = unsafe { (*api).iVersion };
+        // 版本过低时不读取结构体中的接口，直接返回错误
+        unsafe { (*api).iVersion = 1 };
+        let result = register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default());
+        unsafe { (*api).iVersion = version };
+        let Err(err) = result else {
+            panic!("unexpected result {result:?}");
+        };
+        assert!(matches!(
+            err,
+            RegisterTokenizerError::Fts5ApiVersionUnsupported(1)
+        ));
+        assert_eq!(
+            "The version of fts5 api is 1, which is not supported, expected version 2 or later \
+             (version 3 or later for fts5_tokenizer_v2 version 2).",
+            err.to_string()
+        );
+        // 更高的版本兼容当前的接口，仍然使用 v2 接口注册
+        unsafe { (*api).iVersion = FTS5_API_VERSION + 1 };
+        let result = register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default());
+        unsafe { (*api).iVersion = version };
+        result.unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple');",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO t1(text) VALUES ('sqlite');", [])
+            .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'sqlite';");
+        assert_eq!(["sqlite"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_register_tokenizer_failed_drops_global() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let conn = Connection::open_in_memory().unwrap();
+        // 临时修改连接上 fts5_api 的版本并去掉 v1 接口，模拟版本过低导致注册失败
+        let api = unsafe { get_fts5_api(&conn).unwrap() };
+        let (version, create_tokenizer) = unsafe { ((*api).iVersion, (*api).xCreateTokenizer) };
+        unsafe {
+            (*api).iVersion = 2;
+            (*api).xCreateTokenizer = None;
+        }
+        let result = register_tokenizer::<CountedTokenizer>(&conn, CountedGlobal(dropped.clone()));
+        unsafe {
+            (*api).iVersion = version;
+            (*api).xCreateTokenizer = create_tokenizer;
+        }
+        assert!(matches!(
+            result,
+            Err(RegisterTokenizerError::Fts5ApiVersionTooLow)
+        ));
+        // 注册失败时全局数据已经释放，关闭连接时不会再次释放
+        assert_eq!(1, dropped.load(Ordering::SeqCst));
+        drop(conn);
+        assert_eq!(1, dropped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_register_all_tokenizers() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_all_tokenizers(&conn).unwrap();
+        for tokenize in ["simple", "jieba", "keyword", "ngram", "unicode"] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE t_{tokenize} USING fts5(text, tokenize = '{tokenize}');"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO t_{tokenize}(text) VALUES ('sqlite');"),
+                [],
+            )
+            .unwrap();
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t_{tokenize} WHERE text MATCH 'sqlite';"),
+            );
+            assert_eq!(["sqlite"], vec.as_slice(), "{tokenize}");
+        }
+    }
+
+    #[cfg(feature = "token_observer")]
+    #[test]
+    fn test_set_token_observer() {
+        use crate::tokenizer::set_token_observer;
+
+        let simple = Arc::new(AtomicUsize::new(0));
+        let jieba = Arc::new(AtomicUsize::new(0));
+        let (simple_count, jieba_count) = (simple.clone(), jieba.clone());
+        set_token_observer(Some(Box::new(move |name, token, range, _| {
+            // 其他测试同时在分词，只统计这个测试用到的单词
+            if !matches!(token, b"narwhal" | b"platypus") {
+                return;
+            }
+            assert!(range.start < range.end);
+            match name {
+                "simple" => simple_count.fetch_add(1, Ordering::SeqCst),
+                "jieba" => jieba_count.fetch_add(1, Ordering::SeqCst),
+                _ => 0,
+            };
+        })));
+        let text = "Narwhal platypuses".as_bytes();
+        let tokens = tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec![],
+            TokenizeReason::Document,
+            text,
+        )
+        .unwrap();
+        // 回调不影响分词的结果
+        let tokens = tokens
+            .into_iter()
+            .map(|t| String::from_utf8(t.token).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(["narwhal", "platypus"], tokens.as_slice());
+        tokenize_with_positions::<JiebaTokenizer>(
+            &JiebaGlobal::default(),
+            vec![],
+            TokenizeReason::Document,
+            text,
+        )
+        .unwrap();
+        set_token_observer(None);
+        // 取消回调之后不再调用
+        tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec![],
+            TokenizeReason::Document,
+            text,
+        )
+        .unwrap();
+        assert_eq!(2, simple.load(Ordering::SeqCst));
+        assert_eq!(2, jieba.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_fts5_api_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert!(fts5_api_version(&conn).unwrap() >= 3);
+    }
+
+    #[test]
+    fn test_is_tokenizer_registered() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert!(!is_tokenizer_registered(&conn, c"simple"));
+        assert!(!is_tokenizer_registered(&conn, c"simple2"));
+        // fts5 内置的分词器
+        assert!(is_tokenizer_registered(&conn, c"unicode61"));
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        assert!(is_tokenizer_registered(&conn, c"simple"));
+        assert!(!is_tokenizer_registered(&conn, c"simple2"));
+        register_tokenizer_named::<SimpleTokenizer>(&conn, c"simple2", SimpleGlobal::default())
+            .unwrap();
+        assert!(is_tokenizer_registered(&conn, c"simple2"));
+        // 注册只影响当前的连接
+        let other = Connection::open_in_memory().unwrap();
+        assert!(!is_tokenizer_registered(&other, c"simple"));
+    }
+
+    #[test]
+    fn test_register_tokenizer_named() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        register_tokenizer_named::<SimpleTokenizer>(&conn, c"simple2", SimpleGlobal::default())
+            .unwrap();
+        for (table, tokenize) in [("t1", "simple"), ("t2", "simple2 disable_pinyin")] {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            conn.execute(&format!("INSERT INTO {table}(text) VALUES ('国家');"), [])
+                .unwrap();
+        }
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'guo';");
+        assert_eq!(["国家"], vec.as_slice());
+        // 使用另一个名称注册的分词器是独立的，参数只影响这个表
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'guo';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '国';");
+        assert_eq!(["国家"], vec.as_slice());
+    }
+
+    /// 指定了 locale 时，将 locale 作为 token 前缀写入的分词器，用于检查 locale 是否传递给分词器
+    struct LocaleTokenizer;
+
+    impl Tokenizer for LocaleTokenizer {
+        type Global = ();
+
+        fn name() -> &'static CStr {
+            c"locale"
+        }
+
+        fn new(_global: &Self::Global, _args: Vec<String>) -> Result<Self, rusqlite::Error> {
+            Ok(Self)
+        }
+
+        fn tokenize<TKF>(
+            &mut self,
+            reason: TokenizeReason,
+            text: &[u8],
+            push_token: TKF,
+        ) -> Result<(), rusqlite::Error>
+        where
+            TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>,
+        {
+            self.tokenize_with_locale(reason, None, text, push_token)
+        }
+
+        fn tokenize_with_locale<TKF>(
+            &mut self,
+            _reason: TokenizeReason,
+            locale: Option<&str>,
+            text: &[u8],
+            mut push_token: TKF,
+        ) -> Result<(), rusqlite::Error>
+        where
+            TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>,
+        {
+            let text = str::from_utf8(text).unwrap();
+            let token = match locale {
+                Some(locale) => format!("{locale}_{text}"),
+                None => text.to_owned(),
+            };
+            (push_token)(token.as_bytes(), 0..text.len(), false)
+        }
+    }
+
+    #[test]
+    fn test_tokenize_with_locale() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<LocaleTokenizer>(&conn, ()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'locale', locale = 1);",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES (fts5_locale('fr', 'bonjour')),('hello');",
+            [],
+        )
+        .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE t1 MATCH 'fr_bonjour';");
+        assert_eq!(["bonjour"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE t1 MATCH 'bonjour';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE t1 MATCH 'hello';");
+        assert_eq!(["hello"], vec.as_slice());
+        // 查询时同样可以指定 locale
+        let vec = query_texts(
+            &conn,
+            "SELECT * FROM t1 WHERE t1 MATCH fts5_locale('fr', 'bonjour');",
+        );
+        assert_eq!(["bonjour"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_register_jieba_tokenizer() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        // 创建一个测试表, simple 不开启 pinyin 分词
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'jieba');",
+            [],
+        )
+        .unwrap();
+        // 插入数据
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('中华人民共和国国歌'),('静夜思'),('国家'),('举头望明月'),('like'),('liking'),('liked');",
+            [],
+        )
+            .unwrap();
+        let mut stmt = conn
+            .prepare("SELECT * FROM t1 WHERE text MATCH '国家';")
+            .unwrap();
+        let result = stmt
+            .query_map([], |row| Ok(row.get::<_, String>(0).unwrap()))
+            .unwrap();
+        for row in result {
+            let row = row.unwrap();
+            assert_eq!("国家".to_owned(), row);
         }
     }
 
     #[test]
     fn test_register_jieba_tokenizer_with_space_str() {
         let conn = Connection::open_in_memory().unwrap();
-        register_tokenizer::<JiebaTokenizer>(&conn, ()).unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
         // 创建一个测试表, simple 不开启 pinyin 分词
         conn.execute(
             "CREATE VIRTUAL TABLE t1 USING fts5(id, title, introduction, summary, readme, tokenize = 'jieba');",
@@ -520,7 +2212,7 @@ mod tests {
     #[test]
     fn test_register_jieba_tokenizer_with_() {
         let conn = Connection::open_in_memory().unwrap();
-        register_tokenizer::<JiebaTokenizer>(&conn, ()).unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
         // 创建一个测试表
         conn.execute(
             "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'jieba');",
@@ -544,4 +2236,378 @@ mod tests {
         }
         assert_eq!(["社会主义国家", "国家"], vec.as_slice());
     }
+
+    #[test]
+    fn test_rebuild_produces_identical_tokens() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        let queries = ["'国'", "'guo'", "'like'", "'tokenizr'", "'国家'", "'英语'"];
+        for tokenize in [
+            "'simple'",
+            "\"simple disable_pinyin 'shingle=3'\"",
+            "'jieba'",
+            "\"jieba 'shingle=3'\"",
+        ] {
+            conn.execute("DROP TABLE IF EXISTS t1;", []).unwrap();
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = {tokenize});"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                r#"INSERT INTO t1(text) VALUES ('中华人民共和国国歌'),('社会主义国家'),('铁锅'),('liking'),('I''m making a sqlite tokenizer'),('I''m learning English 英语');"#,
+                [],
+            )
+            .unwrap();
+            let before = queries
+                .iter()
+                .map(|q| query_texts(&conn, &format!("SELECT * FROM t1 WHERE t1 MATCH {q};")))
+                .collect::<Vec<_>>();
+            // 使用 rebuild 命令，以 Document 原因重新分词全部内容
+            conn.execute("INSERT INTO t1(t1) VALUES('rebuild');", [])
+                .unwrap();
+            let after = queries
+                .iter()
+                .map(|q| query_texts(&conn, &format!("SELECT * FROM t1 WHERE t1 MATCH {q};")))
+                .collect::<Vec<_>>();
+            assert_eq!(before, after, "tokenize = {tokenize}");
+        }
+    }
+
+    #[test]
+    fn test_highlight_with_combining_marks() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        // simple 将附加符号合并成 é，jieba 单独切分出附加符号，token 不包含附加符号
+        for (tokenize, cafe) in [("simple", "café"), ("jieba", "cafe")] {
+            conn.execute("DROP TABLE IF EXISTS t1;", []).unwrap();
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            // 分解形式的附加符号，高亮的范围包含完整的字素簇
+            conn.execute(
+                "INSERT INTO t1(text) VALUES ('cafe\u{301} latte'),('国\u{301}家');",
+                [],
+            )
+            .unwrap();
+            let sql = "SELECT highlight(t1, 0, '[', ']') FROM t1 WHERE t1 MATCH ";
+            let vec = query_texts(&conn, &format!("{sql}'{cafe}';"));
+            assert_eq!(
+                ["[cafe\u{301}] latte"],
+                vec.as_slice(),
+                "tokenize = {tokenize}"
+            );
+            let vec = query_texts(&conn, &format!("{sql}'国';"));
+            assert_eq!(["[国\u{301}]家"], vec.as_slice(), "tokenize = {tokenize}");
+        }
+    }
+
+    #[test]
+    fn test_highlight_with_bidi_text() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        for tokenize in ["simple", "jieba"] {
+            conn.execute("DROP TABLE IF EXISTS t1;", []).unwrap();
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            // 阿拉伯语、希伯来语与拉丁字母、数字混排，字节偏移量始终按逻辑顺序计算
+            conn.execute(
+                "INSERT INTO t1(text) VALUES ('مرحبا sqlite 2024 שלום עולם'),('كتاب جديد book');",
+                [],
+            )
+            .unwrap();
+            let sql = "SELECT highlight(t1, 0, '[', ']') FROM t1 WHERE t1 MATCH ";
+            let vec = query_texts(&conn, &format!("{sql}'sqlite';"));
+            assert_eq!(["مرحبا [sqlite] 2024 שלום עולם"], vec.as_slice());
+            let vec = query_texts(&conn, &format!("{sql}'שלום';"));
+            assert_eq!(["مرحبا sqlite 2024 [שלום] עולם"], vec.as_slice());
+            let vec = query_texts(&conn, &format!("{sql}'2024';"));
+            assert_eq!(["مرحبا sqlite [2024] שלום עולם"], vec.as_slice());
+            let vec = query_texts(&conn, &format!("{sql}'جديد';"));
+            assert_eq!(
+                ["كتاب [جديد] book"],
+                vec.as_slice(),
+                "tokenize = {tokenize}"
+            );
+            let vec = query_texts(&conn, &format!("{sql}'كتاب book';"));
+            assert_eq!(
+                ["[كتاب] جديد [book]"],
+                vec.as_slice(),
+                "tokenize = {tokenize}"
+            );
+            // 带有方向控制字符（RLM、LRM）的文本，控制字符不影响 token
+            conn.execute(
+                "INSERT INTO t1(text) VALUES ('\u{200f}עולם\u{200f} \u{200e}rust\u{200e}');",
+                [],
+            )
+            .unwrap();
+            let vec = query_texts(&conn, &format!("{sql}'עולם rust';"));
+            assert_eq!(1, vec.len(), "tokenize = {tokenize}");
+        }
+    }
+
+    #[test]
+    fn test_integrity_check() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        for tokenize in [
+            "'simple'",
+            "'simple disable_stopword'",
+            "\"simple 'shingle=3' 'unit_map=feet:ft'\"",
+            "'jieba'",
+            "'jieba raw_mode'",
+        ] {
+            conn.execute("DROP TABLE IF EXISTS t1;", []).unwrap();
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = {tokenize});"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                r#"INSERT INTO t1(text) VALUES ('中华人民共和国国歌'),('jump 32.3feet'),('I''m making a sqlite tokenizer'),('社会主义国家');"#,
+                [],
+            )
+            .unwrap();
+            // integrity-check 会重新分词并与索引比较，分词结果不一致时返回 SQLITE_CORRUPT_VTAB
+            conn.execute("INSERT INTO t1(t1, rank) VALUES('integrity-check', 1);", [])
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_integrity_check_after_set_global_stopwords() {
+        let _lock = GLOBAL_STOPWORDS_LOCK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        for (table, tokenize) in [("t1", "simple"), ("t2", "jieba")] {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('wombat sqlite'),('麒麟的国家');"),
+                [],
+            )
+            .unwrap();
+        }
+        // 其他测试同时在使用全局停词表，替换的停词表只加上其他测试不会用到的 wombat 和 麒麟
+        let stopwords = crate::STOPWORD
+            .iter()
+            .map(|word| (*word).to_owned())
+            .chain(["wombat".to_owned(), "麒麟".to_owned()]);
+        set_global_stopwords(stopwords);
+        let result = (|| {
+            for table in ["t1", "t2"] {
+                // 打开的表继续使用创建时的停词表，写入、删除和重新分词的结果都和索引一致
+                conn.execute(
+                    &format!("INSERT INTO {table}(text) VALUES ('wombat tokenizer');"),
+                    [],
+                )?;
+                conn.execute(&format!("DELETE FROM {table} WHERE rowid = 1;"), [])?;
+                conn.execute(
+                    &format!("INSERT INTO {table}({table}, rank) VALUES('integrity-check', 1);"),
+                    [],
+                )?;
+            }
+            Ok::<_, rusqlite::Error>(())
+        })();
+        reset_global_stopwords();
+        result.unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'wombat';");
+        assert_eq!(["wombat tokenizer"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '麒麟';");
+        assert_eq!(["麒麟的国家"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_x_tokenize_with_null_text() {
+        unsafe extern "C" fn push_token(
+            ctx: *mut c_void,
+            _flags: c_int,
+            _token: *const c_char,
+            _token_len: c_int,
+            _start: c_int,
+            _end: c_int,
+        ) -> c_int {
+            unsafe { *ctx.cast::<usize>() += 1 };
+            SQLITE_OK
+        }
+        let mut tokenizer = SimpleTokenizer::new(&SimpleGlobal::default(), vec![]).unwrap();
+        let mut count = 0usize;
+        // 空指针和长度为 0、负数的文本都按照空文本分词，不写入任何 token
+        for (data, data_len) in [
+            (std::ptr::null(), 0),
+            (std::ptr::null(), 5),
+            (c"sqlite".as_ptr(), -1),
+        ] {
+            let rc = unsafe {
+                x_tokenize::<SimpleTokenizer>(
+                    (&raw mut tokenizer).cast(),
+                    (&raw mut count).cast(),
+                    FTS5_TOKENIZE_DOCUMENT,
+                    data,
+                    data_len,
+                    std::ptr::null(),
+                    0,
+                    Some(push_token),
+                )
+            };
+            assert_eq!(SQLITE_OK, rc);
+        }
+        assert_eq!(0, count);
+        let rc = unsafe {
+            x_tokenize::<SimpleTokenizer>(
+                (&raw mut tokenizer).cast(),
+                (&raw mut count).cast(),
+                FTS5_TOKENIZE_DOCUMENT,
+                c"sqlite".as_ptr(),
+                6,
+                std::ptr::null(),
+                0,
+                Some(push_token),
+            )
+        };
+        assert_eq!((SQLITE_OK, 1), (rc, count));
+    }
+
+    #[test]
+    fn test_contentless_table() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        for (tokenize, query, expected) in [
+            ("simple", "'国'", vec![10, 20]),
+            ("simple", "'learn'", vec![30]),
+            ("jieba", "'国家'", vec![20]),
+        ] {
+            conn.execute("DROP TABLE IF EXISTS t1;", []).unwrap();
+            // 无内容表不保存原始文本，写入时仍然会调用分词器，NULL 的字段作为空文本分词
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE t1 USING fts5(title, body, content = '', contentless_delete = 1, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO t1(rowid, title, body) VALUES (10, '国歌', '中华人民共和国国歌'),(20, NULL, '社会主义国家'),(30, 'sqlite', 'I''m learning English'),(40, '', NULL);",
+                [],
+            )
+            .unwrap();
+            let query_ids = || {
+                let mut stmt = conn
+                    .prepare(&format!(
+                        "SELECT rowid FROM t1 WHERE body MATCH {query} ORDER BY rowid;"
+                    ))
+                    .unwrap();
+                stmt.query_map([], |row| row.get::<_, i64>(0))
+                    .unwrap()
+                    .map(Result::unwrap)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(expected, query_ids(), "tokenize = {tokenize}");
+            // 没有保存原始文本，读取字段和 highlight 都得到 NULL
+            let body = conn
+                .query_row(
+                    &format!(
+                        "SELECT body, highlight(t1, 1, '[', ']') FROM t1 WHERE body MATCH {query};"
+                    ),
+                    [],
+                    |row| {
+                        Ok((
+                            row.get::<_, Option<String>>(0)?,
+                            row.get::<_, Option<String>>(1)?,
+                        ))
+                    },
+                )
+                .unwrap();
+            assert_eq!((None, None), body, "tokenize = {tokenize}");
+            // contentless_delete 的删除和更新不需要提供原始文本
+            conn.execute("DELETE FROM t1 WHERE rowid = ?1;", [expected[0]])
+                .unwrap();
+            assert_eq!(expected[1..], query_ids(), "tokenize = {tokenize}");
+            conn.execute(
+                "UPDATE t1 SET title = NULL, body = 'quokka' WHERE rowid = 40;",
+                [],
+            )
+            .unwrap();
+            let count = conn
+                .query_row(
+                    "SELECT count(*) FROM t1 WHERE t1 MATCH 'quokka';",
+                    [],
+                    |row| row.get::<_, i64>(0),
+                )
+                .unwrap();
+            assert_eq!(1, count, "tokenize = {tokenize}");
+            conn.execute("INSERT INTO t1(t1, rank) VALUES('integrity-check', 1);", [])
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_external_content_table() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        register_tokenizer::<JiebaTokenizer>(&conn, JiebaGlobal::default()).unwrap();
+        conn.execute("CREATE TABLE docs(id INTEGER PRIMARY KEY, body TEXT);", [])
+            .unwrap();
+        conn.execute(
+            r#"INSERT INTO docs(id, body) VALUES (10, '中华人民共和国国歌'),(20, '社会主义国家'),(30, 'I''m making a sqlite tokenizer');"#,
+            [],
+        )
+        .unwrap();
+        for (tokenize, query, expected) in [
+            ("'simple'", "'国'", vec![10, 20]),
+            ("\"simple 'shingle=3'\"", "'tokenizr'", vec![30]),
+            ("'jieba'", "'国家'", vec![20]),
+        ] {
+            conn.execute("DROP TABLE IF EXISTS t1;", []).unwrap();
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE t1 USING fts5(body, content = 'docs', content_rowid = 'id', tokenize = {tokenize});"),
+                [],
+            )
+            .unwrap();
+            // 外部内容表通过 rebuild 建立索引，分词结果与直接写入时一致
+            conn.execute("INSERT INTO t1(t1) VALUES('rebuild');", [])
+                .unwrap();
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT docs.id FROM t1 JOIN docs ON docs.id = t1.rowid WHERE t1 MATCH {query} ORDER BY docs.id;"
+                ))
+                .unwrap();
+            let ids = stmt
+                .query_map([], |row| row.get::<_, i64>(0))
+                .unwrap()
+                .map(Result::unwrap)
+                .collect::<Vec<_>>();
+            assert_eq!(expected, ids, "tokenize = {tokenize}");
+            // 删除时需要提供原始内容，重新分词后的 token 必须与写入时一致，否则索引会损坏
+            conn.execute(
+                "INSERT INTO t1(t1, rowid, body) SELECT 'delete', id, body FROM docs WHERE id = 10;",
+                [],
+            )
+            .unwrap();
+            // 索引中缺少外部内容表中的文档，integrity-check 失败
+            conn.execute("INSERT INTO t1(t1, rank) VALUES('integrity-check', 1);", [])
+                .unwrap_err();
+            conn.execute(
+                "INSERT INTO t1(rowid, body) SELECT id, body FROM docs WHERE id = 10;",
+                [],
+            )
+            .unwrap();
+            conn.execute("INSERT INTO t1(t1, rank) VALUES('integrity-check', 1);", [])
+                .unwrap();
+        }
+    }
 }
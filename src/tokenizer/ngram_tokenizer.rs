@@ -0,0 +1,232 @@
+use crate::tokenizer::{
+    TokenizeReason, Tokenizer,
+    utils::{
+        ConfigSummary, LossyText, Normalizer, Script, parse_positive_arg, reject_unknown_args,
+        script_runs,
+    },
+};
+use rusqlite::Error;
+use std::ffi::CStr;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// 默认的片段长度
+const DEFAULT_NGRAM: usize = 2;
+
+/// 对连续的汉字按照 n-gram 分词的分词器，可以查询汉字文本中的任意片段
+///
+/// 连续的汉字按照 `n` 个字符的滑动窗口写入，不足 `n` 个字符时整体写入，其余文本按照单词切分，转换成小写写入。
+/// 查询长度不小于 `n` 的片段时，使用双引号包裹查询文本，例如 `MATCH '"民共和"'`，
+/// 查询文本同样被切分成连续的 n-gram，只匹配包含这个片段的文档
+pub struct NgramTokenizer {
+    /// 汉字片段的长度，默认为 2
+    n: usize,
+    /// 单词归一化的配置
+    normalizer: Normalizer,
+}
+
+impl Default for NgramTokenizer {
+    fn default() -> Self {
+        Self {
+            n: DEFAULT_NGRAM,
+            normalizer: Normalizer::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for NgramTokenizer {
+    /// 输出 [`Tokenizer::config_summary`] 的配置摘要
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.config_summary())
+    }
+}
+
+impl NgramTokenizer {
+    /// 设置汉字片段的长度
+    pub fn set_n(&mut self, n: usize) {
+        self.n = n;
+    }
+
+    /// 对文本分词，并将每个 token 交给 `push_token` 处理
+    fn tokenize_words<TKF>(&self, text: &str, mut push_token: TKF) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        let mut word_buf = String::new();
+        // 连续的汉字片段，逐字切分的汉字在这里合并
+        let mut han_run: Option<Range<usize>> = None;
+        for (index, word) in text.unicode_word_indices() {
+            for (script, run) in script_runs(word) {
+                let range = index + run.start..index + run.end;
+                if script == Script::Han {
+                    match &mut han_run {
+                        Some(han) if han.end == range.start => han.end = range.end,
+                        _ => {
+                            if let Some(han) = han_run.replace(range) {
+                                self.push_ngrams(text, han, &mut word_buf, &mut push_token)?;
+                            }
+                        }
+                    }
+                    continue;
+                }
+                if let Some(han) = han_run.take() {
+                    self.push_ngrams(text, han, &mut word_buf, &mut push_token)?;
+                }
+                self.normalizer
+                    .make_lowercase(&text[range.clone()], &mut word_buf);
+                if !word_buf.is_empty() {
+                    (push_token)(word_buf.as_bytes(), range, false)?;
+                }
+            }
+        }
+        if let Some(han) = han_run {
+            self.push_ngrams(text, han, &mut word_buf, &mut push_token)?;
+        }
+        Ok(())
+    }
+
+    /// 将连续的汉字按照 n 个字符的滑动窗口写入
+    fn push_ngrams<TKF>(
+        &self,
+        text: &str,
+        run: Range<usize>,
+        word_buf: &mut String,
+        push_token: &mut TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        let chars = text[run.clone()]
+            .char_indices()
+            .map(|(offset, ch)| run.start + offset..run.start + offset + ch.len_utf8())
+            .collect::<Vec<_>>();
+        // 不足 n 个字符时整体写入
+        let windows = chars.windows(self.n.min(chars.len()));
+        for window in windows {
+            let range = window[0].start..window[window.len() - 1].end;
+            self.normalizer
+                .make_lowercase(&text[range.clone()], word_buf);
+            if !word_buf.is_empty() {
+                (push_token)(word_buf.as_bytes(), range, false)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Tokenizer for NgramTokenizer {
+    type Global = ();
+
+    fn name() -> &'static CStr {
+        c"ngram"
+    }
+
+    fn config_summary(&self) -> String {
+        let mut summary = ConfigSummary::new(Self::name());
+        summary.field("n", self.n);
+        self.normalizer.summarize(&mut summary);
+        summary.finish()
+    }
+
+    fn new(_global: &Self::Global, args: Vec<String>) -> Result<Self, Error> {
+        let mut tokenizer = Self::default();
+        let mut unknown = Vec::new();
+        for arg in args {
+            match arg.split_once('=') {
+                Some(("n", n)) => {
+                    tokenizer.set_n(parse_positive_arg("n", n)?);
+                }
+                _ => unknown.push(arg),
+            }
+        }
+        reject_unknown_args("ngram", &unknown)?;
+        Ok(tokenizer)
+    }
+
+    fn tokenize<TKF>(
+        &mut self,
+        _reason: TokenizeReason,
+        text: &[u8],
+        mut push_token: TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        // 分词得到的是转换后字符串中的位置，写入之前转换成原始字节中的位置
+        let text = LossyText::new(text);
+        self.tokenize_words(text.as_str(), |token, range, colocated| {
+            (push_token)(token, text.original_range(range), colocated)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::ngram_tokenizer::NgramTokenizer;
+    use crate::tokenizer::standalone::tokenize_to_vec;
+    use crate::tokenizer::tests::query_texts;
+    use crate::tokenizer::{TokenizeReason, register_tokenizer};
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_ngram_tokenizer() {
+        let tokenize = |args: Vec<String>, text: &str| {
+            tokenize_to_vec::<NgramTokenizer>(&(), args, TokenizeReason::Document, text.as_bytes())
+                .unwrap()
+                .into_iter()
+                .map(|(token, range, _)| (String::from_utf8(token).unwrap(), range))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            [
+                ("中华", 0..6),
+                ("华人", 3..9),
+                ("人民", 6..12),
+                ("sqlite", 13..19),
+                ("国", 20..23),
+            ]
+            .map(|(token, range)| (token.to_owned(), range))
+            .as_slice(),
+            tokenize(vec![], "中华人民 SQLite 国").as_slice()
+        );
+        assert_eq!(
+            [("中华人", 0..9), ("华人民", 3..12)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(vec!["n=3".to_owned()], "中华人民").as_slice()
+        );
+        // 标点将汉字分隔成不同的片段
+        assert_eq!(
+            [("中华", 0..6), ("人民", 9..15)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(vec![], "中华，人民").as_slice()
+        );
+    }
+
+    #[test]
+    fn test_register_ngram_tokenizer() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<NgramTokenizer>(&conn, ()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'ngram');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('中华人民共和国国歌'),('人民日报'),('Made in China');",
+            [],
+        )
+        .unwrap();
+        // 查询词语中间的片段
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"民共和\"';");
+        assert_eq!(["中华人民共和国国歌"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"人民\"';");
+        assert_eq!(["中华人民共和国国歌", "人民日报"], vec.as_slice());
+        // 片段必须连续
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"人共和\"';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'china';");
+        assert_eq!(["Made in China"], vec.as_slice());
+    }
+}
@@ -1,31 +1,286 @@
-use crate::STOPWORD;
-use crate::pinyin::{get_pinyin, split_pinyin};
+use crate::pinyin::{
+    fuzzy_pinyin, get_pinyin, join_pinyin, join_pinyin_with_separator, pinyin_initials,
+    pinyin_readings, pinyin_with_tone, primary_pinyin, split_pinyin,
+};
 use crate::tokenizer::{
-    TokenizeReason, Tokenizer,
-    utils::{EN_STEMMER, make_lowercase, need_pinyin},
+    StemmerLanguage, TokenizeReason, Tokenizer,
+    utils::{
+        ConfigSummary, DEFAULT_MAX_TOKEN_BYTES, InvalidUtf8Policy, Ligatures, LossyText,
+        MAX_PINYIN_COMBINATIONS, NormalizationForm, NumberMode, PinyinPrimary, Script,
+        StopwordCounter, SymbolPolicy, TokenLimit, TokenPipeline, TokenTransform,
+        VariationSelectors, alnum_parts, cjk_number_spans, dedup_tokens, en_number_word,
+        fold_diacritics, identifier_parts, is_latin_word, is_punctuation_or_space_str,
+        load_synonyms, need_pinyin, observe_tokens, parse_pinyin_sep_arg, parse_positive_arg,
+        parse_stemmer_arg, prefix_end, reject_unknown_args, script_runs, shingles,
+        skip_long_tokens, split_number_unit, symbol_ranges, whitespace_word_indices,
+    },
 };
 use rusqlite::Error;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::ffi::CStr;
 use std::ops::Range;
+use unicode_normalization::UnicodeNormalization;
 use unicode_segmentation::UnicodeSegmentation;
 
+/// 注册 simple 分词器时提供的全局数据，由同一次注册的全部 `simple` 表共享
+#[derive(Default)]
+pub struct SimpleGlobal {
+    /// 自定义的 token 转换
+    transform: Option<TokenTransform>,
+}
+
+impl SimpleGlobal {
+    /// 设置自定义的 token 转换，在归一化之后、停词和词干提取之前调用，返回 `None` 时不写入这个单词
+    ///
+    /// 用于音译这类内置参数无法实现的转换，不需要为此实现完整的 [`Tokenizer`]，汉字的拼音不经过这个转换
+    pub fn set_transform(&mut self, transform: TokenTransform) {
+        self.transform = Some(transform);
+    }
+}
+
+/// `raw_mode` 的切分方式，开启后不做归一化、停词、词干提取和拼音处理
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RawMode {
+    /// 按照单词边界切分，原样写入分词结果，去掉空白和标点，对应 `raw_mode`
+    Words,
+    /// 只按照空白字符切分，原样写入每个单词，单词中的标点和符号也会写入，对应 `raw_mode=whitespace`
+    Whitespace,
+    /// 和 `Whitespace` 相同，写入之前转换成小写，对应 `raw_mode=whitespace,lowercase`
+    WhitespaceLowercase,
+}
+
+impl RawMode {
+    /// 从 `raw_mode=` 参数的值中解析切分方式
+    fn from_arg(value: &str) -> Result<Self, Error> {
+        match value {
+            "words" => Ok(Self::Words),
+            "whitespace" => Ok(Self::Whitespace),
+            "whitespace,lowercase" => Ok(Self::WhitespaceLowercase),
+            _ => Err(Error::ModuleError(format!(
+                "raw_mode must be one of words, whitespace, whitespace,lowercase, got {value}"
+            ))),
+        }
+    }
+
+    /// 参数的写法，逗号替换成 `|`，用于配置摘要
+    fn summary(self) -> &'static str {
+        match self {
+            Self::Words => "words",
+            Self::Whitespace => "whitespace",
+            Self::WhitespaceLowercase => "whitespace|lowercase",
+        }
+    }
+}
+
 /// 适用于拼音和中文的分词器
+///
+/// 先按照 Unicode 单词边界切分，再在书写系统变化的位置切分，因此 `stars全部都是eye` 这样没有空白的混合文本
+/// 会切分成 `stars`、`全`、`部`、`都`、`是`、`eye`，每个片段的范围都是它在原始文本中的位置，
+/// 数字和标点不属于任何书写系统，归入前一个片段，出现在单词开头时归入后一个片段，例如 `3d` 作为一个拉丁字母片段。
+/// 连续的片假名（例如 `コンピューター`）作为一个片段写入，不提取词干，平假名逐字写入
 pub struct SimpleTokenizer {
     /// 是否支持拼音，默认支持拼音
     enable_pinyin: bool,
-    /// 是否启用停词表, 默认启用
-    enable_stopword: bool,
+    /// 单词的归一化、停词和词干提取
+    pipeline: TokenPipeline,
+    /// 本次分词中被停词表过滤的单词数量
+    stopword_hits: StopwordCounter,
+    /// 本次分词是前缀查询时，最后一个单词的结束位置，这个单词不检查停词
+    prefix_end: Option<usize>,
+    /// 写入文档时，是否在词干之外额外写入原本的单词，默认关闭
+    keep_original: bool,
+    /// 写入文档时，是否写入去掉附加符号的单词，并将原本的单词作为 colocated token 写入，默认关闭
+    fold_ascii_keep_original: bool,
+    /// 拉丁字母单词的 n-gram 片段长度，默认不生成片段
+    shingle: Option<usize>,
+    /// 同义词表，写入文档时为匹配的单词额外写入同一组中的其余同义词，默认为空
+    synonyms: HashMap<String, Vec<String>>,
+    /// 是否将带单位的数字（例如 `32.3feet`）额外拆分成数字和单位，默认不拆分
+    number_units: bool,
+    /// 是否将代码中的标识符按照下划线和驼峰切分成子词，默认不切分
+    identifier_split: bool,
+    /// 是否在字母和数字相邻的位置切分拉丁字母单词（例如 `ABC123` 切分成 `abc`、`123`），默认不切分
+    alnum_split: bool,
+    /// 单位和单位缩写的映射，用于生成归一化的数字和单位组合（例如 `32.3ft`）
+    unit_map: HashMap<String, String>,
+    /// 只分词、不做任何转换时的切分方式，默认为 `None`，正常处理单词
+    raw_mode: Option<RawMode>,
+    /// 写入文档时，是否对整个文档中的 token 去重，默认关闭
+    dedup_document: bool,
+    /// token 的最大字节数，超过的 token 不写入，默认为 1024
+    max_token_bytes: usize,
+    /// 写入文档时最多写入的 token 数量，超过的部分不写入，默认不限制
+    max_tokens: Option<usize>,
+    /// 数字单词的处理方式，默认原样写入
+    numbers: NumberMode,
+    /// emoji 等符号的处理方式，默认不写入
+    symbols: SymbolPolicy,
+    /// 文本中无效的 UTF-8 字节的处理方式，默认替换成 U+FFFD
+    invalid_utf8: InvalidUtf8Policy,
+    /// 是否为连续的汉字额外生成拼接的全拼（例如 `国家` 生成 `guojia`），默认关闭
+    full_pinyin: bool,
+    /// 拼接的全拼中音节之间的分隔符，默认为空串，不使用分隔符
+    pinyin_sep: String,
+    /// 是否额外生成拼音首字母（例如 `中华人民共和国` 生成 `zhhrmghg`），默认关闭
+    pinyin_initials: bool,
+    /// 是否为数字和英文数字单词额外生成另一种写法（例如 `3` 和 `three`），默认关闭
+    number_words: bool,
+    /// 是否为汉字数字额外生成阿拉伯数字（例如 `三十二` 生成 `32`），默认关闭
+    cjk_numbers: bool,
+    /// 是否额外写入带声调的拼音（例如 `国` 写入 `guó`），默认关闭
+    pinyin_tone: bool,
+    /// 多音字逐字写入拼音时第一个读音的选择方式，默认按照字母顺序
+    pinyin_primary: PinyinPrimary,
+    /// 是否将拼音音节按照模糊音映射成统一的形式（例如 `shi` 和 `si`），默认关闭
+    fuzzy_pinyin: bool,
+    /// 是否写入汉字本身，拼音作为 colocated token 写入，查询汉字时只匹配相同的汉字，默认关闭
+    keep_han: bool,
+}
+
+/// `simple` 分词器常用选项的类型化配置，用于在代码中生成建表语句里的 `tokenize = '...'` 参数
+///
+/// 选项来自应用的配置时，使用这个结构体代替手动拼接参数字符串，避免拼写错误或者混入其他 SQL 文本
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SimpleTokenizerConfig {
+    /// 是否支持拼音，默认支持拼音
+    pub pinyin: bool,
+    /// 是否启用停词表，默认启用
+    pub stopword: bool,
+    /// 是否对英文单词做词干提取，默认启用
+    pub stemming: bool,
+}
+
+impl Default for SimpleTokenizerConfig {
+    fn default() -> Self {
+        Self {
+            pinyin: true,
+            stopword: true,
+            stemming: true,
+        }
+    }
+}
+
+impl SimpleTokenizerConfig {
+    /// 生成 `tokenize = '...'` 中的参数字符串，例如 `simple disable_pinyin`
+    pub fn to_tokenize_arg(&self) -> String {
+        let mut arg = SimpleTokenizer::name().to_string_lossy().into_owned();
+        for (enabled, flag) in [
+            (self.pinyin, "disable_pinyin"),
+            (self.stopword, "disable_stopword"),
+            (self.stemming, "disable_stemming"),
+        ] {
+            if !enabled {
+                arg.push(' ');
+                arg.push_str(flag);
+            }
+        }
+        arg
+    }
+
+    /// 解析 [`SimpleTokenizerConfig::to_tokenize_arg`] 生成的参数字符串
+    ///
+    /// 分词器名称不是 `simple`，或者包含这个配置不支持的参数时返回错误
+    pub fn from_tokenize_arg(arg: &str) -> Result<Self, Error> {
+        let mut words = arg.split_whitespace();
+        let name = SimpleTokenizer::name().to_string_lossy();
+        if words.next() != Some(name.as_ref()) {
+            return Err(Error::ModuleError(format!(
+                "tokenize arg must start with {name}, got {arg}"
+            )));
+        }
+        let mut config = Self::default();
+        let mut unknown = Vec::new();
+        for word in words {
+            match word {
+                "disable_pinyin" => config.pinyin = false,
+                "disable_stopword" => config.stopword = false,
+                "disable_stemming" => config.stemming = false,
+                _ => unknown.push(word.to_owned()),
+            }
+        }
+        reject_unknown_args("simple", &unknown)?;
+        Ok(config)
+    }
+}
+
+/// 连续的汉字片段，用于生成拼接的拼音
+#[derive(Default)]
+struct HanRun {
+    /// 片段在原始文本中的字节范围
+    range: Range<usize>,
+    /// 每个字的全部读音，拼音借用自静态的拼音表，不需要拼接的拼音和首字母时为空
+    readings: Vec<Vec<&'static str>>,
+    /// 片段中是否已经写入了逐字的拼音，片段全部是停词时为 `false`
+    has_token: bool,
+}
+
+impl HanRun {
+    /// 这个范围是否紧接在当前片段之后，空片段可以接上任意范围
+    fn is_adjacent(&self, range: &Range<usize>) -> bool {
+        self.readings.is_empty() || self.range.end == range.start
+    }
+
+    /// 追加一个相邻的汉字，`has_token` 表示这个字是否写入了逐字的拼音
+    fn push(&mut self, range: Range<usize>, pinyin_vec: Vec<&'static str>, has_token: bool) {
+        if self.readings.is_empty() {
+            self.range.start = range.start;
+        }
+        self.range.end = range.end;
+        self.readings.push(pinyin_vec);
+        self.has_token |= has_token;
+    }
+
+    /// 清空片段，保留已经分配的内存给下一个片段使用
+    fn clear(&mut self) {
+        self.range = 0..0;
+        self.readings.clear();
+        self.has_token = false;
+    }
 }
 
 impl Default for SimpleTokenizer {
     fn default() -> Self {
         Self {
             enable_pinyin: true,
-            enable_stopword: true,
+            pipeline: TokenPipeline::default(),
+            stopword_hits: StopwordCounter::default(),
+            prefix_end: None,
+            keep_original: false,
+            fold_ascii_keep_original: false,
+            shingle: None,
+            synonyms: HashMap::new(),
+            number_units: false,
+            identifier_split: false,
+            alnum_split: false,
+            unit_map: HashMap::new(),
+            raw_mode: None,
+            dedup_document: false,
+            max_token_bytes: DEFAULT_MAX_TOKEN_BYTES,
+            max_tokens: None,
+            numbers: NumberMode::Keep,
+            symbols: SymbolPolicy::Drop,
+            invalid_utf8: InvalidUtf8Policy::Lossy,
+            full_pinyin: false,
+            pinyin_sep: String::new(),
+            pinyin_initials: false,
+            number_words: false,
+            cjk_numbers: false,
+            pinyin_tone: false,
+            pinyin_primary: PinyinPrimary::Sorted,
+            fuzzy_pinyin: false,
+            keep_han: false,
         }
     }
 }
 
+impl std::fmt::Debug for SimpleTokenizer {
+    /// 输出 [`Tokenizer::config_summary`] 的配置摘要
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.config_summary())
+    }
+}
+
 impl SimpleTokenizer {
     /// 关闭拼音分词
     pub fn disable_pinyin(&mut self) {
@@ -33,7 +288,247 @@ impl SimpleTokenizer {
     }
     /// 不启用停词表
     pub fn disable_stopword(&mut self) {
-        self.enable_stopword = false;
+        self.pipeline.stopword = false;
+    }
+    /// 使用停词表文件代替默认的停词表，只影响这个分词器
+    ///
+    /// 文件使用 UTF-8 编码，每行一个停词，忽略空行和以 `#` 开头的注释行，
+    /// 文件无法读取时，记录一条警告日志，继续使用默认的停词表
+    pub fn load_stopwords(&mut self, path: &str) {
+        self.pipeline.load_stopwords(path);
+    }
+    /// 在当前的停词表上添加停词，只影响这个分词器
+    ///
+    /// 不启用停词表时，只使用添加的停词，不再使用默认的停词表
+    pub fn add_stopwords<S: AsRef<str>>(&mut self, words: impl IntoIterator<Item = S>) {
+        self.pipeline.add_stopwords(words);
+    }
+    /// 当前生效的停词表，包括 `stopword=` 文件、`stopword_add=` 添加的停词以及作为基础的全局停词表
+    ///
+    /// 用于排查某个单词为什么没有写入索引，不启用停词表时为空
+    pub fn effective_stopwords(&self) -> Cow<'_, HashSet<String>> {
+        self.pipeline.effective_stopwords()
+    }
+    /// 不对英文单词做词干提取，只写入小写的单词，查询时只匹配相同的单词
+    pub fn disable_stemming(&mut self) {
+        self.pipeline.stemming = false;
+    }
+    /// 只在写入文档时做词干提取，查询时使用原本的单词
+    ///
+    /// 文档中的单词会被提取词干，查询的单词不会，因此只有查询的单词刚好是词干时才能匹配，
+    /// 例如文档中的 `liking` 写入 `like`，查询 `like` 可以匹配，查询 `liking` 反而不能匹配
+    pub fn enable_stem_documents_only(&mut self) {
+        self.pipeline.stem_documents_only = true;
+    }
+    /// 对词干提取的结果检查停词，默认在词干提取之前检查
+    ///
+    /// 词干提取可能把不是停词的单词变成停词，例如 `running` 的词干 `run`，
+    /// 开启后 `running` 不再写入，而停词 `does` 的词干 `doe` 不是停词，会被写入
+    pub fn enable_stopword_after_stem(&mut self) {
+        self.pipeline.stopword_after_stem = true;
+    }
+    /// 写入文档时，单词做了词干提取的，额外写入原本的小写单词，作为 colocated token 写入
+    ///
+    /// 和 `stem_documents_only` 一起使用时，查询原本的单词也可以完全匹配，例如查询 `running` 可以匹配 `running`
+    pub fn enable_keep_original(&mut self) {
+        self.keep_original = true;
+    }
+    /// 去掉单词中的附加符号后写入，例如 `naïve` 写入 `naive`，写入文档时原本的单词作为 colocated token 写入同一个位置
+    ///
+    /// 查询时同样去掉附加符号，查询 `naive` 和 `naïve` 都可以匹配
+    pub fn enable_fold_ascii_keep_original(&mut self) {
+        self.fold_ascii_keep_original = true;
+    }
+    /// 设置词干提取使用的语言，默认使用英语
+    pub fn set_stemmer(&mut self, language: StemmerLanguage) {
+        self.pipeline.stemmer = language;
+    }
+    /// 从文件加载同义词表，写入文档时为匹配的单词额外写入同一组中的其余同义词，作为 colocated token 写入
+    ///
+    /// 文件使用 UTF-8 编码，每行是一组用逗号分隔的同义词，忽略空行和以 `#` 开头的注释行，
+    /// 同义词和归一化后的单词比较，写入时和单词一样做词干提取，因此查询任意一个同义词都可以匹配其余的同义词。
+    /// 同义词应当是分词后的单个单词，文件无法读取时，记录一条警告日志，不扩展同义词
+    pub fn load_synonyms(&mut self, path: &str) {
+        self.synonyms = load_synonyms(path).unwrap_or_default();
+    }
+    /// 为拉丁字母单词额外生成 n 个字符的片段，作为 colocated token 写入，用于容错匹配
+    ///
+    /// 会显著增大索引体积
+    pub fn enable_shingle(&mut self, n: usize) {
+        self.shingle = Some(n);
+    }
+    /// 将带单位的数字额外拆分成数字和单位，作为 colocated token 写入
+    ///
+    /// 只在写入文档时生效，查询时保持原样，避免查询被扩展成单独的数字或单位
+    pub fn enable_number_units(&mut self) {
+        self.number_units = true;
+    }
+    /// 将代码中的标识符按照下划线和驼峰切分成子词，每个子词单独做停词过滤和词干提取
+    ///
+    /// 例如 `getUserName` 和 `get_user_name` 都写入 `get`、`user`、`name`，查询 `user` 都可以匹配。
+    /// 同时开启 `keep_original` 时，写入文档时额外将整个标识符作为 colocated token 写入
+    pub fn enable_identifier_split(&mut self) {
+        self.identifier_split = true;
+    }
+    /// 在字母和 ascii 数字相邻的位置切分拉丁字母单词，每个部分单独做停词过滤和词干提取
+    ///
+    /// 例如 `ABC123` 写入 `abc`、`123`，`version2beta` 写入 `version`、`2`、`beta`，查询 `abc` 或 `123` 都可以匹配。
+    /// 和 `identifier_split` 同时开启时，先按照下划线和驼峰切分，再切分每个子词中的字母和数字；
+    /// 同时开启 `keep_original` 时，写入文档时额外将整个单词作为 colocated token 写入
+    pub fn enable_alnum_split(&mut self) {
+        self.alnum_split = true;
+    }
+    /// 添加单位的缩写，写入文档时额外生成数字和单位缩写的组合，会同时开启数字和单位的拆分
+    pub fn add_unit_abbreviation(&mut self, unit: &str, abbreviation: &str) {
+        self.enable_number_units();
+        self.unit_map
+            .insert(unit.to_lowercase(), abbreviation.to_lowercase());
+    }
+    /// 只分词，原样写入分词结果，不做归一化、停词、词干提取和拼音处理，其余配置均不生效
+    pub fn enable_raw_mode(&mut self) {
+        self.raw_mode = Some(RawMode::Words);
+    }
+    /// 设置 `raw_mode` 的切分方式，[`SimpleTokenizer::enable_raw_mode`] 相当于 [`RawMode::Words`]
+    ///
+    /// [`RawMode::Whitespace`] 只按照空白字符切分，单词中的标点和符号也会写入，例如 `C++` 和 `C#` 是两个不同的 token，
+    /// 适用于标签、枚举值等需要精确匹配的字段。查询时使用双引号包裹单词，例如 `MATCH '"C++"'`。
+    /// 其余配置中只有 token 的长度和数量限制、`dedup_document` 生效
+    pub fn set_raw_mode(&mut self, mode: RawMode) {
+        self.raw_mode = Some(mode);
+    }
+    /// 设置 emoji、`©`、`★` 等符号的处理方式，默认不写入
+    ///
+    /// 开启 [`SymbolPolicy::Keep`] 时，每个符号按照扩展字素簇作为一个 token 写入，
+    /// 使用零宽连接符组合的 emoji 序列是一个 token，可以使用 `MATCH '👍'` 查询
+    pub fn set_symbols(&mut self, symbols: SymbolPolicy) {
+        self.symbols = symbols;
+    }
+    /// 写入文档时，每个不同的 token 只写入一次，用于减小 `detail=none` 索引的体积
+    ///
+    /// 重复的 token 不再写入，token 的位置随之改变，短语查询和 NEAR 查询的结果将不再准确
+    pub fn enable_dedup_document(&mut self) {
+        self.dedup_document = true;
+    }
+    /// 设置 token 的最大字节数，超过的 token 不写入，避免超长的 token 增大索引体积
+    pub fn set_max_token_bytes(&mut self, max_bytes: usize) {
+        self.max_token_bytes = max_bytes;
+    }
+    /// 设置写入文档时最多写入的 token 数量，避免超长的文档产生大量的 token，拖慢写入
+    ///
+    /// 达到上限后停止分词并记录一条警告日志，文档仍然可以写入，只有前面的 token 可以查询，
+    /// 同一位置上的拼音等 token 不计入数量，查询的文本不受限制
+    pub fn set_max_tokens(&mut self, max_tokens: usize) {
+        self.max_tokens = Some(max_tokens);
+    }
+    /// 设置 token 的最少字符数，归一化后不足的单词不写入，用于过滤 `can't` 中的 `t` 这样的片段
+    ///
+    /// 单个汉字也有独立的含义，包含汉字的单词不受影响
+    pub fn set_min_token_chars(&mut self, min_chars: usize) {
+        self.pipeline.min_token_chars = min_chars;
+    }
+    /// 设置数字单词的处理方式，默认原样写入
+    ///
+    /// 数字是只包含数字、千位分隔符和一个小数点的单词，例如 `32.3`、`1,000`，`1990s` 这样的单词不是数字
+    pub fn set_numbers(&mut self, mode: NumberMode) {
+        self.numbers = mode;
+    }
+    /// 设置文本中无效的 UTF-8 字节的处理方式，默认替换成 U+FFFD
+    ///
+    /// `skip` 跳过无效的字节，`error` 使包含无效字节的写入和查询失败，适用于不应该出现二进制数据的字段
+    pub fn set_invalid_utf8(&mut self, policy: InvalidUtf8Policy) {
+        self.invalid_utf8 = policy;
+    }
+    /// 设置 Unicode 归一化的形式，默认为 NFKC
+    pub fn set_normalization(&mut self, form: NormalizationForm) {
+        self.pipeline.set_normalization(form);
+    }
+    /// 设置连字的处理方式，连字在归一化之前处理，默认不单独处理
+    pub fn set_ligatures(&mut self, ligatures: Ligatures) {
+        self.pipeline.set_ligatures(ligatures);
+    }
+    /// 保留组合附加符号，例如分解形式 `e\u{301}` 中的 `\u{301}`，默认去掉
+    ///
+    /// 默认的 NFKC 归一化会把能组合的附加符号合并成 `é` 这样的字符，合并后的字符不受影响，
+    /// 这个选项主要影响 `normalization=none` 时的分解形式，以及没有组合字符的附加符号
+    pub fn enable_keep_diacritics(&mut self) {
+        self.pipeline.enable_keep_diacritics();
+    }
+    /// 使用完整的 Unicode 大小写折叠代替小写转换，例如 `Straße` 和 `STRASSE` 都写入 `strasse`
+    ///
+    /// 折叠可能改变单词的长度，token 的范围仍然是单词在原始文本中的位置
+    pub fn enable_casefold(&mut self) {
+        self.pipeline.enable_casefold();
+    }
+    /// 将半角片假名转换成全角片假名，例如 `ｺﾝﾋﾟｭｰﾀｰ` 写入 `コンピューター`
+    ///
+    /// 默认的 NFKC 归一化本身就会转换，这个选项用于 `normalization=nfc` 或 `normalization=none` 时只转换片假名
+    pub fn enable_fullwidth_kana(&mut self) {
+        self.pipeline.enable_fullwidth_kana();
+    }
+    /// 设置汉字异体字选择符的处理方式，默认去掉异体字选择符
+    pub fn set_variation_selectors(&mut self, ivs: VariationSelectors) {
+        self.pipeline.set_variation_selectors(ivs);
+    }
+    /// 写入文档时，为连续的汉字额外生成拼接的全拼，作为 colocated token 写入
+    ///
+    /// 逐字的拼音仍然写入，拼接的全拼覆盖整个汉字片段的范围，多音字会列举读音组合
+    pub fn enable_full_pinyin(&mut self) {
+        self.full_pinyin = true;
+    }
+    /// 设置拼接的全拼中音节之间的分隔符，会同时开启拼接的全拼
+    ///
+    /// 用于区分有歧义的拼接，例如使用 `'` 时，`西安` 写入 `xi'an`，和 `先` 的 `xian` 不再相同，
+    /// 分隔符需要是单词的一部分（例如 `'`、`_`），查询时使用双引号包裹，例如 `MATCH '"xi''an"'`
+    pub fn set_pinyin_separator(&mut self, separator: &str) {
+        self.enable_full_pinyin();
+        self.pinyin_sep = separator.to_owned();
+    }
+    /// 写入文档时，为每个汉字和连续的汉字额外生成拼音首字母，作为 colocated token 写入
+    ///
+    /// 声母为 `zh`、`ch`、`sh` 时同时生成一个字母和两个字母的首字母，多音字会列举全部读音的首字母
+    pub fn enable_pinyin_initials(&mut self) {
+        self.pinyin_initials = true;
+    }
+    /// 写入文档时，为数字额外生成英文单词，为英文数字单词额外生成数字，作为 colocated token 写入
+    ///
+    /// 只处理 0 到 99 中可以写成一个英文单词的数字，这些数字和数字单词不再作为停词忽略
+    pub fn enable_number_words(&mut self) {
+        self.number_words = true;
+    }
+    /// 写入文档时，为连续的汉字数字额外生成阿拉伯数字，作为 colocated token 写入，例如 `三十二` 生成 `32`
+    ///
+    /// 支持 `十`、`百`、`千`、`万`、`亿` 位值和 `壹`、`贰` 等大写数字，没有位值的数字逐位转换，
+    /// 例如 `一九八四` 生成 `1984`。全部是停词的数字（例如单独的 `一`）不生成阿拉伯数字
+    pub fn enable_cjk_numbers(&mut self) {
+        self.cjk_numbers = true;
+    }
+    /// 写入文档时，为汉字额外写入带声调的拼音，作为 colocated token 写入，用于区分同音字
+    ///
+    /// 轻声的读音和不带声调的拼音相同，不再重复写入
+    pub fn enable_pinyin_tone(&mut self) {
+        self.pinyin_tone = true;
+    }
+    /// 设置多音字逐字写入拼音时第一个读音的选择方式，默认按照字母顺序
+    ///
+    /// 第一个读音作为普通的 token 写入，其余读音作为 colocated token 写入同一个位置，
+    /// [`PinyinPrimary::Common`] 先写入最常用的读音，例如 `行` 先写入 `xing`，再写入 `hang`、`heng`，
+    /// `highlight` 等辅助函数使用第一个读音。全部读音都可以匹配，查询的结果不变
+    pub fn set_pinyin_primary(&mut self, primary: PinyinPrimary) {
+        self.pinyin_primary = primary;
+    }
+    /// 将拼音音节按照模糊音映射成统一的形式，不区分 zh/z、ch/c、sh/s、l/n 和前后鼻音
+    ///
+    /// 汉字的拼音和本身就是合法拼音音节的单词在写入和查询时都会映射，因此 `si` 可以匹配 `诗`，
+    /// 合法的拼音音节不再作为停词忽略
+    pub fn enable_fuzzy_pinyin(&mut self) {
+        self.fuzzy_pinyin = true;
+    }
+    /// 写入文档时先写入汉字本身，全部读音的拼音作为 colocated token 写入同一个位置，查询汉字时只匹配相同的汉字
+    ///
+    /// 查询拼音时仍然可以匹配拼音相同的汉字，使用 `国 OR guo` 查询时，`国` 同时匹配两个短语，
+    /// `bm25` 的排名高于只有拼音相同的 `锅`
+    pub fn enable_keep_han(&mut self) {
+        self.keep_han = true;
     }
     /// 将查询文档转换成 SQLite 的 match 语句
     pub fn tokenize_query(text: &str) -> Option<String> {
@@ -83,82 +578,2270 @@ impl SimpleTokenizer {
                 acc
             })
     }
+
+    /// 对文本分词，并将每个 token 交给 `push_token` 处理
+    ///
+    /// 先使用 unicode_word_indices 切分单词，再将单词按照书写系统切分成连续的片段：
+    /// 汉字片段逐字转换成拼音，拉丁字母片段做词干提取，假名和其余片段只做归一化
+    fn tokenize_words<TKF>(
+        &self,
+        reason: TokenizeReason,
+        text: &str,
+        mut push_token: TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        let mut word_buf = String::new();
+        let mut han_run = HanRun::default();
+        if let Some(mode @ (RawMode::Whitespace | RawMode::WhitespaceLowercase)) = self.raw_mode {
+            return Self::tokenize_whitespace(mode, text, push_token);
+        }
+        let mut numbers = self.cjk_numbers(&reason, text).into_iter().peekable();
+        for (index, word) in text.split_word_bound_indices() {
+            // 不包含字母和数字的片段是空白、标点或者符号，和 unicode_word_indices 的结果相同，
+            // 开启 symbols=keep 时，其中的每个符号作为一个 token 写入
+            if !word.chars().any(char::is_alphanumeric) {
+                if self.symbols == SymbolPolicy::Keep {
+                    self.push_symbols(
+                        reason.clone(),
+                        text,
+                        index..index + word.len(),
+                        &mut word_buf,
+                        &mut han_run,
+                        &mut push_token,
+                    )?;
+                }
+                continue;
+            }
+            if self.raw_mode.is_some() {
+                if !is_punctuation_or_space_str(word) {
+                    (push_token)(word.as_bytes(), index..index + word.len(), false)?;
+                }
+                continue;
+            }
+            for (script, run) in script_runs(word) {
+                let range = index + run.start..index + run.end;
+                let run = &word[run];
+                if script == Script::Han && self.enable_pinyin {
+                    // 汉字片段逐字处理，每个字的范围单独计算
+                    let mut chars = run.char_indices().peekable();
+                    while let Some((offset, ch)) = chars.next() {
+                        let start = range.start + offset;
+                        let mut ch_range = start..start + ch.len_utf8();
+                        // 去掉的异体字选择符并入前一个字的范围
+                        while let Some(&(_, next)) = chars.peek()
+                            && self.pipeline.normalizer.strips_variation_selector(next)
+                        {
+                            ch_range.end += next.len_utf8();
+                            chars.next();
+                        }
+                        self.push_han(
+                            reason.clone(),
+                            ch,
+                            ch_range.clone(),
+                            &mut word_buf,
+                            &mut han_run,
+                            &mut push_token,
+                        )?;
+                    }
+                } else {
+                    self.flush_han_run(reason.clone(), &mut han_run, &mut push_token)?;
+                    // 英语只提取拉丁字母单词的词干，其他语言还可能是西里尔字母、希腊字母等
+                    let stemmable = script == Script::Latin
+                        || (script == Script::Other
+                            && self.pipeline.stemmer != StemmerLanguage::English);
+                    if (self.identifier_split || self.alnum_split) && script == Script::Latin {
+                        self.push_identifier(
+                            reason.clone(),
+                            run,
+                            range,
+                            &mut word_buf,
+                            &mut push_token,
+                        )?;
+                        continue;
+                    }
+                    self.push_word(
+                        reason.clone(),
+                        run,
+                        range.clone(),
+                        stemmable,
+                        &mut word_buf,
+                        &mut push_token,
+                    )?;
+                }
+                // 汉字数字的最后一个字写入之后，阿拉伯数字作为 colocated token 写入同一个位置，
+                // 最后一个字是停词时没有可以依附的 token，写入新的位置
+                while let Some((span, number)) = numbers.next_if(|(span, _)| span.end <= range.end)
+                {
+                    let last = text[span.clone()].chars().next_back().unwrap_or_default();
+                    let colocated = !self.pipeline.is_stopword(last.encode_utf8(&mut [0; 4]));
+                    (push_token)(number.as_bytes(), span, colocated)?;
+                }
+            }
+        }
+        self.flush_han_run(reason, &mut han_run, &mut push_token)
+    }
+
+    /// 将片段中的每个符号作为一个 token 写入，符号不提取词干，也没有拼音
+    fn push_symbols<TKF>(
+        &self,
+        reason: TokenizeReason,
+        text: &str,
+        range: Range<usize>,
+        word_buf: &mut String,
+        han_run: &mut HanRun,
+        push_token: &mut TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        for symbol in symbol_ranges(text, range) {
+            // 之前的汉字片段在符号之前写入拼接的拼音
+            self.flush_han_run(reason.clone(), han_run, push_token)?;
+            if self.raw_mode.is_some() {
+                (push_token)(text[symbol.clone()].as_bytes(), symbol, false)?;
+                continue;
+            }
+            self.push_word(
+                reason.clone(),
+                &text[symbol.clone()],
+                symbol,
+                false,
+                word_buf,
+                push_token,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 开启 `raw_mode=whitespace` 时，按照空白字符切分文本，原样或者转换成小写后写入
+    fn tokenize_whitespace<TKF>(mode: RawMode, text: &str, mut push_token: TKF) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        for (index, word) in whitespace_word_indices(text) {
+            let range = index..index + word.len();
+            if mode == RawMode::WhitespaceLowercase {
+                (push_token)(word.to_lowercase().as_bytes(), range, false)?;
+            } else {
+                (push_token)(word.as_bytes(), range, false)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 开启 `cjk_numbers` 时，找出写入的文档中的汉字数字，返回数字的范围和对应的阿拉伯数字
+    ///
+    /// 全部是停词的数字没有写入拼音，也不写入阿拉伯数字
+    fn cjk_numbers(&self, reason: &TokenizeReason, text: &str) -> Vec<(Range<usize>, String)> {
+        if !self.cjk_numbers || matches!(reason, TokenizeReason::Query { .. }) {
+            return Vec::new();
+        }
+        let mut spans = cjk_number_spans(text);
+        spans.retain(|(span, _)| {
+            !text[span.clone()]
+                .chars()
+                .all(|ch| self.pipeline.is_stopword(ch.encode_utf8(&mut [0; 4])))
+        });
+        spans
+    }
+
+    /// 将标识符切分成子词后分别写入，开启 `keep_original` 时，写入文档时额外写入整个标识符
+    ///
+    /// 按照 `identifier_split` 和 `alnum_split` 的设置切分，两个都开启时先按照下划线和驼峰切分
+    fn push_identifier<TKF>(
+        &self,
+        reason: TokenizeReason,
+        word: &str,
+        range: Range<usize>,
+        word_buf: &mut String,
+        push_token: &mut TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        let mut parts = match self.identifier_split {
+            true => identifier_parts(word),
+            false => std::iter::once(0..word.len()).collect(),
+        };
+        if self.alnum_split {
+            parts = parts
+                .into_iter()
+                .flat_map(|part| {
+                    alnum_parts(&word[part.clone()])
+                        .into_iter()
+                        .map(move |sub| part.start + sub.start..part.start + sub.end)
+                })
+                .collect();
+        }
+        // 子词全部是停词时，整个标识符没有可以依附的 token
+        let mut pushed = false;
+        for part in &parts {
+            let part_range = range.start + part.start..range.start + part.end;
+            self.push_word(
+                reason.clone(),
+                &word[part.clone()],
+                part_range,
+                true,
+                word_buf,
+                &mut |token: &[u8], range: Range<usize>, colocated: bool| {
+                    pushed = true;
+                    (push_token)(token, range, colocated)
+                },
+            )?;
+        }
+        if self.keep_original
+            && pushed
+            && parts.len() > 1
+            && !matches!(reason, TokenizeReason::Query { .. })
+        {
+            self.pipeline.normalize(word, word_buf);
+            (push_token)(word_buf.as_bytes(), range, true)?;
+        }
+        Ok(())
+    }
+
+    /// 汉字片段结束时，写入拼接的拼音
+    fn flush_han_run<TKF>(
+        &self,
+        reason: TokenizeReason,
+        han_run: &mut HanRun,
+        push_token: &mut TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        if han_run.readings.len() < 2 || matches!(reason, TokenizeReason::Query { .. }) {
+            // 单个汉字已经写入了逐字的拼音，查询时不扩展
+            han_run.clear();
+            return Ok(());
+        }
+        // 片段全部是停词时没有可以依附的 token，第一个 token 不能是 colocated token
+        let mut colocated = han_run.has_token;
+        if self.full_pinyin {
+            for pinyin in join_pinyin_with_separator(
+                &han_run.readings,
+                &self.pinyin_sep,
+                MAX_PINYIN_COMBINATIONS,
+            ) {
+                (push_token)(pinyin.as_bytes(), han_run.range.clone(), colocated)?;
+                colocated = true;
+            }
+        }
+        if self.pinyin_initials {
+            let initials = han_run
+                .readings
+                .iter()
+                .map(|pinyin_vec| pinyin_initials(pinyin_vec))
+                .collect::<Vec<_>>();
+            for initials in join_pinyin(&initials, MAX_PINYIN_COMBINATIONS) {
+                (push_token)(initials.as_bytes(), han_run.range.clone(), colocated)?;
+                colocated = true;
+            }
+        }
+        han_run.clear();
+        Ok(())
+    }
+
+    /// 将单个汉字转换成拼音，没有拼音的汉字按照普通单词处理
+    fn push_han<TKF>(
+        &self,
+        reason: TokenizeReason,
+        ch: char,
+        range: Range<usize>,
+        word_buf: &mut String,
+        han_run: &mut HanRun,
+        push_token: &mut TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        // 兼容汉字（例如 U+F900）需要先归一化，才能查找到拼音
+        let ch = ch.nfkc().next().unwrap_or(ch);
+        let mut char_buf = [0; 4];
+        let word = ch.encode_utf8(&mut char_buf);
+        if !need_pinyin(word) {
+            self.flush_han_run(reason.clone(), han_run, push_token)?;
+            return self.push_word(reason, word, range, false, word_buf, push_token);
+        }
+        let Some(readings) = pinyin_readings(ch) else {
+            return self.flush_han_run(reason, han_run, push_token);
+        };
+        // 只有拼接的拼音和首字母需要保存每个字的读音，空的 Vec 不会分配内存
+        let pinyin_vec = if self.full_pinyin || self.pinyin_initials {
+            readings.clone().collect()
+        } else {
+            Vec::new()
+        };
+        if !han_run.is_adjacent(&range) {
+            // 和前一个汉字不相邻，开始一个新的片段
+            self.flush_han_run(reason.clone(), han_run, push_token)?;
+        }
+        let prefix = self.prefix_end == Some(range.end);
+        if self.keep_han
+            && matches!(reason, TokenizeReason::Query { .. })
+            && (prefix || !self.pipeline.is_stopword(word))
+        {
+            // 查询时只写入汉字本身，只匹配相同的汉字
+            (push_token)(word.as_bytes(), range.clone(), false)?;
+            han_run.push(range, pinyin_vec, true);
+            return Ok(());
+        }
+        if prefix && let Some(primary) = primary_pinyin(ch) {
+            // 前缀查询的最后一个汉字和拉丁字母的前缀一样不检查停词，并且只写入最常用的读音，
+            // 多音字的其他读音作为前缀会匹配到无关的拼音，例如 `家*` 的 `gu*` 会匹配 `guo`
+            let pinyin = if self.fuzzy_pinyin {
+                fuzzy_pinyin(primary).map_or(Cow::Borrowed(primary), Cow::Owned)
+            } else {
+                Cow::Borrowed(primary)
+            };
+            (push_token)(pinyin.as_bytes(), range.clone(), false)?;
+            han_run.push(range, pinyin_vec, true);
+            return Ok(());
+        }
+        if self.stopword_hits.record(self.pipeline.is_stopword(word)) {
+            // 停词不写入逐字的拼音，但仍然属于汉字片段，用于生成拼接的拼音
+            han_run.push(range, pinyin_vec, false);
+            return Ok(());
+        }
+        // 多音字的每个读音都在同一个位置上，第一个读音之后的读音作为 colocated token 写入，
+        // 开启 keep_han 时先写入汉字本身，全部读音都作为 colocated token 写入
+        let primary = match self.pinyin_primary {
+            PinyinPrimary::Common => primary_pinyin(ch),
+            PinyinPrimary::Sorted => None,
+        };
+        let readings = primary
+            .into_iter()
+            .chain(readings.filter(move |pinyin| Some(*pinyin) != primary));
+        if self.keep_han {
+            (push_token)(word.as_bytes(), range.clone(), false)?;
+        }
+        if self.fuzzy_pinyin {
+            let mut fuzzy_vec = Vec::<String>::new();
+            for pinyin in readings.filter_map(fuzzy_pinyin) {
+                if !fuzzy_vec.contains(&pinyin) {
+                    fuzzy_vec.push(pinyin);
+                }
+            }
+            for (index, pinyin) in fuzzy_vec.iter().enumerate() {
+                (push_token)(pinyin.as_bytes(), range.clone(), self.keep_han || index > 0)?;
+            }
+        } else {
+            for (index, pinyin) in readings.enumerate() {
+                (push_token)(pinyin.as_bytes(), range.clone(), self.keep_han || index > 0)?;
+            }
+        }
+        if self.pinyin_tone
+            && !matches!(reason, TokenizeReason::Query { .. })
+            && let Some(tone_vec) = pinyin_with_tone(ch)
+        {
+            for pinyin in tone_vec.iter().filter(|pinyin| !pinyin.is_ascii()) {
+                (push_token)(pinyin.as_bytes(), range.clone(), true)?;
+            }
+        }
+        if self.pinyin_initials && !matches!(reason, TokenizeReason::Query { .. }) {
+            for initial in pinyin_initials(&pinyin_vec) {
+                (push_token)(initial.as_bytes(), range.clone(), true)?;
+            }
+        }
+        han_run.push(range, pinyin_vec, true);
+        Ok(())
+    }
+
+    /// 对单词做归一化、停词、词干提取等处理后写入
+    ///
+    /// `stemmable` 为 `true` 时，表示这个单词的书写系统和词干提取的语言匹配，可以做词干提取
+    fn push_word<TKF>(
+        &self,
+        reason: TokenizeReason,
+        word: &str,
+        range: Range<usize>,
+        stemmable: bool,
+        word_buf: &mut String,
+        push_token: &mut TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        // 对单词做归一化处理，并且将单词转换成小写
+        let mut ascii = self.pipeline.normalize(word, word_buf);
+        // 去掉附加符号之前的单词，写入文档时作为 colocated token 写入
+        let mut original = None;
+        if self.fold_ascii_keep_original
+            && let Some(folded) = fold_diacritics(word_buf)
+        {
+            ascii = folded.is_ascii() && folded.len() > 1;
+            original = Some(std::mem::replace(word_buf, folded));
+        }
+        if word_buf.is_empty() {
+            // 归一化后为空串，例如只包含方向控制字符
+            return Ok(());
+        }
+        if !self.pipeline.transform(word_buf, &mut ascii) {
+            // 自定义的转换不写入这个单词
+            return Ok(());
+        }
+        let need_stem = stemmable && self.pipeline.need_stem(&reason, ascii, word_buf);
+        if self.pipeline.is_too_short(word_buf) {
+            // 少于 min_token_chars 个字符的单词不写入
+            return Ok(());
+        }
+        if !self.numbers.apply(word_buf) {
+            // 开启 numbers=drop 时不写入数字
+            return Ok(());
+        }
+        let number_word = if self.number_words {
+            en_number_word(word_buf)
+        } else {
+            None
+        };
+        let fuzzy = if self.fuzzy_pinyin && stemmable {
+            fuzzy_pinyin(word_buf)
+        } else {
+            None
+        };
+        // 前缀查询的最后一个单词不完整，不检查停词
+        let prefix = self.prefix_end == Some(range.end);
+        if !prefix
+            && number_word.is_none()
+            && fuzzy.is_none()
+            && self
+                .stopword_hits
+                .record(self.pipeline.is_stemmed_stopword(word_buf, need_stem))
+        {
+            // 不处理停词，开启 number_words 时的数字和数字单词、开启 fuzzy_pinyin 时的拼音音节需要保留
+            return Ok(());
+        }
+        if let Some(fuzzy) = fuzzy {
+            // 合法的拼音音节不做词干提取，和汉字的拼音一样映射
+            (push_token)(fuzzy.as_bytes(), range.clone(), false)?;
+        } else if need_stem {
+            // 词干提取没有改变单词时借用 word_buf，不需要分配内存
+            let stemmed = self.pipeline.stem(word_buf);
+            (push_token)(stemmed.as_bytes(), range.clone(), false)?;
+            if self.keep_original
+                && stemmed != *word_buf
+                && !matches!(reason, TokenizeReason::Query { .. })
+            {
+                (push_token)(word_buf.as_bytes(), range.clone(), true)?;
+            }
+        } else {
+            (push_token)(word_buf.as_bytes(), range.clone(), false)?;
+        }
+        if let Some(original) = original
+            && !matches!(reason, TokenizeReason::Query { .. })
+        {
+            (push_token)(original.as_bytes(), range.clone(), true)?;
+        }
+        if !matches!(reason, TokenizeReason::Query { .. })
+            && let Some(synonyms) = self.synonyms.get(word_buf.as_str())
+        {
+            for synonym in synonyms {
+                // 查询的同义词会和单词一样做词干提取，这里保持一致
+                let synonym = if self
+                    .pipeline
+                    .need_stem(&reason, synonym.is_ascii(), synonym)
+                {
+                    self.pipeline.stem(synonym)
+                } else {
+                    Cow::Borrowed(synonym.as_str())
+                };
+                (push_token)(synonym.as_bytes(), range.clone(), true)?;
+            }
+        }
+        if let Some(n) = self.shingle
+            && is_latin_word(word_buf)
+        {
+            for shingle in shingles(word_buf, n) {
+                (push_token)(shingle.as_bytes(), range.clone(), true)?;
+            }
+        }
+        if let Some(number_word) = number_word
+            && !matches!(reason, TokenizeReason::Query { .. })
+        {
+            // 查询时会对英文单词做词干提取，这里保持一致
+            let stemmed = self.pipeline.stem(&number_word);
+            (push_token)(stemmed.as_bytes(), range.clone(), true)?;
+        }
+        if self.number_units
+            && !matches!(reason, TokenizeReason::Query { .. })
+            && let Some(split) = split_number_unit(word_buf)
+        {
+            // 自定义的转换可能改变单词的长度，长度不变时数字和单位才能对应到原始文本中的位置
+            let (number_range, unit_range) = if word_buf.len() == word.len() {
+                (
+                    range.start..range.start + split,
+                    range.start + split..range.end,
+                )
+            } else {
+                (range.clone(), range.clone())
+            };
+            let (number, unit) = word_buf.split_at(split);
+            (push_token)(number.as_bytes(), number_range, true)?;
+            let stemmed = self.pipeline.stem(unit);
+            (push_token)(stemmed.as_bytes(), unit_range, true)?;
+            if let Some(abbreviation) = self.unit_map.get(unit) {
+                let normalized = format!("{number}{abbreviation}");
+                (push_token)(normalized.as_bytes(), range.clone(), true)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Tokenizer for SimpleTokenizer {
-    type Global = ();
+    type Global = SimpleGlobal;
 
     fn name() -> &'static CStr {
         c"simple"
     }
 
-    fn new(_global: &Self::Global, args: Vec<String>) -> Result<Self, Error> {
+    /// 默认支持拼音、启用停词表并且做词干提取，这些选项只有 `disable_*` 参数，不出现在默认参数中
+    fn default_args() -> Vec<String> {
+        [
+            "stemmer=english".to_owned(),
+            "normalization=nfkc".to_owned(),
+            "ligatures=keep".to_owned(),
+            "ivs=strip".to_owned(),
+            "numbers=keep".to_owned(),
+            "invalid_utf8=lossy".to_owned(),
+            format!("max_token_bytes={DEFAULT_MAX_TOKEN_BYTES}"),
+        ]
+        .into()
+    }
+
+    /// 总是包含拼音、停词和词干提取的开关，其余参数只在和默认值不同时出现
+    fn config_summary(&self) -> String {
+        let mut summary = ConfigSummary::new(Self::name());
+        summary.switch("pinyin", self.enable_pinyin);
+        self.pipeline.summarize(&mut summary);
+        summary
+            .flag("full_pinyin", self.full_pinyin, false)
+            .flag("pinyin_initials", self.pinyin_initials, false)
+            .flag("pinyin_tone", self.pinyin_tone, false)
+            .flag("fuzzy_pinyin", self.fuzzy_pinyin, false)
+            .option("pinyin_primary", self.pinyin_primary, PinyinPrimary::Sorted);
+        if !self.pinyin_sep.is_empty() {
+            summary.field("pinyin_sep", &self.pinyin_sep);
+        }
+        summary
+            .flag("keep_original", self.keep_original, false)
+            .flag(
+                "fold_ascii_keep_original",
+                self.fold_ascii_keep_original,
+                false,
+            )
+            .flag("keep_han", self.keep_han, false);
+        if let Some(n) = self.shingle {
+            summary.field("shingle", n);
+        }
+        if !self.synonyms.is_empty() {
+            summary.field("synonyms", self.synonyms.len());
+        }
+        if !self.unit_map.is_empty() {
+            summary.field("unit_map", self.unit_map.len());
+        }
+        summary
+            .flag("number_units", self.number_units, false)
+            .flag("identifier_split", self.identifier_split, false)
+            .flag("alnum_split", self.alnum_split, false);
+        if let Some(mode) = self.raw_mode {
+            summary.field("raw_mode", mode.summary());
+        }
+        summary
+            .flag("dedup_document", self.dedup_document, false)
+            .option("numbers", self.numbers, NumberMode::Keep)
+            .option("symbols", self.symbols, SymbolPolicy::Drop)
+            .option("invalid_utf8", self.invalid_utf8, InvalidUtf8Policy::Lossy)
+            .flag("number_words", self.number_words, false)
+            .flag("cjk_numbers", self.cjk_numbers, false);
+        if self.max_token_bytes != DEFAULT_MAX_TOKEN_BYTES {
+            summary.field("max_token_bytes", self.max_token_bytes);
+        }
+        if let Some(n) = self.max_tokens {
+            summary.field("max_tokens", n);
+        }
+        summary.finish()
+    }
+
+    fn new(global: &Self::Global, args: Vec<String>) -> Result<Self, Error> {
         let mut tokenizer = Self::default();
+        // 全局停词表在创建时获取一次，之后替换不影响这个实例，重新分词已经写入的文档时结果一致
+        tokenizer.pipeline.refresh_stopwords();
+        tokenizer.pipeline.set_transform(global.transform.clone());
+        let mut stopword_add = Vec::new();
+        let mut raw_modes = Vec::new();
+        let mut unknown = Vec::new();
         for arg in args {
-            match arg.as_str() {
-                "disable_pinyin" => {
-                    tokenizer.disable_pinyin();
+            match arg.split_once('=') {
+                Some(("stopword", path)) => {
+                    tokenizer.load_stopwords(path);
+                }
+                Some(("stopword_add", word)) => {
+                    stopword_add.push(word.to_owned());
+                }
+                Some(("synonyms", path)) => {
+                    tokenizer.load_synonyms(path);
+                }
+                Some(("stemmer", language)) => {
+                    tokenizer.set_stemmer(parse_stemmer_arg(language)?);
+                }
+                Some(("shingle", n)) => {
+                    tokenizer.enable_shingle(parse_positive_arg("shingle", n)?);
+                }
+                Some(("max_token_bytes", n)) => {
+                    tokenizer.set_max_token_bytes(parse_positive_arg("max_token_bytes", n)?);
                 }
-                "disable_stopword" => {
-                    tokenizer.disable_stopword();
+                Some(("max_tokens", n)) => {
+                    tokenizer.set_max_tokens(parse_positive_arg("max_tokens", n)?);
                 }
-                _ => {}
+                Some(("min_token_chars", n)) => {
+                    tokenizer.set_min_token_chars(parse_positive_arg("min_token_chars", n)?);
+                }
+                Some(("normalization", form)) => {
+                    tokenizer.set_normalization(NormalizationForm::from_arg(form)?);
+                }
+                Some(("numbers", mode)) => {
+                    tokenizer.set_numbers(NumberMode::from_arg(mode)?);
+                }
+                Some(("symbols", symbols)) => {
+                    tokenizer.set_symbols(SymbolPolicy::from_arg(symbols)?);
+                }
+                Some(("raw_mode", mode)) => {
+                    raw_modes.push(RawMode::from_arg(mode)?);
+                }
+                Some(("invalid_utf8", policy)) => {
+                    tokenizer.set_invalid_utf8(InvalidUtf8Policy::from_arg(policy)?);
+                }
+                Some(("ligatures", ligatures)) => {
+                    tokenizer.set_ligatures(Ligatures::from_arg(ligatures)?);
+                }
+                Some(("ivs", ivs)) => {
+                    tokenizer.set_variation_selectors(VariationSelectors::from_arg(ivs)?);
+                }
+                Some(("pinyin_primary", primary)) => {
+                    tokenizer.set_pinyin_primary(PinyinPrimary::from_arg(primary)?);
+                }
+                Some(("pinyin_sep", separator)) => {
+                    tokenizer.set_pinyin_separator(&parse_pinyin_sep_arg(separator)?);
+                }
+                Some(("number_words", "en")) => {
+                    tokenizer.enable_number_words();
+                }
+                Some(("number_words", language)) => {
+                    return Err(Error::ModuleError(format!(
+                        "number_words only supports en, got {language}"
+                    )));
+                }
+                Some(("cjk_numbers", "arabic")) => {
+                    tokenizer.enable_cjk_numbers();
+                }
+                Some(("cjk_numbers", format)) => {
+                    return Err(Error::ModuleError(format!(
+                        "cjk_numbers only supports arabic, got {format}"
+                    )));
+                }
+                Some(("unit_map", pairs)) => {
+                    for pair in pairs.split(',') {
+                        let Some((unit, abbreviation)) = pair.split_once(':') else {
+                            return Err(Error::ModuleError(format!(
+                                "unit_map entry must be unit:abbreviation, got {pair}"
+                            )));
+                        };
+                        tokenizer.add_unit_abbreviation(unit.trim(), abbreviation.trim());
+                    }
+                }
+                _ => match arg.as_str() {
+                    "disable_pinyin" => {
+                        tokenizer.disable_pinyin();
+                    }
+                    "disable_stopword" => {
+                        tokenizer.disable_stopword();
+                    }
+                    "disable_stemming" => {
+                        tokenizer.disable_stemming();
+                    }
+                    "keep_diacritics" => {
+                        tokenizer.enable_keep_diacritics();
+                    }
+                    "casefold" => {
+                        tokenizer.enable_casefold();
+                    }
+                    "fullwidth_kana" => {
+                        tokenizer.enable_fullwidth_kana();
+                    }
+                    "stem_documents_only" => {
+                        tokenizer.enable_stem_documents_only();
+                    }
+                    "stopword_after_stem" => {
+                        tokenizer.enable_stopword_after_stem();
+                    }
+                    "keep_original" => {
+                        tokenizer.enable_keep_original();
+                    }
+                    "fold_ascii_keep_original" => {
+                        tokenizer.enable_fold_ascii_keep_original();
+                    }
+                    "keep_han" => {
+                        tokenizer.enable_keep_han();
+                    }
+                    "number_units" => {
+                        tokenizer.enable_number_units();
+                    }
+                    "identifier_split" => {
+                        tokenizer.enable_identifier_split();
+                    }
+                    "alnum_split" => {
+                        tokenizer.enable_alnum_split();
+                    }
+                    "raw_mode" => {
+                        raw_modes.push(RawMode::Words);
+                    }
+                    "dedup_document" => {
+                        tokenizer.enable_dedup_document();
+                    }
+                    "full_pinyin" => {
+                        tokenizer.enable_full_pinyin();
+                    }
+                    "pinyin_initials" => {
+                        tokenizer.enable_pinyin_initials();
+                    }
+                    "pinyin_tone" => {
+                        tokenizer.enable_pinyin_tone();
+                    }
+                    "fuzzy_pinyin" => {
+                        tokenizer.enable_fuzzy_pinyin();
+                    }
+                    _ => unknown.push(arg.clone()),
+                },
+            }
+        }
+        reject_unknown_args("simple", &unknown)?;
+        match raw_modes.as_slice() {
+            [] => {}
+            [mode] => tokenizer.set_raw_mode(*mode),
+            // 多个切分方式总有一个不生效，不按照参数的顺序选择其中一个
+            _ => {
+                return Err(Error::ModuleError(
+                    "raw_mode can only be given once".to_owned(),
+                ));
             }
         }
+        if !stopword_add.is_empty() {
+            // 所有参数处理完之后再合并，结果和参数的顺序无关
+            tokenizer.add_stopwords(stopword_add);
+        }
         Ok(tokenizer)
     }
 
     fn tokenize<TKF>(
         &mut self,
-        _reason: TokenizeReason,
+        reason: TokenizeReason,
         text: &[u8],
-        mut push_token: TKF,
+        push_token: TKF,
     ) -> Result<(), Error>
     where
         TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
     {
-        let text = String::from_utf8_lossy(text);
-        // 使用 unicode_word_indices 进行分词，所有中文字符应该是单独一个字符成 word
-        let mut word_buf = String::new();
-        for (index, word) in text.unicode_word_indices() {
-            let range = index..index + word.len();
-            // 开启 pinyin 并且这个是中文字符
-            if self.enable_pinyin && need_pinyin(word) {
-                if self.enable_stopword && STOPWORD.contains(word) {
-                    // 不处理停词
-                    continue;
-                }
-                if let Some(ch) = word.chars().next()
-                    && let Some(pinyin_vec) = get_pinyin(&ch)
-                {
-                    for pinyin in pinyin_vec {
-                        (push_token)(pinyin.as_bytes(), range.clone(), false)?;
-                    }
-                }
-            } else {
-                // 不需要使用 pinyin 模块进行处理
-                // 对单词做归一化处理，并且将单词转换成小写
-                let need_stem = make_lowercase(word, &mut word_buf);
-                if self.enable_stopword && STOPWORD.contains(word_buf.as_str()) {
-                    // 不处理停词
-                    continue;
-                }
-                if need_stem {
-                    let stemmed = EN_STEMMER.stem(word_buf.as_str()).into_owned();
-                    (push_token)(stemmed.as_bytes(), range, false)?;
-                } else {
-                    (push_token)(word_buf.as_bytes(), range, false)?;
-                }
-            }
-        }
-        Ok(())
+        let mut push_token = observe_tokens("simple", push_token);
+        self.stopword_hits.reset();
+        // 分词得到的是转换后字符串中的位置，写入之前转换成原始字节中的位置
+        let text = LossyText::with_policy(text, self.invalid_utf8)?;
+        self.prefix_end = prefix_end(&reason, text.as_str());
+        // 达到 token 数量的上限后停止分词，已经写入的 token 仍然有效
+        let limit = TokenLimit::new(self.max_tokens, &reason);
+        let push_token = skip_long_tokens(
+            self.max_token_bytes,
+            limit.wrap(|token: &[u8], range: Range<usize>, colocated: bool| {
+                (push_token)(token, text.original_range(range), colocated)
+            }),
+        );
+        let result = if self.dedup_document && reason == TokenizeReason::Document {
+            // 同一个 token 在文档中只写入一次
+            self.tokenize_words(reason, text.as_str(), dedup_tokens(push_token))
+        } else {
+            self.tokenize_words(reason, text.as_str(), push_token)
+        };
+        limit.finish("simple", result)
+    }
+
+    fn stopwords_filtered(&self) -> usize {
+        self.stopword_hits.get()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::tokenizer::simple_tokenizer::{
+        SimpleGlobal, SimpleTokenizer, SimpleTokenizerConfig,
+    };
+    use crate::tokenizer::standalone::{tokenize_to_vec, tokenize_with_positions};
+    use crate::tokenizer::tests::query_texts;
+    use crate::tokenizer::{TokenizeReason, Tokenizer, register_tokenizer};
+    use rusqlite::Connection;
+    use std::ops::Range;
+    use std::sync::Arc;
     use unicode_segmentation::UnicodeSegmentation;
 
+    #[test]
+    fn test_simple_tokenizer_default_args() {
+        let args = SimpleTokenizer::default_args();
+        // 拼音、停词表和词干提取默认启用
+        for flag in ["disable_pinyin", "disable_stopword", "disable_stemming"] {
+            assert!(!args.iter().any(|arg| arg == flag), "{flag}");
+        }
+        assert!(args.iter().any(|arg| arg == "stemmer=english"));
+        // 默认参数和不指定参数的分词结果相同
+        let text = "中华人民共和国 SQLite databases";
+        assert_eq!(
+            tokenize_to_vec::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                vec![],
+                TokenizeReason::Document,
+                text.as_bytes()
+            )
+            .unwrap(),
+            tokenize_to_vec::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                text.as_bytes()
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_simple_tokenizer_config() {
+        assert_eq!("simple", SimpleTokenizerConfig::default().to_tokenize_arg());
+        let config = SimpleTokenizerConfig {
+            pinyin: false,
+            stopword: true,
+            stemming: false,
+        };
+        assert_eq!(
+            "simple disable_pinyin disable_stemming",
+            config.to_tokenize_arg()
+        );
+        // 序列化后再解析得到相同的配置
+        for pinyin in [true, false] {
+            for stopword in [true, false] {
+                for stemming in [true, false] {
+                    let config = SimpleTokenizerConfig {
+                        pinyin,
+                        stopword,
+                        stemming,
+                    };
+                    let parsed =
+                        SimpleTokenizerConfig::from_tokenize_arg(&config.to_tokenize_arg());
+                    assert_eq!(config, parsed.unwrap());
+                }
+            }
+        }
+        assert!(SimpleTokenizerConfig::from_tokenize_arg("jieba disable_stopword").is_err());
+        assert!(SimpleTokenizerConfig::from_tokenize_arg("simple disable_pinyn").is_err());
+        assert!(SimpleTokenizerConfig::from_tokenize_arg("").is_err());
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        let config = SimpleTokenizerConfig {
+            stopword: false,
+            ..Default::default()
+        };
+        conn.execute(
+            &format!(
+                "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = '{}');",
+                config.to_tokenize_arg()
+            ),
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO t1(text) VALUES ('the tokenizer');", [])
+            .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'the';");
+        assert_eq!(["the tokenizer"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_shingle() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t2 USING fts5(text, tokenize = \"simple 'shingle=3'\");",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            r#"INSERT INTO t1(text) VALUES ('I''m making a sqlite tokenizer'),('静夜思');"#,
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            r#"INSERT INTO t2(text) VALUES ('I''m making a sqlite tokenizer'),('静夜思');"#,
+            [],
+        )
+        .unwrap();
+        // 少了一个字母的查询，没有片段时无法匹配
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'tokenizr';");
+        assert_eq!(0, vec.len());
+        // 通过共同的片段部分匹配
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'tokenizr';");
+        assert_eq!(["I'm making a sqlite tokenizer"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_number_units() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = \"simple 'unit_map=feet:ft,inches:in'\");",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('jump 32.3feet'),('32.3 meters'),('run 10inches');",
+            [],
+        )
+        .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"32.3feet\"';");
+        assert_eq!(["jump 32.3feet"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"32.3\"';");
+        assert_eq!(["jump 32.3feet", "32.3 meters"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'feet';");
+        assert_eq!(["jump 32.3feet"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"32.3ft\"';");
+        assert_eq!(["jump 32.3feet"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"10in\"';");
+        assert_eq!(["run 10inches"], vec.as_slice());
+        // 查询时不拆分，带单位的数字不会匹配只有数字的文档
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\"32.3meters\"';");
+        assert_eq!(0, vec.len());
+        // highlight 会忽略 colocated token，高亮整个单词
+        let vec = query_texts(
+            &conn,
+            "SELECT highlight(t1, 0, '[', ']') FROM t1 WHERE text MATCH 'feet';",
+        );
+        assert_eq!(["jump [32.3feet]"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_number_words() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = \"simple 'number_words=en'\");",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('3 apples'),('seventy pears'),('21 grapes');",
+            [],
+        )
+        .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'three';");
+        assert_eq!(["3 apples"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '3';");
+        assert_eq!(["3 apples"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '70';");
+        assert_eq!(["seventy pears"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'seventy';");
+        assert_eq!(["seventy pears"], vec.as_slice());
+        // 复合的数字不做处理
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'twenty';");
+        assert_eq!(0, vec.len());
+        let result = conn.execute(
+            "CREATE VIRTUAL TABLE t2 USING fts5(text, tokenize = \"simple 'number_words=fr'\");",
+            [],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_cjk_numbers() {
+        let tokenize = |reason: TokenizeReason, text: &str| {
+            tokenize_with_positions::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                vec!["cjk_numbers=arabic".to_owned()],
+                reason,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| (String::from_utf8(t.token).unwrap(), t.range, t.position))
+            .collect::<Vec<_>>()
+        };
+        // 阿拉伯数字和数字的最后一个字在同一个位置，范围是整个数字
+        assert_eq!(
+            [
+                ("san", 0..3, 0),
+                ("shi", 3..6, 1),
+                ("er", 6..9, 2),
+                ("32", 0..9, 2),
+            ]
+            .map(|(token, range, position)| (token.to_owned(), range, position))
+            .as_slice(),
+            tokenize(TokenizeReason::Document, "三十二").as_slice()
+        );
+        // 最后一个字是停词时，阿拉伯数字写入新的位置
+        assert_eq!(
+            [("san", 0..3, 0), ("shi", 3..6, 1), ("31", 0..9, 2)]
+                .map(|(token, range, position)| (token.to_owned(), range, position))
+                .as_slice(),
+            tokenize(TokenizeReason::Document, "三十一").as_slice()
+        );
+        // 查询时不生成阿拉伯数字
+        assert_eq!(
+            ["san", "shi", "er"].as_slice(),
+            tokenize(TokenizeReason::Query { prefix: false }, "三十二")
+                .into_iter()
+                .map(|(token, _, _)| token)
+                .collect::<Vec<_>>()
+                .as_slice()
+        );
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_cjk_numbers() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = \"simple 'cjk_numbers=arabic'\");",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('三十二名员工'),('第一百零五条'),('quokka 105');",
+            [],
+        )
+        .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '32';");
+        assert_eq!(["三十二名员工"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '105';");
+        assert_eq!(["第一百零五条", "quokka 105"], vec.as_slice());
+        // 汉字数字仍然可以使用汉字查询
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '三十二';");
+        assert_eq!(["三十二名员工"], vec.as_slice());
+        let result = conn.execute(
+            "CREATE VIRTUAL TABLE t2 USING fts5(text, tokenize = \"simple 'cjk_numbers=roman'\");",
+            [],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_normalized_offsets() {
+        // 全角字符和连字归一化后长度改变，范围仍然对应原始的字节
+        let text = "ＳＱＬｉｔｅ ３ ﬁle oﬃce";
+        let tokens = tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["disable_stopword".to_owned()],
+            TokenizeReason::Document,
+            text.as_bytes(),
+        )
+        .unwrap();
+        let tokens = tokens
+            .iter()
+            .map(|t| {
+                (
+                    String::from_utf8_lossy(&t.token).into_owned(),
+                    &text[t.range.clone()],
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("sqlite".to_owned(), "ＳＱＬｉｔｅ"),
+                ("3".to_owned(), "３"),
+                ("file".to_owned(), "ﬁle"),
+                ("offic".to_owned(), "oﬃce"),
+            ],
+            tokens
+        );
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_invalid_utf8() {
+        // 无效的 UTF-8 字节被替换成 U+FFFD，范围仍然对应原始的字节
+        let text = b"\xff\xfe sqlite \xe5\x9b tokenizer \xe5\x9b\xbd\xe5\xae\xb6\xe5";
+        let tokens = tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["disable_stopword".to_owned(), "raw_mode".to_owned()],
+            TokenizeReason::Document,
+            text,
+        )
+        .unwrap();
+        assert!(!tokens.is_empty());
+        for token in &tokens {
+            assert!(token.range.end <= text.len());
+            if token.token.is_ascii() {
+                assert_eq!(token.token, text[token.range.clone()]);
+            }
+        }
+        let ranges = tokens
+            .iter()
+            .filter(|t| t.token == b"sqlite" || t.token == b"tokenizer")
+            .map(|t| t.range.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![3..9, 13..22], ranges);
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_max_token_bytes() {
+        let tokenize = |args: Vec<String>, text: &str| {
+            tokenize_with_positions::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| String::from_utf8(t.token).unwrap())
+            .collect::<Vec<_>>()
+        };
+        // 没有空白的超长文本作为一个单词，超过默认的限制，不写入
+        let blob = "QUJDREVGR0hJSktMTU5PUFFSU1RVVldYWVo".repeat(1500);
+        assert!(tokenize(vec![], &blob).is_empty());
+        assert_eq!(
+            ["sqlite", "rust"],
+            tokenize(vec![], &format!("sqlite {blob} rust")).as_slice()
+        );
+        assert_eq!(
+            ["sqlite"],
+            tokenize(vec!["max_token_bytes=6".to_owned()], "sqlite databases").as_slice()
+        );
+        let result = tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["max_token_bytes=0".to_owned()],
+            TokenizeReason::Document,
+            b"sqlite",
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_max_tokens() {
+        let tokenize = |reason: TokenizeReason, text: &str| {
+            tokenize_with_positions::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                vec!["max_tokens=2".to_owned()],
+                reason,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| String::from_utf8(t.token).unwrap())
+            .collect::<Vec<_>>()
+        };
+        // 多音字的几个拼音在同一位置，只计入一次
+        assert_eq!(
+            ["sqlite", "hang", "heng", "xing"],
+            tokenize(TokenizeReason::Document, "sqlite 行 quokka zephyr").as_slice()
+        );
+        // 查询的文本不受限制
+        assert_eq!(
+            ["sqlite", "quokka", "zephyr"],
+            tokenize(
+                TokenizeReason::Query { prefix: false },
+                "sqlite quokka zephyr"
+            )
+            .as_slice()
+        );
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_max_tokens() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = \"simple 'max_tokens=10'\");",
+            [],
+        )
+        .unwrap();
+        let text = (0..100_000)
+            .map(|i| format!("word{i}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        conn.execute("INSERT INTO t1(text) VALUES (?1);", [&text])
+            .unwrap();
+        // 只有前 10 个单词可以查询
+        for (query, count) in [("word0", 1), ("word9", 1), ("word10", 0), ("word99999", 0)] {
+            let rows: i64 = conn
+                .query_row(
+                    &format!("SELECT count(*) FROM t1 WHERE text MATCH '{query}';"),
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(count, rows, "{query}");
+        }
+        // 删除文档时同样只删除前 10 个 token，索引保持一致
+        conn.execute("DELETE FROM t1;", []).unwrap();
+        conn.execute("INSERT INTO t1(t1) VALUES ('integrity-check');", [])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_transform() {
+        let mut global = SimpleGlobal::default();
+        // 转换成大写，丢弃 quokka
+        global.set_transform(Arc::new(|word: &str| {
+            (word != "quokka").then(|| word.to_uppercase())
+        }));
+        let tokens = tokenize_with_positions::<SimpleTokenizer>(
+            &global,
+            vec![],
+            TokenizeReason::Document,
+            "SQLite quokka learning 国".as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|t| String::from_utf8(t.token).unwrap())
+        .collect::<Vec<_>>();
+        // 大写的单词不被词干提取改变，汉字的拼音不经过转换
+        assert_eq!(["SQLITE", "LEARNING", "guo"], tokens.as_slice());
+        // 转换改变了带单位的数字的长度时，数字和单位使用整个单词的位置
+        let mut global = SimpleGlobal::default();
+        global.set_transform(Arc::new(|word: &str| Some(word.replace("000", ""))));
+        let tokens = tokenize_to_vec::<SimpleTokenizer>(
+            &global,
+            vec!["number_units".to_owned()],
+            TokenizeReason::Document,
+            "1000000m".as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|(token, range, _)| (String::from_utf8(token).unwrap(), range))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            [("1m", 0..8), ("1", 0..8), ("m", 0..8)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokens.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_min_token_chars() {
+        let tokenize = |args: Vec<String>, text: &str| {
+            tokenize_with_positions::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| String::from_utf8(t.token).unwrap())
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ["i", "can't", "imagin", "guo"],
+            tokenize(vec!["disable_stopword".to_owned()], "I can't imagine 国").as_slice()
+        );
+        // 汉字按照拼音写入，不受影响
+        assert_eq!(
+            ["can't", "imagin", "guo"],
+            tokenize(
+                vec![
+                    "disable_stopword".to_owned(),
+                    "min_token_chars=2".to_owned()
+                ],
+                "I can't imagine 国"
+            )
+            .as_slice()
+        );
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_invalid_utf8_policy() {
+        let tokenize = |args: Vec<String>| {
+            tokenize_with_positions::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                b"sqlite\xffquokka",
+            )
+            .map(|tokens| {
+                tokens
+                    .into_iter()
+                    .map(|t| (String::from_utf8(t.token).unwrap(), t.range))
+                    .collect::<Vec<_>>()
+            })
+        };
+        let expected = [("sqlite", 0..6), ("quokka", 7..13)]
+            .map(|(token, range)| (token.to_owned(), range))
+            .to_vec();
+        // simple 不写入 U+FFFD，lossy 和 skip 的结果相同
+        assert_eq!(expected, tokenize(vec![]).unwrap());
+        assert_eq!(
+            expected,
+            tokenize(vec!["invalid_utf8=skip".to_owned()]).unwrap()
+        );
+        assert!(matches!(
+            tokenize(vec!["invalid_utf8=error".to_owned()]),
+            Err(rusqlite::Error::Utf8Error(_))
+        ));
+        assert!(
+            SimpleTokenizer::new(
+                &SimpleGlobal::default(),
+                vec!["invalid_utf8=ignore".to_owned()]
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_numbers() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, numbers) in [("t1", "keep"), ("t2", "drop"), ("t3", "normalize")] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = \"simple 'numbers={numbers}'\");"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('jump 32.3 feet'),('1,000 rust'),('code 0042');"),
+                [],
+            )
+            .unwrap();
+        }
+        let count = |table: &str, query: &str| {
+            query_texts(
+                &conn,
+                &format!("SELECT * FROM {table} WHERE text MATCH '{query}';"),
+            )
+            .len()
+        };
+        assert_eq!(1, count("t1", "\"32.3\""));
+        assert_eq!(1, count("t1", "\"1,000\""));
+        assert_eq!(0, count("t1", "1000"));
+        assert_eq!(0, count("t1", "42"));
+        // 不写入数字，数字两边的单词相邻
+        assert_eq!(0, count("t2", "\"32.3\""));
+        assert_eq!(0, count("t2", "\"1,000\""));
+        assert_eq!(1, count("t2", "\"jump feet\""));
+        assert_eq!(1, count("t2", "rust"));
+        // 去掉千位分隔符和开头的 0，查询时同样归一化
+        assert_eq!(1, count("t3", "\"32.3\""));
+        assert_eq!(1, count("t3", "1000"));
+        assert_eq!(1, count("t3", "\"1,000\""));
+        assert_eq!(1, count("t3", "42"));
+        assert_eq!(1, count("t3", "0042"));
+        let result = conn.execute(
+            "CREATE VIRTUAL TABLE t4 USING fts5(text, tokenize = \"simple 'numbers=round'\");",
+            [],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_dedup_document() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [("t1", "simple"), ("t2", "simple dedup_document")] {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table}_vocab USING fts5vocab({table}, 'instance');"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES (?1);"),
+                ["tokenizer ".repeat(100)],
+            )
+            .unwrap();
+        }
+        let count = |table: &str| -> i64 {
+            conn.query_row(
+                &format!("SELECT count(*) FROM {table}_vocab WHERE term = 'token';"),
+                [],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(100, count("t1"));
+        assert_eq!(1, count("t2"));
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'tokenizer';");
+        assert_eq!(1, vec.len());
+        // 行的第一个读音 hang 已经写入，之后的读音写入行的位置，不依附到前面的 hang
+        let tokens = tokenize_to_vec::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["dedup_document".to_owned()],
+            TokenizeReason::Document,
+            "hang 行".as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|(token, range, colocated)| (String::from_utf8(token).unwrap(), range, colocated))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            [
+                ("hang", 0..4, false),
+                ("heng", 5..8, false),
+                ("xing", 5..8, true)
+            ]
+            .map(|(token, range, colocated)| (token.to_owned(), range, colocated))
+            .as_slice(),
+            tokens.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_normalization() {
+        let tokenize = |form: &str, text: &str| {
+            tokenize_to_vec::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                vec![
+                    "disable_stopword".to_owned(),
+                    format!("normalization={form}"),
+                ],
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(token, _, _)| String::from_utf8(token).unwrap())
+            .collect::<Vec<_>>()
+        };
+        for (form, expected) in [
+            ("nfkc", ["2", "fi", "sql"]),
+            ("nfc", ["②", "ﬁ", "ｓｑｌ"]),
+            ("none", ["②", "ﬁ", "ｓｑｌ"]),
+        ] {
+            assert_eq!(
+                expected.as_slice(),
+                [
+                    tokenize(form, "②"),
+                    tokenize(form, "ﬁ"),
+                    tokenize(form, "ＳＱＬ")
+                ]
+                .concat(),
+                "{form}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_ligatures() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = \"simple 'normalization=nfc'\");",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t2 USING fts5(text, tokenize = \"simple 'normalization=nfc' 'ligatures=decompose'\");",
+            [],
+        )
+        .unwrap();
+        for table in ["t1", "t2"] {
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('ﬁle'),('②');"),
+                [],
+            )
+            .unwrap();
+        }
+        // NFC 不分解连字
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'file';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'file';");
+        assert_eq!(["ﬁle"], vec.as_slice());
+        // 只分解连字，其余的兼容字符保持不变
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '2';");
+        assert_eq!(0, vec.len());
+        let result = conn.execute(
+            "CREATE VIRTUAL TABLE t3 USING fts5(text, tokenize = \"simple 'ligatures=split'\");",
+            [],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_ivs() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [
+            ("t1", "simple"),
+            ("t2", "simple disable_pinyin"),
+            ("t3", "simple disable_pinyin 'ivs=keep'"),
+        ] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = \"{tokenize}\");"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('葛\u{e0100}城'),('葛根');"),
+                [],
+            )
+            .unwrap();
+        }
+        // 去掉异体字选择符后，异体字形和基本字符互相匹配
+        for table in ["t1", "t2"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM {table} WHERE text MATCH '\"葛城\"';"),
+            );
+            assert_eq!(["葛\u{e0100}城"], vec.as_slice());
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM {table} WHERE text MATCH '\"葛\u{e0100}\"';"),
+            );
+            assert_eq!(["葛\u{e0100}城", "葛根"], vec.as_slice());
+        }
+        // 保留异体字选择符时，异体字选择符是一个单独的 token
+        let vec = query_texts(&conn, "SELECT * FROM t3 WHERE text MATCH '\"葛城\"';");
+        assert_eq!(0, vec.len());
+        // 异体字选择符并入基本字符的范围
+        let tokens = tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            Vec::new(),
+            TokenizeReason::Document,
+            "葛\u{e0100}城".as_bytes(),
+        )
+        .unwrap();
+        let ranges = tokens.into_iter().map(|t| t.range).collect::<Vec<_>>();
+        assert_eq!(vec![0..7, 7..10], ranges);
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_fold_ascii_keep_original() {
+        let tokens = tokenize_to_vec::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["fold_ascii_keep_original".to_owned()],
+            TokenizeReason::Document,
+            "naïve sqlite".as_bytes(),
+        )
+        .unwrap();
+        // 折叠后的单词和原本的单词写入同一个位置
+        assert_eq!(
+            vec![
+                (b"naiv".to_vec(), 0..6, false),
+                ("naïve".as_bytes().to_vec(), 0..6, true),
+                (b"sqlite".to_vec(), 7..13, false),
+            ],
+            tokens
+        );
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple fold_ascii_keep_original');",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO t1(text) VALUES ('naïve'),('sqlite');", [])
+            .unwrap();
+        for query in ["naive", "naïve", "NAÏVE"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t1 WHERE text MATCH '{query}';"),
+            );
+            assert_eq!(["naïve"], vec.as_slice(), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_keep_diacritics() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [
+            ("t1", "simple 'normalization=none'"),
+            ("t2", "simple 'normalization=none' keep_diacritics"),
+            ("t3", "simple keep_diacritics"),
+        ] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = \"{tokenize}\");"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES (?1),(?2);"),
+                ["re\u{301}sume\u{301}", "résumé"],
+            )
+            .unwrap();
+        }
+        // 默认去掉分解形式中的附加符号，可以匹配不带重音的单词
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'resume';");
+        assert_eq!(["re\u{301}sume\u{301}"], vec.as_slice());
+        // 保留附加符号时，不带重音的单词不能匹配
+        for table in ["t2", "t3"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM {table} WHERE text MATCH 'resume';"),
+            );
+            assert_eq!(0, vec.len(), "{table}");
+        }
+        let vec = query_texts(&conn, "SELECT * FROM t3 WHERE text MATCH 'résumé';");
+        assert_eq!(["re\u{301}sume\u{301}", "résumé"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_casefold() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [("t1", "simple"), ("t2", "simple casefold")] {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('Die Straße ist lang'),('quokka');"),
+                [],
+            )
+            .unwrap();
+        }
+        // 小写转换不改变 ß，不能匹配 strasse
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'strasse';");
+        assert_eq!(0, vec.len());
+        for query in ["strasse", "STRASSE", "Straße"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t2 WHERE text MATCH '{query}';"),
+            );
+            assert_eq!(["Die Straße ist lang"], vec.as_slice(), "{query}");
+        }
+        // 折叠改变了单词的长度，高亮的范围仍然是原始文本中的单词
+        let vec = query_texts(
+            &conn,
+            "SELECT highlight(t2, 0, '[', ']') FROM t2 WHERE text MATCH 'strasse';",
+        );
+        assert_eq!(["Die [Straße] ist lang"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_kana() {
+        let tokenize = |args: Vec<String>, text: &str| {
+            tokenize_to_vec::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(token, range, _)| (String::from_utf8(token).unwrap(), range))
+            .collect::<Vec<_>>()
+        };
+        // 片假名和相邻的汉字、拉丁字母分开，整个片假名单词是一个 token
+        assert_eq!(
+            [("guo", 0..3), ("コンピューター", 3..24), ("sqlite", 24..30)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(vec![], "国コンピューターSQLite").as_slice()
+        );
+        assert_eq!(
+            [("ｺﾝﾋﾟｭｰﾀｰ", 0..24)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(vec!["normalization=nfc".to_owned()], "ｺﾝﾋﾟｭｰﾀｰ").as_slice()
+        );
+        assert_eq!(
+            [("コンピューター", 0..24)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(
+                vec!["normalization=nfc".to_owned(), "fullwidth_kana".to_owned()],
+                "ｺﾝﾋﾟｭｰﾀｰ"
+            )
+            .as_slice()
+        );
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_kana() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = \"simple 'normalization=nfc' fullwidth_kana\");",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('新しいコンピューターを買った'),('quokka');",
+            [],
+        )
+        .unwrap();
+        // 全角和半角的片假名都能匹配整个单词
+        for query in ["コンピューター", "ｺﾝﾋﾟｭｰﾀｰ"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t1 WHERE text MATCH '{query}';"),
+            );
+            assert_eq!(["新しいコンピューターを買った"], vec.as_slice(), "{query}");
+        }
+        // 片假名单词不会被逐字切分，单词的一部分不能匹配
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'コン';");
+        assert_eq!(0, vec.len());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_mixed_scripts() {
+        let tokenize = |args: Vec<String>, text: &str| {
+            tokenize_with_positions::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|t| (String::from_utf8(t.token).unwrap(), t.range))
+            .collect::<Vec<_>>()
+        };
+        // 汉字转换成拼音，拉丁字母按照单词处理
+        assert_eq!(
+            vec![
+                ("a".to_owned(), 0..1),
+                ("guo".to_owned(), 1..4),
+                ("b".to_owned(), 4..5),
+                ("wen".to_owned(), 5..8),
+                ("c".to_owned(), 8..9),
+            ],
+            tokenize(vec!["disable_stopword".to_owned()], "a国b文c")
+        );
+        // 关闭拼音后，汉字和拉丁字母仍然分开处理，拉丁字母单词做词干提取
+        assert_eq!(
+            vec![
+                ("learn".to_owned(), 0..8),
+                ("国".to_owned(), 8..11),
+                ("jump".to_owned(), 11..18),
+            ],
+            tokenize(vec!["disable_pinyin".to_owned()], "learning国jumping")
+        );
+        // 兼容汉字归一化后再转换成拼音
+        assert_eq!(
+            vec![("jin".to_owned(), 0..3)],
+            tokenize(Vec::new(), "\u{f90a}")
+        );
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_identifier_split() {
+        let tokenize = |args: &[&str], text: &str| {
+            tokenize_to_vec::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                args.iter().map(|arg| (*arg).to_owned()).collect(),
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(token, range, colocated)| (String::from_utf8(token).unwrap(), range, colocated))
+            .collect::<Vec<_>>()
+        };
+        let expected = |tokens: &[(&str, std::ops::Range<usize>, bool)]| {
+            tokens
+                .iter()
+                .map(|(token, range, colocated)| ((*token).to_owned(), range.clone(), *colocated))
+                .collect::<Vec<_>>()
+        };
+        let args = ["disable_stopword", "identifier_split"];
+        assert_eq!(
+            expected(&[
+                ("get", 0..3, false),
+                ("user", 3..7, false),
+                ("name", 7..11, false)
+            ]),
+            tokenize(&args, "getUserName")
+        );
+        // 连续的大写字母是一个缩写
+        assert_eq!(
+            expected(&[("http", 0..4, false), ("server", 4..10, false)]),
+            tokenize(&args, "HTTPServer")
+        );
+        assert_eq!(
+            expected(&[
+                ("snake", 0..5, false),
+                ("case", 6..10, false),
+                ("name", 11..15, false)
+            ]),
+            tokenize(&args, "snake_case_name")
+        );
+        // 开启 keep_original 时整个标识符作为 colocated token 写入
+        assert_eq!(
+            expected(&[
+                ("get", 0..3, false),
+                ("user", 3..7, false),
+                ("name", 7..11, false),
+                ("getusername", 0..11, true)
+            ]),
+            tokenize(
+                &["disable_stopword", "identifier_split", "keep_original"],
+                "getUserName"
+            )
+        );
+        // 子词单独做停词过滤和词干提取
+        assert_eq!(
+            expected(&[("user", 3..7, false), ("server", 7..14, false)]),
+            tokenize(&["identifier_split"], "getUserServers")
+        );
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple identifier_split');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('getUserName'),('get_user_name'),('username');",
+            [],
+        )
+        .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'user';");
+        assert_eq!(["getUserName", "get_user_name"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_alnum_split() {
+        let tokenize = |args: &[&str], text: &str| {
+            tokenize_to_vec::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                args.iter().map(|arg| (*arg).to_owned()).collect(),
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(token, range, colocated)| (String::from_utf8(token).unwrap(), range, colocated))
+            .collect::<Vec<_>>()
+        };
+        let expected = |tokens: &[(&str, std::ops::Range<usize>, bool)]| {
+            tokens
+                .iter()
+                .map(|(token, range, colocated)| ((*token).to_owned(), range.clone(), *colocated))
+                .collect::<Vec<_>>()
+        };
+        let args = ["disable_stopword", "alnum_split"];
+        assert_eq!(
+            expected(&[("abc", 0..3, false), ("123", 3..6, false)]),
+            tokenize(&args, "ABC123")
+        );
+        assert_eq!(
+            expected(&[("3", 0..1, false), ("d", 1..2, false)]),
+            tokenize(&args, "3D")
+        );
+        assert_eq!(
+            expected(&[
+                ("version", 0..7, false),
+                ("2", 7..8, false),
+                ("beta", 8..12, false)
+            ]),
+            tokenize(&args, "version2beta")
+        );
+        // 开启 keep_original 时整个单词作为 colocated token 写入
+        assert_eq!(
+            expected(&[
+                ("abc", 0..3, false),
+                ("123", 3..6, false),
+                ("abc123", 0..6, true)
+            ]),
+            tokenize(
+                &["disable_stopword", "alnum_split", "keep_original"],
+                "ABC123"
+            )
+        );
+        // 不切分时整个单词是一个 token
+        assert_eq!(
+            expected(&[("abc123", 0..6, false)]),
+            tokenize(&["disable_stopword"], "ABC123")
+        );
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple alnum_split');",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO t1(text) VALUES ('part ABC123'),('part ABC124'),('sqlite');",
+            [],
+        )
+        .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'abc';");
+        assert_eq!(["part ABC123", "part ABC124"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '123';");
+        assert_eq!(["part ABC123"], vec.as_slice());
+        // 查询完整的单词时，切分后的部分作为短语匹配
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'ABC124';");
+        assert_eq!(["part ABC124"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_astral_han() {
+        // 扩展 B 区的汉字占 4 个字节，和基本区的汉字一样写入拼音
+        let tokens = tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            Vec::new(),
+            TokenizeReason::Document,
+            "\u{20001}国".as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|t| (String::from_utf8(t.token).unwrap(), t.range))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            vec![("qi".to_owned(), 0..4), ("guo".to_owned(), 4..7)],
+            tokens
+        );
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_stopword_after_stem() {
+        let tokenize = |args: &[&str]| {
+            tokenize_to_vec::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                args.iter().map(|arg| (*arg).to_owned()).collect(),
+                TokenizeReason::Document,
+                "running does".as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(token, _, _)| String::from_utf8(token).unwrap())
+            .collect::<Vec<_>>()
+        };
+        // 默认在词干提取之前检查停词
+        assert_eq!(["run"], tokenize(&[]).as_slice());
+        assert_eq!(["doe"], tokenize(&["stopword_after_stem"]).as_slice());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_pinyin_primary() {
+        let tokenize = |args: &[&str]| {
+            tokenize_to_vec::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                args.iter().map(|arg| (*arg).to_owned()).collect(),
+                TokenizeReason::Document,
+                "行".as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(token, _, colocated)| (String::from_utf8(token).unwrap(), colocated))
+            .collect::<Vec<_>>()
+        };
+        let expected = |tokens: &[(&str, bool)]| {
+            tokens
+                .iter()
+                .map(|(token, colocated)| ((*token).to_owned(), *colocated))
+                .collect::<Vec<_>>()
+        };
+        // 默认按照字母顺序写入
+        assert_eq!(
+            expected(&[("hang", false), ("heng", true), ("xing", true)]),
+            tokenize(&[])
+        );
+        // 最常用的读音 xing 作为普通的 token 写入，其余读音作为 colocated token 写入
+        assert_eq!(
+            expected(&[("xing", false), ("hang", true), ("heng", true)]),
+            tokenize(&["pinyin_primary=common"])
+        );
+        assert_eq!(tokenize(&[]), tokenize(&["pinyin_primary=sorted"]));
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = \"simple 'pinyin_primary=common'\");",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO t1(text) VALUES ('行'),('quokka');", [])
+            .unwrap();
+        for query in ["xing", "hang", "行"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t1 WHERE text MATCH '{query}';"),
+            );
+            assert_eq!(["行"], vec.as_slice(), "{query}");
+        }
+        let result = conn.execute(
+            "CREATE VIRTUAL TABLE t2 USING fts5(text, tokenize = \"simple 'pinyin_primary=rare'\");",
+            [],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_spaced_pinyin() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [("t1", "simple"), ("t2", "simple full_pinyin")] {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(text) VALUES ('国家'),('家国'),('快乐');"),
+                [],
+            )
+            .unwrap();
+            // 空格分隔的拼音切分成逐字的拼音，每个音节都要匹配，不要求顺序
+            for query in ["guo jia", "guo  jia", "GUO JIA"] {
+                let vec = query_texts(
+                    &conn,
+                    &format!("SELECT * FROM {table} WHERE text MATCH '{query}';"),
+                );
+                assert_eq!(["国家", "家国"], vec.as_slice(), "{table} {query}");
+            }
+            // 逐字的拼音写入相邻的位置，短语查询要求音节的顺序相同
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM {table} WHERE text MATCH '\"guo jia\"';"),
+            );
+            assert_eq!(["国家"], vec.as_slice(), "{table}");
+        }
+        // 只有开启 full_pinyin 时才能使用不带空格的全拼查询
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'guojia';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'guojia';");
+        assert_eq!(["国家"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_traditional_han() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple full_pinyin');",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO t1(text) VALUES ('國家'),('国家');", [])
+            .unwrap();
+        // 拼音表同样包含繁体字的读音，繁体字和简体字写入相同的拼音和拼接的全拼
+        for query in ["guojia", "guo", "国家", "國家"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t1 WHERE text MATCH '{query}';"),
+            );
+            assert_eq!(["國家", "国家"], vec.as_slice(), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_register_simple_tokenizer_with_mixed_scripts() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = 'simple');",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO t1(text) VALUES ('stars全部都是eye');", [])
+            .unwrap();
+        // 拉丁字母和汉字之间没有空白时，同样按照书写系统切分
+        for query in ["stars", "eye", "全部", "quan"] {
+            let vec = query_texts(
+                &conn,
+                &format!("SELECT * FROM t1 WHERE text MATCH '{query}';"),
+            );
+            assert_eq!(["stars全部都是eye"], vec.as_slice(), "{query}");
+        }
+        let vec = query_texts(
+            &conn,
+            "SELECT highlight(t1, 0, '[', ']') FROM t1 WHERE text MATCH 'stars OR eye';",
+        );
+        assert_eq!(["[stars]全部都是[eye]"], vec.as_slice());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_raw_mode() {
+        let tokens = tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["raw_mode".to_owned(), "shingle=3".to_owned()],
+            TokenizeReason::Document,
+            "The quick (\"brown\") fox can't jump! 国家".as_bytes(),
+        )
+        .unwrap();
+        let tokens = tokens
+            .into_iter()
+            .map(|t| String::from_utf8(t.token).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ["The", "quick", "brown", "fox", "can't", "jump", "国", "家"],
+            tokens.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_symbols() {
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let text = format!("sqlite 👍, {family}!");
+        let tokenize = |args: Vec<String>| {
+            tokenize_to_vec::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(token, range, _)| (String::from_utf8(token).unwrap(), range))
+            .collect::<Vec<_>>()
+        };
+        // 默认和标点一样不写入
+        assert_eq!(
+            [("sqlite".to_owned(), 0..6)].as_slice(),
+            tokenize(vec![]).as_slice()
+        );
+        assert_eq!(
+            tokenize(vec![]).as_slice(),
+            tokenize(vec!["symbols=drop".to_owned()]).as_slice()
+        );
+        // 零宽连接符组合的 emoji 序列是一个 token，范围覆盖整个序列
+        assert_eq!(
+            [
+                ("sqlite".to_owned(), 0..6),
+                ("👍".to_owned(), 7..11),
+                (family.to_owned(), 13..31)
+            ]
+            .as_slice(),
+            tokenize(vec!["symbols=keep".to_owned()]).as_slice()
+        );
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = \"simple 'symbols=keep'\");",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            &format!(
+                "INSERT INTO t1(text) VALUES ('国家👍'),('{family}'),('{}');",
+                "\u{1f468}"
+            ),
+            [],
+        )
+        .unwrap();
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '👍';");
+        assert_eq!(["国家👍"], vec.as_slice());
+        // 序列和序列中的单个 emoji 是不同的 token
+        let vec = query_texts(
+            &conn,
+            &format!("SELECT * FROM t1 WHERE text MATCH '{family}';"),
+        );
+        assert_eq!([family], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH '\u{1f468}';");
+        assert_eq!(["\u{1f468}"], vec.as_slice());
+        let vec = query_texts(
+            &conn,
+            "SELECT highlight(t1, 0, '[', ']') FROM t1 WHERE text MATCH '👍';",
+        );
+        assert_eq!(["国家[👍]"], vec.as_slice());
+        let result = conn.execute(
+            "CREATE VIRTUAL TABLE t2 USING fts5(text, tokenize = \"simple 'symbols=all'\");",
+            [],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_raw() {
+        let tokenize = |args: &[&str], text: &str| {
+            tokenize_to_vec::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                args.iter().map(|arg| (*arg).to_owned()).collect(),
+                TokenizeReason::Document,
+                text.as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(token, range, _)| (String::from_utf8(token).unwrap(), range))
+            .collect::<Vec<_>>()
+        };
+        // 默认的分词去掉了符号，C++ 和 C# 都写入 c
+        assert_eq!(
+            [("c", 0..1), ("c", 4..5)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(&["disable_stopword"], "C++ C#").as_slice()
+        );
+        assert_eq!(
+            [("C++", 0..3), ("C#", 4..6), ("Résumé", 8..16)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(&["raw_mode=whitespace"], "C++ C#\t Résumé").as_slice()
+        );
+        assert_eq!(
+            [("c++", 0..3), ("c#", 4..6)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(&["raw_mode=whitespace,lowercase"], "C++ C#").as_slice()
+        );
+
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        for (table, tokenize) in [
+            ("t1", "simple disable_stopword"),
+            ("t2", "simple 'raw_mode=whitespace,lowercase'"),
+        ] {
+            conn.execute(
+                &format!(
+                    "CREATE VIRTUAL TABLE {table} USING fts5(tag, tokenize = \"{tokenize}\");"
+                ),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!("INSERT INTO {table}(tag) VALUES ('C++'),('C#');"),
+                [],
+            )
+            .unwrap();
+        }
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE tag MATCH '\"C++\"';");
+        assert_eq!(["C++", "C#"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE tag MATCH '\"C++\"';");
+        assert_eq!(["C++"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE tag MATCH '\"c#\"';");
+        assert_eq!(["C#"], vec.as_slice());
+        // 无法识别的切分方式，以及同时指定多个切分方式
+        for tokenize in [
+            "simple 'raw_mode=upper'",
+            "simple raw_mode 'raw_mode=whitespace'",
+        ] {
+            let result = conn.execute(
+                &format!("CREATE VIRTUAL TABLE t3 USING fts5(tag, tokenize = \"{tokenize}\");"),
+                [],
+            );
+            assert!(result.is_err(), "{tokenize}");
+        }
+    }
+
+    #[test]
+    fn test_simple_tokenizer_with_invalid_shingle() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<SimpleTokenizer>(&conn, SimpleGlobal::default()).unwrap();
+        let result = conn.execute(
+            "CREATE VIRTUAL TABLE t1 USING fts5(text, tokenize = \"simple 'shingle=0'\");",
+            [],
+        );
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_tokenize_by_unicode_word_indices() {
         let text = "The quick (\"brown\") fox can't jump 32.3 feet, right? 我将点燃星海！天上的stars全部都是 eye，不要凝视";
@@ -195,4 +2878,76 @@ mod tests {
         ];
         assert_eq!(&uwi1[..], b);
     }
+    #[test]
+    fn test_simple_tokenizer_borrowed_tokens() {
+        // 逐字的拼音借用静态的拼音表，词干提取没有改变单词时借用 word_buf，写入的 token 不受影响
+        let tokenize = |args: Vec<String>| {
+            tokenize_to_vec::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                args,
+                TokenizeReason::Document,
+                "重庆 running SQLite 的数据库".as_bytes(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(token, range, colocated)| (String::from_utf8(token).unwrap(), range, colocated))
+            .collect::<Vec<_>>()
+        };
+        let expected = |tokens: &[(&str, Range<usize>, bool)]| {
+            tokens
+                .iter()
+                .map(|(token, range, colocated)| (token.to_string(), range.clone(), *colocated))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            expected(&[
+                ("chong", 0..3, false),
+                ("tong", 0..3, true),
+                ("zhong", 0..3, true),
+                ("qing", 3..6, false),
+                ("run", 7..14, false),
+                ("sqlite", 15..21, false),
+                ("shu", 25..28, false),
+                ("shuo", 25..28, true),
+                ("ku", 31..34, false),
+            ]),
+            tokenize(vec![])
+        );
+        assert_eq!(
+            expected(&[
+                ("chong", 0..3, false),
+                ("tong", 0..3, true),
+                ("zhong", 0..3, true),
+                ("qing", 3..6, false),
+                ("chongqing", 0..6, true),
+                ("tongqing", 0..6, true),
+                ("zhongqing", 0..6, true),
+                ("run", 7..14, false),
+                ("sqlite", 15..21, false),
+                ("shu", 25..28, false),
+                ("shuo", 25..28, true),
+                ("ku", 31..34, false),
+                ("deshujuku", 22..34, true),
+                ("deshuojuku", 22..34, true),
+                ("dishujuku", 22..34, true),
+                ("dishuojuku", 22..34, true),
+            ]),
+            tokenize(vec!["full_pinyin".to_owned()])
+        );
+        assert_eq!(
+            expected(&[
+                ("cong", 0..3, false),
+                ("tong", 0..3, true),
+                ("zong", 0..3, true),
+                ("qin", 3..6, false),
+                ("run", 7..14, false),
+                ("running", 7..14, true),
+                ("sqlite", 15..21, false),
+                ("su", 25..28, false),
+                ("suo", 25..28, true),
+                ("ku", 31..34, false),
+            ]),
+            tokenize(vec!["fuzzy_pinyin".to_owned(), "keep_original".to_owned()])
+        );
+    }
 }
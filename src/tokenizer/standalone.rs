@@ -0,0 +1,477 @@
+//! 不需要注册到 FTS5，直接使用 Tokenizer 进行分词的辅助方法
+
+use crate::tokenizer::utils::{LossyText, script_runs};
+use crate::tokenizer::{TokenizeReason, Tokenizer};
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// 带有位置信息的 token
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PositionedToken {
+    /// token 的内容
+    pub token: Vec<u8>,
+    /// token 在原始文本中的字节范围
+    pub range: Range<usize>,
+    /// 对应 `FTS5_TOKEN_COLOCATED`
+    pub colocated: bool,
+    /// token 的位置，与 FTS5 的位置模型一致
+    ///
+    /// 非 colocated 的 token 位置依次递增，colocated 的 token 与前一个 token 共用同一个位置
+    pub position: usize,
+}
+
+/// 依次是 token 的内容、在原始文本中的字节范围、是否是 colocated token
+pub type RawToken = (Vec<u8>, Range<usize>, bool);
+
+/// 使用指定参数创建 Tokenizer，对文本分词，按照写入 FTS5 的顺序返回全部 token
+///
+/// 用于调试和测试，查看写入索引的 token
+pub fn tokenize_to_vec<T: Tokenizer>(
+    global: &T::Global,
+    args: Vec<String>,
+    reason: TokenizeReason,
+    text: &[u8],
+) -> Result<Vec<RawToken>, rusqlite::Error> {
+    let mut tokenizer = T::new(global, args)?;
+    let mut tokens = Vec::new();
+    tokenizer.tokenize(reason, text, |token, range, colocated| {
+        tokens.push((token.to_vec(), range, colocated));
+        Ok(())
+    })?;
+    Ok(tokens)
+}
+
+/// 使用指定参数创建 Tokenizer，对文本分词，并且为每个 token 计算位置
+pub fn tokenize_with_positions<T: Tokenizer>(
+    global: &T::Global,
+    args: Vec<String>,
+    reason: TokenizeReason,
+    text: &[u8],
+) -> Result<Vec<PositionedToken>, rusqlite::Error> {
+    let mut tokens = Vec::<PositionedToken>::new();
+    for (token, range, colocated) in tokenize_to_vec::<T>(global, args, reason, text)? {
+        let position = match tokens.last() {
+            // 第一个 token 总是位于 0，即使它被标记为 colocated
+            None => 0,
+            Some(last) if colocated => last.position,
+            Some(last) => last.position + 1,
+        };
+        tokens.push(PositionedToken {
+            token,
+            range,
+            colocated,
+            position,
+        });
+    }
+    Ok(tokens)
+}
+
+/// 分词结果的统计信息，用于估算索引的规模和调整相关性
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TokenStats {
+    /// 写入的 token 总数，包含 colocated token
+    pub total: usize,
+    /// 不同 token 的数量
+    pub unique: usize,
+    /// colocated token 的数量
+    pub colocated: usize,
+    /// 被停词表过滤的单词数量
+    pub stopwords_filtered: usize,
+}
+
+/// 使用指定参数创建 Tokenizer，对文本分词，统计写入的 token
+///
+/// 被过滤的停词数量来自分词之后的 [`Tokenizer::stopwords_filtered`]，没有实现这个方法的 Tokenizer 总是为 0。
+/// 开启 `max_tokens` 时只统计截断之前的停词
+pub fn tokenize_stats<T: Tokenizer>(
+    global: &T::Global,
+    args: Vec<String>,
+    reason: TokenizeReason,
+    text: &[u8],
+) -> Result<TokenStats, rusqlite::Error> {
+    // 非 colocated 的 token 数量就是占用的位置数量
+    let positions =
+        |tokens: &[RawToken]| tokens.iter().filter(|(_, _, colocated)| !colocated).count();
+    let mut tokenizer = T::new(global, args)?;
+    let mut tokens = Vec::new();
+    tokenizer.tokenize(reason, text, |token, range, colocated| {
+        tokens.push((token.to_vec(), range, colocated));
+        Ok(())
+    })?;
+    let unique = tokens
+        .iter()
+        .map(|(token, _, _)| token.as_slice())
+        .collect::<HashSet<_>>()
+        .len();
+    Ok(TokenStats {
+        total: tokens.len(),
+        unique,
+        colocated: tokens.len() - positions(&tokens),
+        stopwords_filtered: tokenizer.stopwords_filtered(),
+    })
+}
+
+/// 带有位置信息和额外元数据的 token，用于在外部维护 token 和原始文档之间的映射
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenMetadata {
+    /// token 的内容和位置信息
+    pub token: PositionedToken,
+    /// token 的序号，按照写入的顺序从 0 开始递增，colocated 的 token 也会占用一个序号
+    pub ordinal: usize,
+    /// token 所在片段在原始文本中的字节范围
+    ///
+    /// 片段是被空白字符隔开的文本中，属于同一个书写系统的最长连续部分，数字和标点归入相邻的片段
+    pub run: Range<usize>,
+}
+
+/// 与 [`tokenize_with_positions`] 相同，并且为每个 token 额外计算序号和所在片段的范围
+pub fn tokenize_with_metadata<T: Tokenizer>(
+    global: &T::Global,
+    args: Vec<String>,
+    reason: TokenizeReason,
+    text: &[u8],
+) -> Result<Vec<TokenMetadata>, rusqlite::Error> {
+    let tokens = tokenize_with_positions::<T>(global, args, reason, text)?;
+    // 片段在替换了无效字节的字符串中计算，和 token 一样转换成原始字节中的范围
+    let lossy = LossyText::new(text);
+    let runs = text_runs(lossy.as_str())
+        .into_iter()
+        .map(|run| lossy.original_range(run))
+        .collect::<Vec<_>>();
+    // 片段和 token 都按照位置排列，依次向后查找
+    let mut cursor = 0;
+    let metadata = tokens
+        .into_iter()
+        .enumerate()
+        .map(|(ordinal, token)| {
+            let start = token.range.start;
+            if runs.get(cursor).is_some_and(|run| run.start > start) {
+                // 拼接的拼音、jieba 搜索引擎模式的长词语在后面写入，起始位置可能回退，重新查找
+                cursor = runs.partition_point(|run| run.end <= start);
+            }
+            while runs.get(cursor).is_some_and(|run| run.end <= start) {
+                cursor += 1;
+            }
+            // 片段首尾不相接，找到起始位置所在的片段，不在任何片段中时使用 token 自身的范围
+            let run = runs
+                .get(cursor)
+                .filter(|run| run.contains(&start))
+                .map_or_else(
+                    || token.range.clone(),
+                    |run| run.start..run.end.max(token.range.end),
+                );
+            TokenMetadata {
+                token,
+                ordinal,
+                run,
+            }
+        })
+        .collect();
+    Ok(metadata)
+}
+
+/// 将文本按照空白字符切分后，再按照书写系统切分成片段，返回每个片段的字节范围
+fn text_runs(text: &str) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for (index, ch) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (ch.is_whitespace(), start) {
+            (false, None) => start = Some(index),
+            (true, Some(chunk_start)) => {
+                let chunk = &text[chunk_start..index];
+                runs.extend(
+                    script_runs(chunk)
+                        .into_iter()
+                        .map(|(_, run)| chunk_start + run.start..chunk_start + run.end),
+                );
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::TokenizeReason;
+    use crate::tokenizer::jieba_tokenizer::{JiebaGlobal, JiebaTokenizer};
+    use crate::tokenizer::keyword_tokenizer::KeywordTokenizer;
+    use crate::tokenizer::simple_tokenizer::{SimpleGlobal, SimpleTokenizer};
+    use crate::tokenizer::standalone::{
+        TokenStats, tokenize_stats, tokenize_to_vec, tokenize_with_metadata,
+        tokenize_with_positions,
+    };
+    use crate::tokenizer::unicode_tokenizer::UnicodeWordsTokenizer;
+    use rusqlite::ffi::{FTS5_TOKENIZE_PREFIX, FTS5_TOKENIZE_QUERY};
+
+    #[test]
+    fn test_tokenize_to_vec() {
+        let text = "sqlite分词 国家";
+        let tokens = tokenize_to_vec::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec![],
+            TokenizeReason::Document,
+            text.as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|(token, range, colocated)| (String::from_utf8(token).unwrap(), range, colocated))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            [
+                ("sqlite", 0..6, false),
+                ("fen", 6..9, false),
+                ("ci", 9..12, false),
+                ("guo", 13..16, false),
+                ("gu", 16..19, false),
+                ("jia", 16..19, true),
+                ("jie", 16..19, true),
+            ]
+            .map(|(token, range, colocated)| (token.to_owned(), range, colocated))
+            .as_slice(),
+            tokens.as_slice()
+        );
+
+        let tokens = tokenize_to_vec::<JiebaTokenizer>(
+            &JiebaGlobal::default(),
+            vec![],
+            TokenizeReason::Document,
+            text.as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|(token, range, colocated)| (String::from_utf8(token).unwrap(), range, colocated))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            [
+                ("sqlite", 0..6, false),
+                ("分词", 6..12, false),
+                ("国家", 13..19, false),
+            ]
+            .map(|(token, range, colocated)| (token.to_owned(), range, colocated))
+            .as_slice(),
+            tokens.as_slice()
+        );
+        // 参数错误时返回创建 Tokenizer 的错误
+        let result = tokenize_to_vec::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["shingle=0".to_owned()],
+            TokenizeReason::Document,
+            text.as_bytes(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_tokenize_with_fts5_flags() {
+        // 使用 FTS5 传入的 flags 得到分词原因，前缀查询的最后一个单词不检查停词
+        for (flag, expected) in [
+            (FTS5_TOKENIZE_QUERY, ["sqlite"].as_slice()),
+            (
+                FTS5_TOKENIZE_QUERY | FTS5_TOKENIZE_PREFIX,
+                ["sqlite", "the"].as_slice(),
+            ),
+        ] {
+            let reason = TokenizeReason::try_from(flag).unwrap();
+            assert_eq!(flag, reason.to_flag());
+            let tokens = tokenize_to_vec::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                vec![],
+                reason,
+                b"sqlite the",
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(token, _, _)| String::from_utf8(token).unwrap())
+            .collect::<Vec<_>>();
+            assert_eq!(expected, tokens.as_slice(), "{flag}");
+        }
+    }
+
+    #[test]
+    fn test_tokenize_with_positions() {
+        let tokens = tokenize_with_positions::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["shingle=3".to_owned()],
+            TokenizeReason::Document,
+            "sqlite tokenizer 国".as_bytes(),
+        )
+        .unwrap();
+        let positions = tokens
+            .iter()
+            .map(|t| (String::from_utf8(t.token.clone()).unwrap(), t.position))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            [
+                ("sqlite", 0),
+                ("sql", 0),
+                ("qli", 0),
+                ("lit", 0),
+                ("ite", 0),
+                ("token", 1),
+                ("tok", 1),
+                ("oke", 1),
+                ("ken", 1),
+                ("eni", 1),
+                ("niz", 1),
+                ("ize", 1),
+                ("zer", 1),
+                ("guo", 2),
+            ]
+            .map(|(token, position)| (token.to_owned(), position))
+            .as_slice(),
+            positions.as_slice()
+        );
+        // colocated 的 token 共用位置，其余 token 的位置递增
+        for pair in tokens.windows(2) {
+            if pair[1].colocated {
+                assert_eq!(pair[0].position, pair[1].position);
+            } else {
+                assert_eq!(pair[0].position + 1, pair[1].position);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tokenize_stats() {
+        let stats = tokenize_stats::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec![],
+            TokenizeReason::Document,
+            "the sqlite and the 国家 sqlite".as_bytes(),
+        )
+        .unwrap();
+        // the 和 and 是停词，国 的读音 guo，家 的读音 gu、jia、jie
+        assert_eq!(
+            TokenStats {
+                total: 6,
+                unique: 5,
+                colocated: 2,
+                stopwords_filtered: 3,
+            },
+            stats
+        );
+        let stats = tokenize_stats::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["disable_stopword".to_owned()],
+            TokenizeReason::Document,
+            "the sqlite".as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(0, stats.stopwords_filtered);
+        assert_eq!(2, stats.total);
+        // 截断之后的停词不计入
+        let stats = tokenize_stats::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["max_tokens=1".to_owned()],
+            TokenizeReason::Document,
+            "sqlite the quokka the and".as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(1, stats.total);
+        assert_eq!(1, stats.stopwords_filtered);
+        // 去重只影响写入的 token，每个停词都计入
+        let stats = tokenize_stats::<JiebaTokenizer>(
+            &JiebaGlobal::default(),
+            vec!["dedup_document".to_owned()],
+            TokenizeReason::Document,
+            "the sqlite the sqlite".as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(1, stats.total);
+        assert_eq!(2, stats.stopwords_filtered);
+        let stats = tokenize_stats::<UnicodeWordsTokenizer>(
+            &(),
+            vec![],
+            TokenizeReason::Document,
+            "the sqlite and quokka".as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(2, stats.total);
+        assert_eq!(2, stats.stopwords_filtered);
+        // 参数错误时返回错误
+        assert!(
+            tokenize_stats::<SimpleTokenizer>(
+                &SimpleGlobal::default(),
+                vec!["max_tokens=0".to_owned()],
+                TokenizeReason::Document,
+                "the sqlite".as_bytes(),
+            )
+            .is_err()
+        );
+        // 没有统计停词的 Tokenizer
+        let stats = tokenize_stats::<KeywordTokenizer>(
+            &(),
+            vec![],
+            TokenizeReason::Document,
+            "the sqlite".as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(1, stats.total);
+        assert_eq!(0, stats.stopwords_filtered);
+    }
+
+    #[test]
+    fn test_tokenize_with_metadata() {
+        let text = "sqlite分词 tokenizer, 中国";
+        let tokens = tokenize_with_metadata::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec!["full_pinyin".to_owned()],
+            TokenizeReason::Document,
+            text.as_bytes(),
+        )
+        .unwrap();
+        let metadata = tokens
+            .iter()
+            .map(|t| {
+                (
+                    String::from_utf8(t.token.token.clone()).unwrap(),
+                    t.ordinal,
+                    t.token.position,
+                    &text[t.run.clone()],
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("sqlite".to_owned(), 0, 0, "sqlite"),
+                ("fen".to_owned(), 1, 1, "分词"),
+                ("ci".to_owned(), 2, 2, "分词"),
+                ("fenci".to_owned(), 3, 2, "分词"),
+                ("token".to_owned(), 4, 3, "tokenizer,"),
+                ("zhong".to_owned(), 5, 4, "中国"),
+                ("guo".to_owned(), 6, 5, "中国"),
+                ("zhongguo".to_owned(), 7, 5, "中国"),
+            ],
+            metadata
+        );
+        // 无效的字节替换成 3 个字节的 U+FFFD，片段的范围仍然是原始字节中的范围
+        let text = b"quokka \xff\xff \xe5\x9b\xbd\xe5\xae\xb6";
+        let runs = tokenize_with_metadata::<SimpleTokenizer>(
+            &SimpleGlobal::default(),
+            vec![],
+            TokenizeReason::Document,
+            text,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|t| (String::from_utf8(t.token.token).unwrap(), t.run))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            [
+                ("quokka", 0..6),
+                ("guo", 10..16),
+                ("gu", 10..16),
+                ("jia", 10..16),
+                ("jie", 10..16)
+            ]
+            .map(|(token, run)| (token.to_owned(), run))
+            .as_slice(),
+            runs.as_slice()
+        );
+    }
+}
@@ -0,0 +1,361 @@
+use crate::tokenizer::{
+    StemmerLanguage, TokenizeReason, Tokenizer,
+    utils::{
+        ConfigSummary, Ligatures, LossyText, NormalizationForm, StopwordCounter, TokenPipeline,
+        is_punctuation_or_space_str, parse_positive_arg, parse_stemmer_arg, prefix_end,
+        reject_unknown_args,
+    },
+};
+use rusqlite::Error;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::ffi::CStr;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// 只按照 Unicode 单词边界切分的分词器，适用于英语和其他欧洲语言的文本
+///
+/// 不加载 jieba 字典，也不处理拼音，单词使用和 `simple` 相同的 [`TokenPipeline`] 做归一化、停词和词干提取，
+/// 没有全局数据，不支持自定义的 token 转换。
+/// `can't` 这样的缩写作为一个单词处理，排版用的右单引号 `’` 视为 ascii 的撇号
+#[derive(Default)]
+pub struct UnicodeWordsTokenizer {
+    /// 单词的归一化、停词和词干提取
+    pipeline: TokenPipeline,
+    /// 本次分词中被停词表过滤的单词数量
+    stopword_hits: StopwordCounter,
+    /// 本次分词是前缀查询时，最后一个单词的结束位置，这个单词不检查停词
+    prefix_end: Option<usize>,
+}
+
+impl std::fmt::Debug for UnicodeWordsTokenizer {
+    /// 输出 [`Tokenizer::config_summary`] 的配置摘要
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.config_summary())
+    }
+}
+
+impl UnicodeWordsTokenizer {
+    /// 不启用停词表
+    pub fn disable_stopword(&mut self) {
+        self.pipeline.disable_stopword();
+    }
+    /// 使用停词表文件代替默认的停词表，只影响这个分词器
+    ///
+    /// 文件的格式和 `simple` 的 `stopword=` 参数相同，文件无法读取时，记录一条警告日志，继续使用默认的停词表
+    pub fn load_stopwords(&mut self, path: &str) {
+        self.pipeline.load_stopwords(path);
+    }
+    /// 在当前的停词表上添加停词，只影响这个分词器
+    ///
+    /// 不启用停词表时，只使用添加的停词，不再使用默认的停词表
+    pub fn add_stopwords<S: AsRef<str>>(&mut self, words: impl IntoIterator<Item = S>) {
+        self.pipeline.add_stopwords(words);
+    }
+    /// 对词干提取的结果检查停词，默认在词干提取之前检查
+    pub fn enable_stopword_after_stem(&mut self) {
+        self.pipeline.enable_stopword_after_stem();
+    }
+    /// 不做词干提取，只写入小写的单词
+    pub fn disable_stemming(&mut self) {
+        self.pipeline.disable_stemming();
+    }
+    /// 只在写入文档时做词干提取，查询时保持原样
+    pub fn enable_stem_documents_only(&mut self) {
+        self.pipeline.enable_stem_documents_only();
+    }
+    /// 设置词干提取使用的语言，默认使用英语
+    pub fn set_stemmer(&mut self, language: StemmerLanguage) {
+        self.pipeline.set_stemmer(language);
+    }
+    /// 归一化后少于 `min_chars` 个字符的单词不写入
+    pub fn set_min_token_chars(&mut self, min_chars: usize) {
+        self.pipeline.set_min_token_chars(min_chars);
+    }
+    /// 设置 Unicode 归一化的形式，默认为 NFKC
+    pub fn set_normalization(&mut self, form: NormalizationForm) {
+        self.pipeline.set_normalization(form);
+    }
+    /// 设置连字的处理方式，连字在归一化之前处理，默认不单独处理
+    pub fn set_ligatures(&mut self, ligatures: Ligatures) {
+        self.pipeline.set_ligatures(ligatures);
+    }
+    /// 保留组合附加符号，例如分解形式 `e\u{301}` 中的 `\u{301}`，默认去掉
+    ///
+    /// 默认的 NFKC 归一化会把能组合的附加符号合并成 `é` 这样的字符，合并后的字符不受影响，
+    /// 这个选项主要影响 `normalization=none` 时的分解形式，以及没有组合字符的附加符号
+    pub fn enable_keep_diacritics(&mut self) {
+        self.pipeline.enable_keep_diacritics();
+    }
+    /// 使用完整的 Unicode 大小写折叠代替小写转换，默认关闭
+    pub fn enable_casefold(&mut self) {
+        self.pipeline.enable_casefold();
+    }
+    /// 当前生效的停词表，和 `simple` 的 `effective_stopwords` 相同，不启用停词表时为空
+    pub fn effective_stopwords(&self) -> Cow<'_, HashSet<String>> {
+        self.pipeline.effective_stopwords()
+    }
+
+    /// 对文本分词，并将每个 token 交给 `push_token` 处理
+    fn tokenize_words<TKF>(
+        &self,
+        reason: TokenizeReason,
+        text: &str,
+        mut push_token: TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        let mut word_buf = String::new();
+        for (index, word) in text.unicode_word_indices() {
+            if is_punctuation_or_space_str(word) {
+                continue;
+            }
+            let range = index..index + word.len();
+            // 只有包含右单引号的单词需要复制
+            let word = if word.contains('\u{2019}') {
+                Cow::Owned(word.replace('\u{2019}', "'"))
+            } else {
+                Cow::Borrowed(word)
+            };
+            let ascii = self.pipeline.normalize(&word, &mut word_buf);
+            if word_buf.is_empty() || self.pipeline.is_too_short(&word_buf) {
+                continue;
+            }
+            let need_stem = self.pipeline.need_stem(&reason, ascii, &word_buf);
+            // 前缀查询的最后一个单词不完整，不检查停词
+            if self.prefix_end != Some(range.end)
+                && self
+                    .stopword_hits
+                    .record(self.pipeline.is_stemmed_stopword(&word_buf, need_stem))
+            {
+                continue;
+            }
+            if need_stem {
+                // 词干提取没有改变单词时借用 word_buf，不需要分配内存
+                let stemmed = self.pipeline.stem(&word_buf);
+                (push_token)(stemmed.as_bytes(), range, false)?;
+            } else {
+                (push_token)(word_buf.as_bytes(), range, false)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Tokenizer for UnicodeWordsTokenizer {
+    type Global = ();
+
+    fn name() -> &'static CStr {
+        c"unicode"
+    }
+
+    /// 总是包含停词和词干提取的开关，其余参数只在和默认值不同时出现
+    fn config_summary(&self) -> String {
+        let mut summary = ConfigSummary::new(Self::name());
+        self.pipeline.summarize(&mut summary);
+        summary.finish()
+    }
+
+    fn new(_global: &Self::Global, args: Vec<String>) -> Result<Self, Error> {
+        let mut tokenizer = Self::default();
+        // 全局停词表在创建时获取一次，之后替换不影响这个实例
+        tokenizer.pipeline.refresh_stopwords();
+        let mut stopword_add = Vec::new();
+        let mut unknown = Vec::new();
+        for arg in args {
+            match arg.split_once('=') {
+                Some(("stopword", path)) => {
+                    tokenizer.load_stopwords(path);
+                }
+                Some(("stopword_add", word)) => {
+                    stopword_add.push(word.to_owned());
+                }
+                Some(("stemmer", language)) => {
+                    tokenizer.set_stemmer(parse_stemmer_arg(language)?);
+                }
+                Some(("min_token_chars", n)) => {
+                    tokenizer.set_min_token_chars(parse_positive_arg("min_token_chars", n)?);
+                }
+                Some(("normalization", form)) => {
+                    tokenizer.set_normalization(NormalizationForm::from_arg(form)?);
+                }
+                Some(("ligatures", ligatures)) => {
+                    tokenizer.set_ligatures(Ligatures::from_arg(ligatures)?);
+                }
+                _ => match arg.as_str() {
+                    "disable_stopword" => {
+                        tokenizer.disable_stopword();
+                    }
+                    "disable_stemming" => {
+                        tokenizer.disable_stemming();
+                    }
+                    "keep_diacritics" => {
+                        tokenizer.enable_keep_diacritics();
+                    }
+                    "casefold" => {
+                        tokenizer.enable_casefold();
+                    }
+                    "stem_documents_only" => {
+                        tokenizer.enable_stem_documents_only();
+                    }
+                    "stopword_after_stem" => {
+                        tokenizer.enable_stopword_after_stem();
+                    }
+                    _ => unknown.push(arg.clone()),
+                },
+            }
+        }
+        reject_unknown_args("unicode", &unknown)?;
+        if !stopword_add.is_empty() {
+            // 所有参数处理完之后再合并，结果和参数的顺序无关
+            tokenizer.add_stopwords(stopword_add);
+        }
+        Ok(tokenizer)
+    }
+
+    fn tokenize<TKF>(
+        &mut self,
+        reason: TokenizeReason,
+        text: &[u8],
+        mut push_token: TKF,
+    ) -> Result<(), Error>
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), Error>,
+    {
+        // 分词得到的是转换后字符串中的位置，写入之前转换成原始字节中的位置
+        let text = LossyText::new(text);
+        self.prefix_end = prefix_end(&reason, text.as_str());
+        self.stopword_hits.reset();
+        self.tokenize_words(reason, text.as_str(), |token, range, colocated| {
+            (push_token)(token, text.original_range(range), colocated)
+        })
+    }
+
+    fn stopwords_filtered(&self) -> usize {
+        self.stopword_hits.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::standalone::tokenize_to_vec;
+    use crate::tokenizer::tests::query_texts;
+    use crate::tokenizer::unicode_tokenizer::UnicodeWordsTokenizer;
+    use crate::tokenizer::{TokenizeReason, Tokenizer, register_tokenizer};
+    use rusqlite::Connection;
+
+    fn tokenize(args: Vec<String>, text: &str) -> Vec<(String, std::ops::Range<usize>)> {
+        tokenize_to_vec::<UnicodeWordsTokenizer>(
+            &(),
+            args,
+            TokenizeReason::Document,
+            text.as_bytes(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|(token, range, _)| (String::from_utf8(token).unwrap(), range))
+        .collect()
+    }
+
+    #[test]
+    fn test_unicode_tokenizer() {
+        // 缩写是一个单词，can't 和 it's 是停词
+        assert_eq!(
+            [("imagin", 8..15), ("sqlite", 23..29), ("databas", 30..39)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(vec![], "I can't imagine it’s SQLite databases!").as_slice()
+        );
+        // 不启用停词表时，缩写作为一个 token 写入，右单引号转换成撇号
+        assert_eq!(
+            [
+                ("i", 0..1),
+                ("can't", 2..7),
+                ("imagin", 8..15),
+                ("it", 16..22),
+                ("sqlite", 23..29),
+            ]
+            .map(|(token, range)| (token.to_owned(), range))
+            .as_slice(),
+            tokenize(
+                vec!["disable_stopword".to_owned()],
+                "I can't imagine it’s SQLite"
+            )
+            .as_slice()
+        );
+        assert_eq!(
+            [("running", 0..7)]
+                .map(|(token, range)| (token.to_owned(), range))
+                .as_slice(),
+            tokenize(vec!["disable_stemming".to_owned()], "Running").as_slice()
+        );
+    }
+
+    #[test]
+    fn test_unicode_tokenizer_with_pipeline_args() {
+        let args = |args: &[&str]| args.iter().map(|arg| (*arg).to_owned()).collect::<Vec<_>>();
+        let tokens = |args: Vec<String>, text: &str| {
+            tokenize(args, text)
+                .into_iter()
+                .map(|(token, _)| token)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ["strasse"],
+            tokens(args(&["casefold", "disable_stemming"]), "Straße").as_slice()
+        );
+        assert_eq!(
+            ["can't"],
+            tokens(
+                args(&["min_token_chars=3", "disable_stopword"]),
+                "I can't do it"
+            )
+            .as_slice()
+        );
+        assert!(tokens(args(&[]), "does").is_empty());
+        assert_eq!(
+            ["doe"],
+            tokens(args(&["stopword_after_stem"]), "does").as_slice()
+        );
+        // 查询时不做词干提取
+        let query = tokenize_to_vec::<UnicodeWordsTokenizer>(
+            &(),
+            args(&["stem_documents_only"]),
+            TokenizeReason::Query { prefix: false },
+            b"Running",
+        )
+        .unwrap();
+        assert_eq!(b"running", query[0].0.as_slice());
+        assert!(UnicodeWordsTokenizer::new(&(), args(&["min_token_chars=0"])).is_err());
+    }
+
+    #[test]
+    fn test_register_unicode_tokenizer() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_tokenizer::<UnicodeWordsTokenizer>(&conn, ()).unwrap();
+        for (table, tokenize) in [("t1", "unicode"), ("t2", "unicode disable_stopword")] {
+            conn.execute(
+                &format!("CREATE VIRTUAL TABLE {table} USING fts5(text, tokenize = '{tokenize}');"),
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                &format!(
+                    "INSERT INTO {table}(text) VALUES ('I can''t imagine learning'),('the tokenizer');"
+                ),
+                [],
+            )
+            .unwrap();
+        }
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'learn';");
+        assert_eq!(["I can't imagine learning"], vec.as_slice());
+        // 停词被过滤
+        let vec = query_texts(&conn, "SELECT * FROM t1 WHERE text MATCH 'the';");
+        assert_eq!(0, vec.len());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH 'the';");
+        assert_eq!(["the tokenizer"], vec.as_slice());
+        let vec = query_texts(&conn, "SELECT * FROM t2 WHERE text MATCH '\"can''t\"';");
+        assert_eq!(["I can't imagine learning"], vec.as_slice());
+    }
+}
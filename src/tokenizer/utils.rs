@@ -1,10 +1,353 @@
+use crate::STOPWORD;
 use crate::pinyin::has_pinyin;
-use std::sync::LazyLock;
+use crate::tokenizer::TokenizeReason;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::ffi::CStr;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
 use unicode_normalization::UnicodeNormalization;
-use waken_snowball::{Algorithm, Stemmer};
+use unicode_segmentation::{GraphemeCursor, UnicodeSegmentation};
+use waken_snowball::Algorithm;
 
-/// 适用于英语的词干提取器
-pub(super) static EN_STEMMER: LazyLock<Stemmer> = LazyLock::new(|| Algorithm::English.stemmer());
+/// 将 FTS5 传入的字节转换成字符串，无效的 UTF-8 字节被替换成 U+FFFD
+///
+/// 替换会改变字节的长度，分词时得到的是字符串中的位置，需要通过 [`LossyText::original_range`]
+/// 转换成原始字节中的位置，再交给 FTS5
+pub(super) struct LossyText<'a> {
+    text: Cow<'a, str>,
+    /// 字符串中每个字节位置对应的原始字节位置，文本是有效的 UTF-8 时为 `None`，位置不变
+    offsets: Option<Vec<usize>>,
+}
+
+impl<'a> LossyText<'a> {
+    pub(super) fn new(bytes: &'a [u8]) -> Self {
+        Self::replace_invalid(bytes, char::REPLACEMENT_CHARACTER)
+    }
+
+    /// 按照设置的方式处理无效的 UTF-8 字节，[`InvalidUtf8Policy::Error`] 时返回 [`rusqlite::Error::Utf8Error`]
+    pub(super) fn with_policy(
+        bytes: &'a [u8],
+        policy: InvalidUtf8Policy,
+    ) -> Result<Self, rusqlite::Error> {
+        match policy {
+            InvalidUtf8Policy::Lossy => Ok(Self::new(bytes)),
+            // 无效的字节替换成空白，作为单词之间的分隔，不写入任何 token
+            InvalidUtf8Policy::Skip => Ok(Self::replace_invalid(bytes, ' ')),
+            InvalidUtf8Policy::Error => {
+                let text = std::str::from_utf8(bytes)?;
+                Ok(Self {
+                    text: Cow::Borrowed(text),
+                    offsets: None,
+                })
+            }
+        }
+    }
+
+    /// 将每个无效的字节序列替换成一个 `replacement` 字符
+    fn replace_invalid(bytes: &'a [u8], replacement: char) -> Self {
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            return Self {
+                text: Cow::Borrowed(text),
+                offsets: None,
+            };
+        }
+        let mut text = String::with_capacity(bytes.len());
+        let mut offsets = Vec::with_capacity(bytes.len() + 1);
+        let mut original = 0;
+        for chunk in bytes.utf8_chunks() {
+            text.push_str(chunk.valid());
+            offsets.extend(original..original + chunk.valid().len());
+            original += chunk.valid().len();
+            if !chunk.invalid().is_empty() {
+                // 替换字符的每个字节都对应无效字节的起始位置
+                text.push(replacement);
+                offsets.extend(std::iter::repeat_n(original, replacement.len_utf8()));
+                original += chunk.invalid().len();
+            }
+        }
+        offsets.push(original);
+        Self {
+            text: Cow::Owned(text),
+            offsets: Some(offsets),
+        }
+    }
+
+    pub(super) fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// 将字符串中的字节范围转换成原始字节中的范围
+    ///
+    /// 范围先扩展到字素簇的边界，例如去掉附加符号后的 `e` 对应完整的 `e\u{301}`，
+    /// FTS5 的 `highlight` 和 `snippet` 不会切断基本字符和附加符号组成的字素簇
+    pub(super) fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        let range = grapheme_range(&self.text, range);
+        match &self.offsets {
+            Some(offsets) => offsets[range.start]..offsets[range.end],
+            None => range,
+        }
+    }
+}
+
+/// 将字节范围的起点和终点分别向前、向后扩展到最近的字素簇边界
+fn grapheme_range(text: &str, range: Range<usize>) -> Range<usize> {
+    let start = if is_grapheme_boundary(text, range.start) {
+        range.start
+    } else {
+        GraphemeCursor::new(range.start, text.len(), true)
+            .prev_boundary(text, 0)
+            .ok()
+            .flatten()
+            .unwrap_or(0)
+    };
+    let end = if is_grapheme_boundary(text, range.end) {
+        range.end
+    } else {
+        GraphemeCursor::new(range.end, text.len(), true)
+            .next_boundary(text, 0)
+            .ok()
+            .flatten()
+            .unwrap_or(text.len())
+    };
+    start..end
+}
+
+/// 字节位置是否是字素簇的边界
+fn is_grapheme_boundary(text: &str, offset: usize) -> bool {
+    if offset == 0 || offset >= text.len() {
+        return true;
+    }
+    let bytes = text.as_bytes();
+    // 前后都是 ascii 字符时，除了 \r\n 之间都是边界，不需要按照字素簇的规则判断
+    if bytes[offset - 1].is_ascii() && bytes[offset].is_ascii() {
+        return !(bytes[offset - 1] == b'\r' && bytes[offset] == b'\n');
+    }
+    GraphemeCursor::new(offset, text.len(), true)
+        .is_boundary(text, 0)
+        .unwrap_or(true)
+}
+
+/// 获取分词结果中每个 token 在原始文本中的字节范围
+///
+/// token 是原始文本的切片时，通过切片的地址直接计算位置，切片可以互相重叠、不按顺序出现，例如 jieba 搜索引擎模式的结果；
+/// 否则从上一个 token 的结束位置开始查找，要求 token 按照在文本中出现的顺序给出，
+/// 重复出现的 token 依次对应后面的位置，整个文本只被扫描一遍
+pub(super) struct OffsetTracker<'a> {
+    text: &'a str,
+    /// 下一次查找的起始位置
+    cursor: usize,
+}
+
+impl<'a> OffsetTracker<'a> {
+    pub(super) fn new(text: &'a str) -> Self {
+        Self { text, cursor: 0 }
+    }
+
+    /// 获取 token 的字节范围，从当前位置开始找不到这个 token 时返回 `None`
+    pub(super) fn range_of(&mut self, token: &str) -> Option<Range<usize>> {
+        let text_start = self.text.as_ptr() as usize;
+        let token_start = token.as_ptr() as usize;
+        let start = if (text_start..=text_start + self.text.len()).contains(&token_start)
+            && token_start + token.len() <= text_start + self.text.len()
+        {
+            token_start - text_start
+        } else {
+            self.cursor + self.text.get(self.cursor..)?.find(token)?
+        };
+        let range = start..start + token.len();
+        self.cursor = self.cursor.max(range.end);
+        Some(range)
+    }
+}
+
+/// 前缀查询时，获取最后一个单词的结束位置，不是前缀查询时返回 `None`
+///
+/// FTS5 只把前缀查询的最后一个 token 当作前缀，这个单词通常是不完整的，不能按照完整的单词过滤停词，
+/// 例如 `gu*` 中的 `gu` 是停词，过滤之后查询无法匹配 `国`
+pub(super) fn prefix_end(reason: &TokenizeReason, text: &str) -> Option<usize> {
+    matches!(reason, TokenizeReason::Query { prefix: true }).then(|| text.trim_end().len())
+}
+
+/// 解析 `stemmer=language` 参数中的词干提取语言，例如 `english`、`french`、`german`
+pub(super) fn parse_stemmer_arg(value: &str) -> Result<Algorithm, rusqlite::Error> {
+    Algorithm::from_str(value).ok_or_else(|| {
+        rusqlite::Error::ModuleError(format!("unsupported stemmer language {value}"))
+    })
+}
+
+/// 存在无法识别的参数时返回错误，错误信息中列出全部无法识别的参数，避免拼写错误的参数被忽略
+pub(super) fn reject_unknown_args(name: &str, unknown: &[String]) -> Result<(), rusqlite::Error> {
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    Err(rusqlite::Error::ModuleError(format!(
+        "unknown {name} tokenizer arguments: {}",
+        unknown.join(", ")
+    )))
+}
+
+/// 解析形如 `key=N` 的参数中的正整数
+pub(super) fn parse_positive_arg(key: &str, value: &str) -> Result<usize, rusqlite::Error> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(rusqlite::Error::ModuleError(format!(
+            "{key} must be a positive integer, got {value}"
+        ))),
+    }
+}
+
+/// 解析 `pinyin_sep=` 参数，分隔符必须是单词的一部分，否则查询时拼接的拼音会被切分成多个单词
+///
+/// 可以使用 `'`、`_`、`.`、`:` 等 Unicode 单词边界规则中不会切分单词的字符，空串表示不使用分隔符
+pub(super) fn parse_pinyin_sep_arg(value: &str) -> Result<String, rusqlite::Error> {
+    let joined = format!("a{value}a");
+    if joined.unicode_words().eq([joined.as_str()]) {
+        Ok(value.to_owned())
+    } else {
+        Err(rusqlite::Error::ModuleError(format!(
+            "pinyin_sep must not split a word, got {value}"
+        )))
+    }
+}
+
+/// token 默认的最大字节数，超过的 token 不写入
+pub(super) const DEFAULT_MAX_TOKEN_BYTES: usize = 1024;
+
+/// 拼接的拼音最多列举的读音组合数量，避免多音字过多时组合数量爆炸
+pub(super) const MAX_PINYIN_COMBINATIONS: usize = 16;
+
+/// 包装 `push_token`，跳过超过 `max_bytes` 字节的 token，例如没有空白的超长 base64 文本
+///
+/// 被跳过的 token 之后的第一个 colocated token 没有可以依附的 token，改为写入新的位置
+pub(super) fn skip_long_tokens<TKF>(
+    max_bytes: usize,
+    mut push_token: TKF,
+) -> impl FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>
+where
+    TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>,
+{
+    let mut skipped = false;
+    move |token, range, colocated| {
+        if token.len() > max_bytes {
+            skipped |= !colocated;
+            return Ok(());
+        }
+        let colocated = colocated && !skipped;
+        skipped = false;
+        (push_token)(token, range, colocated)
+    }
+}
+
+/// 包装 `push_token`，同一个 token 在文档中只写入一次
+///
+/// 和 [`skip_long_tokens`] 相同，被跳过的重复 token 之后的第一个不重复的 colocated token 改为写入新的位置，
+/// 避免拼音、原本的单词这些 colocated token 依附到前一个不相关的 token 上
+pub(super) fn dedup_tokens<TKF>(
+    mut push_token: TKF,
+) -> impl FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>
+where
+    TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>,
+{
+    let mut seen = HashSet::<Vec<u8>>::new();
+    let mut skipped = false;
+    move |token, range, colocated| {
+        if !seen.insert(token.to_vec()) {
+            skipped |= !colocated;
+            return Ok(());
+        }
+        let colocated = colocated && !skipped;
+        skipped = false;
+        (push_token)(token, range, colocated)
+    }
+}
+
+/// 限制一次分词写入的 token 数量，避免超长的文档产生大量的 token，拖慢写入
+///
+/// 只统计单独占用位置的 token，同一位置上的 colocated token 不计入数量。达到上限后 `push_token`
+/// 返回错误，停止分词，[`TokenLimit::finish`] 记录一条警告日志，把这个错误转换成 `Ok(())`，
+/// 文档仍然可以写入，只有前面的 token 可以查询。查询的文本不受限制
+pub(super) struct TokenLimit {
+    max_tokens: Option<usize>,
+    truncated: Cell<bool>,
+}
+
+impl TokenLimit {
+    pub(super) fn new(max_tokens: Option<usize>, reason: &TokenizeReason) -> Self {
+        let max_tokens = max_tokens.filter(|_| !matches!(reason, TokenizeReason::Query { .. }));
+        Self {
+            max_tokens,
+            truncated: Cell::new(false),
+        }
+    }
+
+    /// 包装 `push_token`，写入 `max_tokens` 个位置之后返回错误
+    pub(super) fn wrap<'a, TKF>(
+        &'a self,
+        mut push_token: TKF,
+    ) -> impl FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error> + 'a
+    where
+        TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error> + 'a,
+    {
+        let mut count = 0;
+        move |token, range, colocated| {
+            if let Some(max_tokens) = self.max_tokens
+                && !colocated
+            {
+                if count == max_tokens {
+                    self.truncated.set(true);
+                    return Err(rusqlite::Error::ModuleError(format!(
+                        "more than {max_tokens} tokens"
+                    )));
+                }
+                count += 1;
+            }
+            (push_token)(token, range, colocated)
+        }
+    }
+
+    /// 分词因为达到上限提前结束时，记录一条警告日志并返回 `Ok(())`，其余结果原样返回
+    pub(super) fn finish(
+        &self,
+        name: &str,
+        result: Result<(), rusqlite::Error>,
+    ) -> Result<(), rusqlite::Error> {
+        match (result, self.max_tokens) {
+            (Err(_), Some(max_tokens)) if self.truncated.get() => {
+                log::warn!("{name}: document truncated after {max_tokens} tokens");
+                Ok(())
+            }
+            (result, _) => result,
+        }
+    }
+}
+
+/// 统计一次分词中被停词表过滤的单词数量，用于 [`Tokenizer::stopwords_filtered`](crate::tokenizer::Tokenizer::stopwords_filtered)
+///
+/// 由分词器持有，[`TokenPipeline`] 只负责判断停词，不保存分词的状态
+#[derive(Debug, Default)]
+pub(super) struct StopwordCounter(AtomicUsize);
+
+impl StopwordCounter {
+    /// 记录一次停词检查的结果，原样返回 `stopword`
+    pub(super) fn record(&self, stopword: bool) -> bool {
+        if stopword {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+        stopword
+    }
+
+    /// 开始一次新的分词，清空计数
+    pub(super) fn reset(&self) {
+        self.0.store(0, Ordering::Relaxed);
+    }
+
+    pub(super) fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
 
 /// 判断是不是由空字符、控制字符、ascii标点字符组成的字符串
 pub(super) fn is_space_or_ascii_punctuation_str(word: &str) -> bool {
@@ -18,7 +361,143 @@ pub(super) fn is_space_or_ascii_punctuation_str(word: &str) -> bool {
     is_space
 }
 
+/// 判断是不是由空字符、控制字符、标点字符组成的字符串，标点字符包括 ascii 标点和全角标点、中文标点等 Unicode 标点
+///
+/// 和 [`is_punctuation_or_space_str`] 不同，`℃`、emoji 这些符号不是标点，不会被当作分隔符
+pub(super) fn is_separator_str(word: &str) -> bool {
+    // 大部分分隔符是 ascii 的空格和标点，先按照 ascii 判断
+    is_space_or_ascii_punctuation_str(word)
+        || word.chars().all(|ch| {
+            ch.is_whitespace()
+                || ch.is_control()
+                || ch.is_ascii_punctuation()
+                || is_unicode_punctuation(ch)
+        })
+}
+
+/// 判断是不是 ascii 以外的标点字符，例如 `！`、`，`、`。`、`《`、`…`
+///
+/// `‰`、`′`、`″`、`⁄`、`§`、`¶` 这些带有含义的符号不是分隔符，`30‰` 和 `5′` 可以被查询
+fn is_unicode_punctuation(ch: char) -> bool {
+    matches!(
+        ch,
+        '¡' | '«'
+            | '·'
+            | '»'
+            | '¿'
+            // 通用标点，例如破折号、引号、省略号
+            | '\u{2010}'..='\u{2027}'
+            | '‹'
+            | '›'
+            | '‼'
+            | '‽'
+            | '⁇'..='⁉'
+            // 中日韩标点，例如顿号、句号、书名号
+            | '\u{3001}'..='\u{3003}'
+            | '\u{3008}'..='\u{3011}'
+            | '\u{3014}'..='\u{301f}'
+            | '\u{3030}'
+            | '\u{303d}'
+            | '\u{30fb}'
+            // 竖排标点、兼容标点和小写标点
+            | '\u{fe10}'..='\u{fe19}'
+            | '\u{fe30}'..='\u{fe4f}'
+            | '\u{fe50}'..='\u{fe6b}'
+            // 全角标点，例如全角的感叹号、逗号、问号
+            | '\u{ff01}'..='\u{ff0f}'
+            | '\u{ff1a}'..='\u{ff20}'
+            | '\u{ff3b}'..='\u{ff40}'
+            | '\u{ff5b}'..='\u{ff65}'
+    )
+}
+
+/// 按照空白字符切分文本，返回每个单词的字节位置和单词，单词中的标点和符号保持原样
+pub(super) fn whitespace_word_indices(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_whitespace()
+        .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+/// 判断是不是不包含任何字母和数字的字符串，例如空白字符、标点符号
+pub(super) fn is_punctuation_or_space_str(word: &str) -> bool {
+    !word.chars().any(char::is_alphanumeric)
+}
+
+/// 字符所属的书写系统
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum Script {
+    /// 汉字
+    Han,
+    /// 拉丁字母
+    Latin,
+    /// 日文的平假名和片假名，包括半角片假名和长音符号 `ー`
+    Kana,
+    /// 其他书写系统
+    Other,
+}
+
+/// 获取字符所属的书写系统
+///
+/// 数字、标点、组合字符等不属于任何书写系统的字符返回 `None`
+fn script_of(ch: char) -> Option<Script> {
+    match ch {
+        '\u{2e80}'..='\u{2fdf}'
+        | '\u{3005}'..='\u{3007}'
+        | '\u{3021}'..='\u{3029}'
+        | '\u{3038}'..='\u{303b}'
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{20000}'..='\u{323af}' => Some(Script::Han),
+        '\u{00d7}' | '\u{00f7}' => None,
+        '\u{3041}'..='\u{309f}'
+        | '\u{30a0}'..='\u{30ff}'
+        | '\u{31f0}'..='\u{31ff}'
+        | '\u{ff66}'..='\u{ff9f}' => Some(Script::Kana),
+        'a'..='z'
+        | 'A'..='Z'
+        | '\u{00c0}'..='\u{02af}'
+        | '\u{1e00}'..='\u{1eff}'
+        | '\u{2c60}'..='\u{2c7f}'
+        | '\u{a720}'..='\u{a7ff}'
+        | '\u{ab30}'..='\u{ab6f}'
+        | '\u{fb00}'..='\u{fb06}'
+        | '\u{ff21}'..='\u{ff3a}'
+        | '\u{ff41}'..='\u{ff5a}' => Some(Script::Latin),
+        _ if ch.is_alphabetic() => Some(Script::Other),
+        _ => None,
+    }
+}
+
+/// 将单词按照书写系统切分成最长的连续片段，返回每个片段的书写系统和字节范围
+///
+/// 数字、标点等字符归入前一个片段，出现在开头时归入后一个片段，全部由这类字符组成的单词归为 `Script::Other`
+pub(super) fn script_runs(word: &str) -> Vec<(Script, Range<usize>)> {
+    let mut runs = Vec::<(Script, Range<usize>)>::new();
+    // 开头没有书写系统的字符，等待归入后一个片段
+    let mut pending_start = None;
+    for (index, ch) in word.char_indices() {
+        let end = index + ch.len_utf8();
+        match (script_of(ch), runs.last_mut()) {
+            (None, Some((_, range))) => range.end = end,
+            (None, None) => {
+                pending_start.get_or_insert(index);
+            }
+            (Some(script), Some((last, range))) if *last == script => range.end = end,
+            (Some(script), _) => {
+                let start = pending_start.take().unwrap_or(index);
+                runs.push((script, start..end));
+            }
+        }
+    }
+    if let Some(start) = pending_start {
+        runs.push((Script::Other, start..word.len()));
+    }
+    runs
+}
+
 /// 判断这个单词是否需要使用 pinyin 模块进行处理
+///
+/// 按照字符而不是字节判断单词是不是单个汉字，扩展 B 区及之后的汉字在 UTF-8 中占 4 个字节，同样可以获取拼音
 pub(super) fn need_pinyin(word: &str) -> bool {
     if word.is_empty() || word.chars().count() > 1 {
         // 空串，或者字符个数大于 1 的单词，不需要 pinyin 处理
@@ -30,30 +509,1866 @@ pub(super) fn need_pinyin(word: &str) -> bool {
     false
 }
 
-/// 对单词做归一化，并转换成小写
+/// Unicode 归一化的形式
 ///
-/// 如果全部都是由 ascii 字符组成的单词，并且长度超过 1，需要返回一个变量用来提示后续步骤做词干提取
-pub(super) fn make_lowercase(word: &str, buf: &mut String) -> bool {
-    buf.clear();
-    let mut need_stem = true;
-    for ch in word.nfkc() {
-        if is_diacritic(ch) {
-            continue;
+/// 不同的形式只影响兼容字符：NFKC 将全角字符 `ＳＱＬ` 转换成 `sql`，带圈数字 `②` 和上标 `²` 转换成 `2`，
+/// 连字 `ﬁ` 转换成 `fi`，`㍻` 转换成 `平成`；NFC 和不做归一化时这些字符都原样写入，
+/// 写入和查询需要使用相同的字形才能匹配。NFC 仍然会把 `e\u{301}` 这样的分解形式组合成 `é`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NormalizationForm {
+    /// 兼容分解后再组合，会折叠全角字符、上标、连字等兼容字符
+    #[default]
+    Nfkc,
+    /// 标准分解后再组合，保留兼容字符的原始字形
+    Nfc,
+    /// 不做归一化
+    None,
+}
+
+impl NormalizationForm {
+    /// 从 `normalization=` 参数的值中解析归一化形式
+    pub(super) fn from_arg(value: &str) -> Result<Self, rusqlite::Error> {
+        match value {
+            "nfkc" => Ok(Self::Nfkc),
+            "nfc" => Ok(Self::Nfc),
+            "none" => Ok(Self::None),
+            _ => Err(rusqlite::Error::ModuleError(format!(
+                "normalization must be one of nfkc, nfc, none, got {value}"
+            ))),
         }
-        if ch.is_ascii() {
-            buf.push(ch.to_ascii_lowercase());
-        } else {
+    }
+}
+
+/// 分词的文本中包含无效的 UTF-8 字节时的处理方式
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum InvalidUtf8Policy {
+    /// 无效的字节替换成 U+FFFD
+    #[default]
+    Lossy,
+    /// 跳过无效的字节，无效的字节作为单词之间的分隔
+    Skip,
+    /// 返回错误，写入或者查询失败
+    Error,
+}
+
+impl InvalidUtf8Policy {
+    /// 从 `invalid_utf8=` 参数的值中解析无效 UTF-8 字节的处理方式
+    pub(super) fn from_arg(value: &str) -> Result<Self, rusqlite::Error> {
+        match value {
+            "lossy" => Ok(Self::Lossy),
+            "skip" => Ok(Self::Skip),
+            "error" => Ok(Self::Error),
+            _ => Err(rusqlite::Error::ModuleError(format!(
+                "invalid_utf8 must be one of lossy, skip, error, got {value}"
+            ))),
+        }
+    }
+}
+
+/// emoji、`©`、`★` 这类不是字母、数字、空白和标点的符号的处理方式
+///
+/// `simple` 默认不写入符号，`jieba` 默认写入符号
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SymbolPolicy {
+    /// 和标点一样作为分隔符，不写入
+    Drop,
+    /// 每个符号作为一个 token 写入，符号按照扩展字素簇切分，
+    /// 使用零宽连接符组合的 emoji 序列（例如 `👨‍👩‍👧`）是一个 token
+    Keep,
+}
+
+impl SymbolPolicy {
+    /// 从 `symbols=` 参数的值中解析符号的处理方式
+    pub(super) fn from_arg(value: &str) -> Result<Self, rusqlite::Error> {
+        match value {
+            "drop" => Ok(Self::Drop),
+            "keep" => Ok(Self::Keep),
+            _ => Err(rusqlite::Error::ModuleError(format!(
+                "symbols must be one of drop, keep, got {value}"
+            ))),
+        }
+    }
+}
+
+/// 判断是不是只由符号组成的字符串，例如 emoji、`©`、`℃`，不包含字母和数字，也不全是空白和标点
+pub(super) fn is_symbol_str(word: &str) -> bool {
+    !word.chars().any(char::is_alphanumeric) && !is_separator_str(word)
+}
+
+/// 将文本中 `range` 范围内的符号按照扩展字素簇切分，返回每个符号在文本中的字节范围，空白和标点不返回
+pub(super) fn symbol_ranges(text: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    text[range.clone()]
+        .grapheme_indices(true)
+        .filter(|(_, grapheme)| is_symbol_str(grapheme))
+        .map(|(offset, grapheme)| range.start + offset..range.start + offset + grapheme.len())
+        .collect()
+}
+
+/// 多音字逐字写入拼音时，第一个读音的选择方式，第一个读音之后的读音作为 colocated token 写入同一个位置
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PinyinPrimary {
+    /// 按照拼音的字母顺序写入，例如 `行` 依次写入 `hang`、`heng`、`xing`
+    #[default]
+    Sorted,
+    /// 先写入拼音表中最常用的读音，例如 `行` 先写入 `xing`，其余读音按照字母顺序写入
+    Common,
+}
+
+impl PinyinPrimary {
+    /// 从 `pinyin_primary=` 参数的值中解析第一个读音的选择方式
+    pub(super) fn from_arg(value: &str) -> Result<Self, rusqlite::Error> {
+        match value {
+            "sorted" => Ok(Self::Sorted),
+            "common" => Ok(Self::Common),
+            _ => Err(rusqlite::Error::ModuleError(format!(
+                "pinyin_primary must be one of sorted, common, got {value}"
+            ))),
+        }
+    }
+}
+
+/// 数字单词的处理方式
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NumberMode {
+    /// 原样写入数字
+    #[default]
+    Keep,
+    /// 不写入数字，减小索引体积
+    Drop,
+    /// 去掉千位分隔符和整数部分开头的 0，例如 `1,000` 写入 `1000`，`007` 写入 `7`
+    Normalize,
+}
+
+impl NumberMode {
+    /// 从 `numbers=` 参数的值中解析数字的处理方式
+    pub(super) fn from_arg(value: &str) -> Result<Self, rusqlite::Error> {
+        match value {
+            "keep" => Ok(Self::Keep),
+            "drop" => Ok(Self::Drop),
+            "normalize" => Ok(Self::Normalize),
+            _ => Err(rusqlite::Error::ModuleError(format!(
+                "numbers must be one of keep, drop, normalize, got {value}"
+            ))),
+        }
+    }
+
+    /// 按照设置处理归一化后的单词，不是数字的单词保持原样，返回 `false` 表示不写入这个单词
+    pub(super) fn apply(self, word: &mut String) -> bool {
+        if self == Self::Keep || !is_number(word) {
+            return true;
+        }
+        if self == Self::Drop {
+            return false;
+        }
+        let number = word.replace(',', "");
+        let integer_end = number.find('.').unwrap_or(number.len());
+        // 整数部分至少保留一个 0
+        let zeros = number[..integer_end - 1]
+            .bytes()
+            .take_while(|b| *b == b'0')
+            .count();
+        *word = number[zeros..].to_owned();
+        true
+    }
+}
+
+/// 是否是数字，例如 `32`、`32.3`、`1,000`，只能有一个小数点，首尾必须是数字
+fn is_number(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    bytes.first().is_some_and(u8::is_ascii_digit)
+        && bytes.last().is_some_and(u8::is_ascii_digit)
+        && bytes
+            .iter()
+            .all(|b| b.is_ascii_digit() || *b == b',' || *b == b'.')
+        && bytes.iter().filter(|b| **b == b'.').count() <= 1
+}
+
+/// 合并被千位分隔符切开的数字，例如 jieba 将 `1,000` 切分成 `1`、`,`、`000`
+///
+/// `ranges` 是依次排列的单词在 `text` 中的范围，逗号后面是三位数字（可以带有小数部分）时，和前面的数字合并
+pub(super) fn merge_grouped_numbers(text: &str, ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    let is_group = |group: &str| {
+        let (digits, fraction) = group.split_at(group.len().min(3));
+        digits.len() == 3
+            && digits.bytes().all(|b| b.is_ascii_digit())
+            && (fraction.is_empty()
+                || fraction.strip_prefix('.').is_some_and(|fraction| {
+                    !fraction.is_empty() && fraction.bytes().all(|b| b.is_ascii_digit())
+                }))
+    };
+    let mut merged = Vec::<Range<usize>>::with_capacity(ranges.len());
+    for range in ranges {
+        merged.push(range);
+        if let [.., number, comma, group] = merged.as_slice()
+            && number.end == comma.start
+            && comma.end == group.start
+            && &text[comma.clone()] == ","
+            && is_number(&text[number.clone()])
+            && !text[number.clone()].contains('.')
+            && is_group(&text[group.clone()])
+        {
+            let range = number.start..group.end;
+            merged.truncate(merged.len() - 3);
+            merged.push(range);
+        }
+    }
+    merged
+}
+
+/// 连字的处理方式
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Ligatures {
+    /// 不单独处理连字，连字是否分解取决于归一化形式
+    #[default]
+    Keep,
+    /// 将排版连字（例如 `ﬁ`、`ﬂ`）分解成组成它的字母
+    Decompose,
+    /// 在 `Decompose` 的基础上，将 `æ`、`œ` 也分解成组成它的字母
+    All,
+}
+
+impl Ligatures {
+    /// 从 `ligatures=` 参数的值中解析连字的处理方式
+    pub(super) fn from_arg(value: &str) -> Result<Self, rusqlite::Error> {
+        match value {
+            "keep" => Ok(Self::Keep),
+            "decompose" => Ok(Self::Decompose),
+            "all" => Ok(Self::All),
+            _ => Err(rusqlite::Error::ModuleError(format!(
+                "ligatures must be one of keep, decompose, all, got {value}"
+            ))),
+        }
+    }
+
+    /// 获取连字分解后的字母，不需要分解时返回 `None`
+    fn decompose(self, ch: char) -> Option<&'static str> {
+        let decomposed = match (self, ch) {
+            (Self::Keep, _) => return None,
+            (_, '\u{fb00}') => "ff",
+            (_, '\u{fb01}') => "fi",
+            (_, '\u{fb02}') => "fl",
+            (_, '\u{fb03}') => "ffi",
+            (_, '\u{fb04}') => "ffl",
+            (_, '\u{fb05}' | '\u{fb06}') => "st",
+            (_, 'Ĳ') => "IJ",
+            (_, 'ĳ') => "ij",
+            (Self::All, 'Æ') => "AE",
+            (Self::All, 'æ') => "ae",
+            (Self::All, 'Œ') => "OE",
+            (Self::All, 'œ') => "oe",
+            _ => return None,
+        };
+        Some(decomposed)
+    }
+}
+
+/// 汉字异体字选择符（VS17 - VS256）的处理方式
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum VariationSelectors {
+    /// 去掉异体字选择符，异体字形和基本字符可以互相匹配
+    #[default]
+    Strip,
+    /// 保留异体字选择符
+    Keep,
+}
+
+impl VariationSelectors {
+    /// 从 `ivs=` 参数的值中解析异体字选择符的处理方式
+    pub(super) fn from_arg(value: &str) -> Result<Self, rusqlite::Error> {
+        match value {
+            "strip" => Ok(Self::Strip),
+            "keep" => Ok(Self::Keep),
+            _ => Err(rusqlite::Error::ModuleError(format!(
+                "ivs must be one of strip, keep, got {value}"
+            ))),
+        }
+    }
+}
+
+/// 分词器配置的摘要，格式为 `name(key=value,...)`，参数按照添加的顺序排列
+pub(super) struct ConfigSummary {
+    /// 分词器名称
+    name: &'static CStr,
+    /// 已经添加的 `key=value`
+    fields: Vec<String>,
+}
+
+impl ConfigSummary {
+    pub(super) fn new(name: &'static CStr) -> Self {
+        Self {
+            name,
+            fields: Vec::new(),
+        }
+    }
+
+    /// 添加一个参数
+    pub(super) fn field(&mut self, key: &str, value: impl std::fmt::Display) -> &mut Self {
+        self.fields.push(format!("{key}={value}"));
+        self
+    }
+
+    /// 添加一个开关，写成 `on` 或者 `off`
+    pub(super) fn switch(&mut self, key: &str, enabled: bool) -> &mut Self {
+        self.field(key, if enabled { "on" } else { "off" })
+    }
+
+    /// 开关和默认值不同时才添加
+    pub(super) fn flag(&mut self, key: &str, enabled: bool, default: bool) -> &mut Self {
+        if enabled != default {
+            self.switch(key, enabled);
+        }
+        self
+    }
+
+    /// 参数和默认值不同时才添加，值写成小写的变体名称，和参数的写法相同
+    pub(super) fn option<T: std::fmt::Debug + PartialEq>(
+        &mut self,
+        key: &str,
+        value: T,
+        default: T,
+    ) -> &mut Self {
+        if value != default {
+            self.field(key, format!("{value:?}").to_lowercase());
+        }
+        self
+    }
+
+    pub(super) fn finish(&self) -> String {
+        format!("{}({})", self.name.to_string_lossy(), self.fields.join(","))
+    }
+}
+
+/// 单词归一化的配置
+#[derive(Clone, Debug, Default)]
+pub(super) struct Normalizer {
+    /// Unicode 归一化的形式
+    pub(super) form: NormalizationForm,
+    /// 连字的处理方式，在归一化之前处理
+    pub(super) ligatures: Ligatures,
+    /// 汉字异体字选择符的处理方式
+    pub(super) ivs: VariationSelectors,
+    /// 是否保留组合附加符号（U+0300–U+036F），默认去掉
+    pub(super) keep_diacritics: bool,
+    /// 是否使用完整的 Unicode 大小写折叠代替小写转换，默认关闭
+    pub(super) casefold: bool,
+    /// 是否将半角片假名转换成全角片假名，默认关闭，NFKC 本身就会转换
+    pub(super) fullwidth_kana: bool,
+}
+
+impl Normalizer {
+    /// 在配置摘要中添加和默认值不同的归一化参数
+    pub(super) fn summarize(&self, summary: &mut ConfigSummary) {
+        summary
+            .option("normalization", self.form, NormalizationForm::default())
+            .option("ligatures", self.ligatures, Ligatures::default())
+            .option("ivs", self.ivs, VariationSelectors::default())
+            .flag("keep_diacritics", self.keep_diacritics, false)
+            .flag("casefold", self.casefold, false)
+            .flag("fullwidth_kana", self.fullwidth_kana, false);
+    }
+
+    /// 这个字符是否在归一化时被去掉的汉字异体字选择符
+    pub(super) fn strips_variation_selector(&self, ch: char) -> bool {
+        self.ivs == VariationSelectors::Strip && is_ideographic_variation_selector(ch)
+    }
+
+    /// 对单词做归一化，并转换成小写
+    ///
+    /// 如果全部都是由 ascii 字符组成的单词，并且长度超过 1，需要返回一个变量用来提示后续步骤做词干提取
+    pub(super) fn make_lowercase(&self, word: &str, buf: &mut String) -> bool {
+        if !word.is_ascii() {
+            return self.normalize_chars(word, buf);
+        }
+        // ascii 字符在各种归一化形式下保持不变，也不是附加符号、方向控制字符、连字或者片假名，只需要转换成小写
+        buf.clear();
+        buf.push_str(word);
+        buf.make_ascii_lowercase();
+        buf.len() > 1
+    }
+
+    /// 逐个字符做归一化并转换成小写，返回值和 [`Normalizer::make_lowercase`] 相同
+    fn normalize_chars(&self, word: &str, buf: &mut String) -> bool {
+        buf.clear();
+        let word = match self.fullwidth_kana {
+            true => widen_kana(word),
+            false => Cow::Borrowed(word),
+        };
+        let mut need_stem = true;
+        let mut push = |ch: char| {
+            if (!self.keep_diacritics && is_diacritic(ch))
+                || is_bidi_control(ch)
+                || self.strips_variation_selector(ch)
+            {
+                return;
+            }
+            if ch.is_ascii() {
+                buf.push(ch.to_ascii_lowercase());
+            } else if self.casefold {
+                // 折叠后是 ascii 的单词同样需要提取词干，和查询的 ascii 单词保持一致，例如 `straße` 折叠成 `strasse`
+                let start = buf.len();
+                case_fold(ch, buf);
+                need_stem &= buf[start..].is_ascii();
+            } else {
+                need_stem = false;
+                buf.extend(ch.to_lowercase());
+            }
+        };
+        let chars = word
+            .chars()
+            .flat_map(|ch| match self.ligatures.decompose(ch) {
+                Some(decomposed) => decomposed.chars().chain(None),
+                None => "".chars().chain(Some(ch)),
+            });
+        match self.form {
+            NormalizationForm::Nfkc => chars.nfkc().for_each(&mut push),
+            NormalizationForm::Nfc => chars.nfc().for_each(&mut push),
+            NormalizationForm::None => chars.for_each(&mut push),
+        }
+        if buf.len() <= 1 {
+            // 单个字符不需要提取词干
             need_stem = false;
+        }
+        need_stem
+    }
+}
+
+/// 是否半角片假名，包括半角的长音符号和浊音符号
+fn is_halfwidth_kana(ch: char) -> bool {
+    ('\u{ff66}'..='\u{ff9f}').contains(&ch)
+}
+
+/// 将半角片假名转换成全角片假名，浊音符号 `ﾞ` 和半浊音符号 `ﾟ` 和前一个假名合并，例如 `ﾋﾟ` 转换成 `ピ`
+///
+/// 只转换半角片假名，其余字符保持原样，因此 NFC 和不做归一化时也可以使用
+fn widen_kana(word: &str) -> Cow<'_, str> {
+    if !word.chars().any(is_halfwidth_kana) {
+        return Cow::Borrowed(word);
+    }
+    let mut widened = String::with_capacity(word.len());
+    for ch in word.chars() {
+        if !is_halfwidth_kana(ch) {
+            widened.push(ch);
+            continue;
+        }
+        // 半角片假名的兼容分解都是单个字符，浊音符号分解成组合用的 U+3099 和 U+309A
+        let wide = std::iter::once(ch).nfkd().next().unwrap_or(ch);
+        let composed = widened
+            .chars()
+            .next_back()
+            .and_then(|last| unicode_normalization::char::compose(last, wide));
+        match composed {
+            Some(composed) => {
+                widened.pop();
+                widened.push(composed);
+            }
+            None => widened.push(wide),
+        }
+    }
+    Cow::Owned(widened)
+}
+
+/// 使用完整的 Unicode 大小写折叠转换一个字符，写入 `buf`
+///
+/// 只处理 `CaseFolding.txt` 中折叠结果和小写转换不同的常用字符，例如 `ß` 折叠成 `ss`、`ς` 折叠成 `σ`，
+/// 折叠结果包含组合附加符号的字符（例如 `ǰ`）保持小写转换的结果，其余字符使用小写转换
+fn case_fold(ch: char, buf: &mut String) {
+    let folded = match ch {
+        'ß' | 'ẞ' => "ss",
+        'ſ' => "s",
+        'ŉ' => "ʼn",
+        'ς' => "σ",
+        'ϐ' => "β",
+        'ϑ' => "θ",
+        'ϕ' => "φ",
+        'ϖ' => "π",
+        'ϰ' => "κ",
+        'ϱ' => "ρ",
+        'ϵ' => "ε",
+        'ẛ' => "ṡ",
+        '\u{1fbe}' => "ι",
+        'ﬀ' => "ff",
+        'ﬁ' => "fi",
+        'ﬂ' => "fl",
+        'ﬃ' => "ffi",
+        'ﬄ' => "ffl",
+        'ﬅ' | 'ﬆ' => "st",
+        _ => {
             buf.extend(ch.to_lowercase());
+            return;
         }
+    };
+    buf.push_str(folded);
+}
+
+/// 去掉单词中的附加符号，得到 ASCII 折叠后的单词，例如 `naïve` 转换成 `naive`，单词没有变化时返回 `None`
+///
+/// 只去掉分解后的组合附加符号，`ø`、`ß` 这些没有分解形式的字母保持不变
+pub(super) fn fold_diacritics(word: &str) -> Option<String> {
+    let folded = word
+        .nfd()
+        .filter(|ch| !is_diacritic(*ch))
+        .nfc()
+        .collect::<String>();
+    (folded != word).then_some(folded)
+}
+
+/// [`TokenPipeline::process`] 处理单词的结果
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PipelineOutcome {
+    /// 不写入这个单词，例如停词、空白和标点
+    Skip,
+    /// 写入处理后的单词
+    Emit,
+}
+
+/// 自定义的 token 转换，参数是归一化后的单词，返回 `None` 时不写入这个单词
+///
+/// 在归一化之后、停词和词干提取之前调用，通过 `simple` 和 `jieba` 的全局数据设置
+pub type TokenTransform = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// 单词的处理流程：归一化并转换成小写、去掉附加符号、自定义转换、过滤停词、词干提取
+///
+/// `simple`、`jieba` 和 `unicode` 分词器使用同一个流程处理切分出的单词，
+/// 实现自己的 [`Tokenizer`](crate::tokenizer::Tokenizer) 时可以复用这个流程，得到和内置分词器相同的 token
+#[derive(Clone)]
+pub struct TokenPipeline {
+    /// 是否启用停词表，默认启用
+    pub(super) stopword: bool,
+    /// 是否做词干提取，默认启用
+    pub(super) stemming: bool,
+    /// 词干提取使用的语言，默认使用英语
+    pub(super) stemmer: Algorithm,
+    /// 归一化后的单词至少包含的字符数，不足的单词不写入，为 0 时不过滤，包含汉字的单词不受影响
+    pub(super) min_token_chars: usize,
+    /// 是否只在写入文档时做词干提取，查询时保持原样，默认关闭
+    pub(super) stem_documents_only: bool,
+    /// 是否对词干提取的结果检查停词，默认关闭，对归一化后、词干提取之前的单词检查停词
+    pub(super) stopword_after_stem: bool,
+    /// 单词归一化的配置
+    pub(super) normalizer: Normalizer,
+    /// 独立使用的停词表，为 `None` 时使用默认的停词表
+    stopwords: Option<HashSet<String>>,
+    /// 通过 [`TokenPipeline::refresh_stopwords`] 获取的全局停词表，为 `None` 时使用内置的停词表
+    global_stopwords: Option<Arc<HashSet<String>>>,
+    /// 自定义的 token 转换
+    transform: Option<TokenTransform>,
+}
+
+impl std::fmt::Debug for TokenPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenPipeline")
+            .field("stopword", &self.stopword)
+            .field("stemming", &self.stemming)
+            .field("stemmer", &self.stemmer)
+            .field("min_token_chars", &self.min_token_chars)
+            .field("stem_documents_only", &self.stem_documents_only)
+            .field("stopword_after_stem", &self.stopword_after_stem)
+            .field("normalizer", &self.normalizer)
+            .field("transform", &self.transform.is_some())
+            .finish_non_exhaustive()
     }
-    if buf.len() <= 1 {
-        // 单个字符不需要提取词干
-        need_stem = false;
+}
+
+impl Default for TokenPipeline {
+    fn default() -> Self {
+        Self {
+            stopword: true,
+            stemming: true,
+            stemmer: Algorithm::English,
+            min_token_chars: 0,
+            stem_documents_only: false,
+            stopword_after_stem: false,
+            normalizer: Normalizer::default(),
+            stopwords: None,
+            global_stopwords: None,
+            transform: None,
+        }
+    }
+}
+
+impl TokenPipeline {
+    /// 在配置摘要中添加停词和词干提取的开关，以及和默认值不同的参数
+    pub(super) fn summarize(&self, summary: &mut ConfigSummary) {
+        // 独立的停词表来自 `stopword=` 或者 `stopword_add=`，摘要中不列出停词
+        match (self.stopword, &self.stopwords) {
+            (true, Some(_)) => summary.field("stopword", "custom"),
+            _ => summary.switch("stopword", self.stopword),
+        };
+        summary.switch("stemming", self.stemming);
+        if self.stemmer != Algorithm::English {
+            summary.field("stemmer", self.stemmer.as_str());
+        }
+        if self.min_token_chars > 0 {
+            summary.field("min_token_chars", self.min_token_chars);
+        }
+        summary
+            .flag("stem_documents_only", self.stem_documents_only, false)
+            .flag("stopword_after_stem", self.stopword_after_stem, false);
+        self.normalizer.summarize(summary);
+        summary.flag("transform", self.transform.is_some(), false);
+    }
+
+    /// 处理一个切分出的单词，返回 [`PipelineOutcome::Emit`] 时 `buf` 中是需要写入的 token
+    ///
+    /// 按照写入文档的方式处理，空白和标点、归一化后为空的单词和停词返回 [`PipelineOutcome::Skip`]
+    pub fn process(&self, word: &str, buf: &mut String) -> PipelineOutcome {
+        if is_separator_str(word) {
+            return PipelineOutcome::Skip;
+        }
+        let mut ascii = self.normalize(word, buf);
+        if buf.is_empty() || !self.transform(buf, &mut ascii) {
+            return PipelineOutcome::Skip;
+        }
+        let need_stem = self.need_stem(&TokenizeReason::Document, ascii, buf);
+        if self.is_too_short(buf) || self.is_stemmed_stopword(buf, need_stem) {
+            return PipelineOutcome::Skip;
+        }
+        if need_stem {
+            let stemmed = self.stem(buf).into_owned();
+            *buf = stemmed;
+        }
+        PipelineOutcome::Emit
+    }
+
+    /// 获取通过 [`set_global_stopwords`] 替换的全局停词表，创建分词器时调用一次
+    ///
+    /// 之后替换全局停词表不影响这个分词器，FTS5 重新分词已经写入的文档时（`delete`、`integrity-check`）
+    /// 得到的 token 和写入时一致。没有调用时使用内置的停词表
+    pub fn refresh_stopwords(&mut self) {
+        self.global_stopwords = global_stopwords();
+    }
+    /// 不启用停词表
+    pub fn disable_stopword(&mut self) {
+        self.stopword = false;
+    }
+    /// 不做词干提取
+    pub fn disable_stemming(&mut self) {
+        self.stemming = false;
+    }
+    /// 设置词干提取使用的语言，默认使用英语
+    pub fn set_stemmer(&mut self, language: Algorithm) {
+        self.stemmer = language;
+    }
+    /// 只在写入文档时做词干提取，查询时保持原样
+    pub fn enable_stem_documents_only(&mut self) {
+        self.stem_documents_only = true;
+    }
+    /// 设置归一化后的单词至少包含的字符数，为 0 时不过滤
+    pub fn set_min_token_chars(&mut self, min_chars: usize) {
+        self.min_token_chars = min_chars;
+    }
+    /// 使用停词表文件代替默认的停词表
+    ///
+    /// 文件使用 UTF-8 编码，每行一个停词，忽略空行和以 `#` 开头的注释行，
+    /// 文件无法读取时，记录一条警告日志，继续使用默认的停词表
+    pub fn load_stopwords(&mut self, path: &str) {
+        self.stopwords = load_stopwords(path);
+    }
+    /// 在当前的停词表上添加停词
+    ///
+    /// 不启用停词表时，只使用添加的停词，不再使用默认的停词表
+    pub fn add_stopwords<S: AsRef<str>>(&mut self, words: impl IntoIterator<Item = S>) {
+        let stopwords = match self.stopwords.take() {
+            Some(stopwords) if self.stopword => stopwords,
+            _ if self.stopword => default_stopwords(),
+            _ => HashSet::new(),
+        };
+        let stopwords = self.stopwords.insert(stopwords);
+        extend_stopwords(stopwords, words);
+        self.stopword = true;
+    }
+    /// 设置 Unicode 归一化的形式，默认为 NFKC
+    pub fn set_normalization(&mut self, form: NormalizationForm) {
+        self.normalizer.form = form;
+    }
+    /// 设置连字的处理方式，连字在归一化之前处理，默认不单独处理
+    pub fn set_ligatures(&mut self, ligatures: Ligatures) {
+        self.normalizer.ligatures = ligatures;
+    }
+    /// 保留组合附加符号，默认去掉
+    pub fn enable_keep_diacritics(&mut self) {
+        self.normalizer.keep_diacritics = true;
+    }
+    /// 使用完整的 Unicode 大小写折叠代替小写转换，默认关闭
+    pub fn enable_casefold(&mut self) {
+        self.normalizer.casefold = true;
+    }
+    /// 将半角片假名转换成全角片假名，默认关闭
+    pub fn enable_fullwidth_kana(&mut self) {
+        self.normalizer.fullwidth_kana = true;
+    }
+    /// 设置汉字异体字选择符的处理方式，默认去掉异体字选择符
+    pub fn set_variation_selectors(&mut self, ivs: VariationSelectors) {
+        self.normalizer.ivs = ivs;
+    }
+
+    /// 设置自定义的 token 转换，为 `None` 时不做转换
+    pub fn set_transform(&mut self, transform: Option<TokenTransform>) {
+        self.transform = transform;
+    }
+    /// 对归一化后的单词做自定义的转换，返回 `false` 表示不写入这个单词
+    ///
+    /// 单词被转换时，重新判断是否是需要做词干提取的 ascii 单词
+    pub(super) fn transform(&self, buf: &mut String, ascii: &mut bool) -> bool {
+        let Some(transform) = &self.transform else {
+            return true;
+        };
+        match transform(buf) {
+            Some(transformed) if transformed.is_empty() => false,
+            Some(transformed) => {
+                if transformed != *buf {
+                    *ascii = transformed.is_ascii() && transformed.len() > 1;
+                    *buf = transformed;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+    /// 对单词做归一化，并转换成小写，返回是否是需要做词干提取的 ascii 单词
+    pub(super) fn normalize(&self, word: &str, buf: &mut String) -> bool {
+        self.normalizer.make_lowercase(word, buf)
+    }
+    /// 归一化后的单词是否是停词，没有启用停词表时总是返回 `false`
+    pub fn is_stopword(&self, word: &str) -> bool {
+        if !self.stopword {
+            return false;
+        }
+        match (&self.stopwords, &self.global_stopwords) {
+            (Some(stopwords), _) => stopwords.contains(word),
+            (None, Some(stopwords)) => stopwords.contains(word),
+            (None, None) => STOPWORD.contains(word),
+        }
+    }
+    /// 按照 `stopword_after_stem` 的设置检查停词，`need_stem` 表示这个单词是否会做词干提取
+    ///
+    /// 默认检查词干提取之前的单词，开启后检查词干提取的结果，例如 `running` 的词干 `run` 是停词，
+    /// 而停词 `does` 的词干 `doe` 不是停词
+    pub(super) fn is_stemmed_stopword(&self, word: &str, need_stem: bool) -> bool {
+        if self.stopword_after_stem && need_stem {
+            self.is_stopword(&self.stem(word))
+        } else {
+            self.is_stopword(word)
+        }
+    }
+    /// 对词干提取的结果检查停词，默认在词干提取之前检查
+    pub fn enable_stopword_after_stem(&mut self) {
+        self.stopword_after_stem = true;
+    }
+    /// 当前生效的停词表，和 [`TokenPipeline::is_stopword`] 使用的停词表相同，用于排查单词为什么没有写入
+    ///
+    /// 依次使用自己的停词表（`stopword=` 文件和添加的停词）、创建分词器时获取的全局停词表和内置的停词表，
+    /// 内置的停词表不是 `HashSet`，需要复制一份，不启用停词表时为空
+    pub fn effective_stopwords(&self) -> Cow<'_, HashSet<String>> {
+        if !self.stopword {
+            return Cow::Owned(HashSet::new());
+        }
+        match (&self.stopwords, &self.global_stopwords) {
+            (Some(stopwords), _) => Cow::Borrowed(stopwords),
+            (None, Some(stopwords)) => Cow::Borrowed(stopwords.as_ref()),
+            (None, None) => Cow::Owned(STOPWORD.iter().map(|word| (*word).to_owned()).collect()),
+        }
+    }
+    /// 归一化后的单词是否少于 `min_token_chars` 个字符
+    ///
+    /// 单个汉字也有独立的含义，包含汉字的单词不受这个限制
+    pub fn is_too_short(&self, word: &str) -> bool {
+        self.min_token_chars > 1
+            && !word.chars().any(is_han)
+            && word.chars().nth(self.min_token_chars - 1).is_none()
+    }
+    /// 归一化后的单词是否需要做词干提取
+    ///
+    /// 开启 `stem_documents_only` 时，查询的单词不做词干提取。
+    /// `ascii` 表示归一化时判断出的纯 ascii 单词，英语只对这类单词做词干提取，
+    /// 其他语言的单词通常带有非 ascii 的字母，只要求单词超过一个字符
+    pub(super) fn need_stem(&self, reason: &TokenizeReason, ascii: bool, word: &str) -> bool {
+        if self.stem_documents_only && matches!(reason, TokenizeReason::Query { .. }) {
+            return false;
+        }
+        self.stemming
+            && (ascii || (self.stemmer != Algorithm::English && word.chars().nth(1).is_some()))
+    }
+    /// 使用设置的语言做词干提取，关闭词干提取时原样返回
+    pub fn stem<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        if self.stemming {
+            waken_snowball::stem(self.stemmer, word)
+        } else {
+            Cow::Borrowed(word)
+        }
+    }
+}
+
+/// 判断字符是不是汉字
+pub(super) fn is_han(ch: char) -> bool {
+    script_of(ch) == Some(Script::Han)
+}
+
+/// 将代码中的标识符按照下划线和驼峰切分，返回每个子词在单词中的字节范围
+///
+/// 小写字母或数字后面的大写字母开始一个新的子词，连续的大写字母中，后面跟着小写字母的最后一个大写字母开始一个新的子词，
+/// 例如 `getUserName` 切分成 `get`、`User`、`Name`，`HTTPServer` 切分成 `HTTP`、`Server`
+pub(super) fn identifier_parts(word: &str) -> Vec<Range<usize>> {
+    let mut parts = Vec::<Range<usize>>::new();
+    let mut start = None;
+    let mut prev: Option<char> = None;
+    let mut chars = word.char_indices().peekable();
+    while let Some((index, ch)) = chars.next() {
+        if ch == '_' {
+            if let Some(start) = start.take() {
+                parts.push(start..index);
+            }
+            prev = None;
+            continue;
+        }
+        let next = chars.peek().map(|&(_, next)| next);
+        let boundary = ch.is_uppercase()
+            && match prev {
+                Some(prev) if prev.is_lowercase() || prev.is_numeric() => true,
+                Some(prev) if prev.is_uppercase() => next.is_some_and(char::is_lowercase),
+                _ => false,
+            };
+        if boundary && let Some(start) = start.take() {
+            parts.push(start..index);
+        }
+        start.get_or_insert(index);
+        prev = Some(ch);
+    }
+    if let Some(start) = start {
+        parts.push(start..word.len());
+    }
+    parts
+}
+
+/// 在字母和 ascii 数字相邻的位置切分单词，返回每个部分在单词中的字节范围
+///
+/// 例如 `ABC123` 切分成 `ABC`、`123`，`version2beta` 切分成 `version`、`2`、`beta`，
+/// 撇号和小数点等其他字符不是切分的位置，`32.3feet` 切分成 `32.3`、`feet`
+pub(super) fn alnum_parts(word: &str) -> Vec<Range<usize>> {
+    let mut parts = Vec::<Range<usize>>::new();
+    let mut start = 0;
+    let mut prev: Option<char> = None;
+    for (index, ch) in word.char_indices() {
+        let boundary = match prev {
+            Some(prev) => {
+                (prev.is_ascii_digit() && ch.is_alphabetic())
+                    || (prev.is_alphabetic() && ch.is_ascii_digit())
+            }
+            None => false,
+        };
+        if boundary {
+            parts.push(start..index);
+            start = index;
+        }
+        if ch.is_alphanumeric() {
+            prev = Some(ch);
+        }
+    }
+    if start < word.len() {
+        parts.push(start..word.len());
+    }
+    parts
+}
+
+/// 判断是不是由拉丁字母组成的单词
+pub(super) fn is_latin_word(word: &str) -> bool {
+    !word.is_empty()
+        && word
+            .chars()
+            .all(|ch| ch.is_ascii_alphabetic() || ('\u{00c0}'..='\u{024f}').contains(&ch))
+}
+
+/// 将单词切分成连续 n 个字符的片段，用于模糊匹配
+///
+/// 单词的字符个数不超过 n 时，不产生片段
+pub(super) fn shingles(word: &str, n: usize) -> Vec<&str> {
+    let bounds = word
+        .char_indices()
+        .map(|(index, _)| index)
+        .chain(std::iter::once(word.len()))
+        .collect::<Vec<usize>>();
+    if n == 0 || bounds.len() - 1 <= n {
+        return Vec::new();
+    }
+    bounds
+        .windows(n + 1)
+        .map(|window| &word[window[0]..window[n]])
+        .collect()
+}
+
+/// 判断 ascii 单词是否是由数字和单位组成，例如 `32.3feet`、`1,000m`
+///
+/// 返回数字部分结束的字节位置，数字中可以包含 `.` 和 `,`，单位只能由字母组成
+pub(super) fn split_number_unit(word: &str) -> Option<usize> {
+    let bytes = word.as_bytes();
+    let mut end = 0;
+    for (index, byte) in bytes.iter().enumerate() {
+        if byte.is_ascii_digit() {
+            end = index + 1;
+        } else if (*byte == b'.' || *byte == b',')
+            && end == index
+            && bytes.get(index + 1).is_some_and(u8::is_ascii_digit)
+        {
+            continue;
+        } else {
+            break;
+        }
+    }
+    if end == 0 || end == bytes.len() || !bytes[end..].iter().all(u8::is_ascii_alphabetic) {
+        return None;
+    }
+    Some(end)
+}
+
+/// 英文数字单词，下标就是对应的数字
+static EN_NUMBER_WORDS: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+/// 英文整十的数字单词，下标加 2 再乘以 10 就是对应的数字
+static EN_TENS_WORDS: [&str; 8] = [
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// 获取小写单词对应的另一种写法：数字得到英文单词，英文单词得到数字，例如 `3` 和 `three`
+///
+/// 只处理 0 到 99 中可以写成一个英文单词的数字，`twenty-one` 这种复合的写法会被切分成两个单词，不做处理
+pub(super) fn en_number_word(word: &str) -> Option<String> {
+    if !word.is_empty() && word.bytes().all(|byte| byte.is_ascii_digit()) {
+        if word.len() > 1 && word.starts_with('0') {
+            return None;
+        }
+        let number = word.parse::<usize>().ok()?;
+        return match number {
+            0..20 => Some(EN_NUMBER_WORDS[number].to_owned()),
+            20..100 if number % 10 == 0 => Some(EN_TENS_WORDS[number / 10 - 2].to_owned()),
+            _ => None,
+        };
+    }
+    if let Some(number) = EN_NUMBER_WORDS.iter().position(|w| *w == word) {
+        return Some(number.to_string());
+    }
+    let tens = EN_TENS_WORDS.iter().position(|w| *w == word)?;
+    Some(((tens + 2) * 10).to_string())
+}
+
+/// 汉字数字的数值，包括大写数字，例如 `三` 和 `叁` 都是 3
+fn cjk_digit(ch: char) -> Option<u64> {
+    let digit = match ch {
+        '零' | '〇' => 0,
+        '一' | '壹' => 1,
+        '二' | '两' | '兩' | '贰' | '貳' => 2,
+        '三' | '叁' | '參' => 3,
+        '四' | '肆' => 4,
+        '五' | '伍' => 5,
+        '六' | '陆' | '陸' => 6,
+        '七' | '柒' => 7,
+        '八' | '捌' => 8,
+        '九' | '玖' => 9,
+        _ => return None,
+    };
+    Some(digit)
+}
+
+/// 汉字数字的位值，`十`、`百`、`千` 是小节内的位值，`万`、`亿` 是小节的位值
+fn cjk_unit(ch: char) -> Option<u64> {
+    let unit = match ch {
+        '十' | '拾' => 10,
+        '百' | '佰' => 100,
+        '千' | '仟' => 1000,
+        '万' | '萬' => 10_000,
+        '亿' | '億' => 100_000_000,
+        _ => return None,
+    };
+    Some(unit)
+}
+
+/// 将汉字数字转换成阿拉伯数字，例如 `三十二` 转换成 `32`、`一百零五` 转换成 `105`
+///
+/// 没有位值的数字逐位转换，例如年份 `一九八四` 转换成 `1984`。位值之后省略的下一级位值按照口语补全，
+/// 例如 `一百五` 是 150、`三万五` 是 35000，`十二` 省略了开头的 `一`。不是合法的汉字数字时返回 `None`
+pub(super) fn cjk_number(text: &str) -> Option<String> {
+    if text.is_empty() {
+        return None;
+    }
+    if !text.chars().any(|ch| cjk_unit(ch).is_some()) {
+        return text
+            .chars()
+            .map(|ch| cjk_digit(ch).and_then(|digit| char::from_digit(digit as u32, 10)))
+            .collect();
+    }
+    // 已经乘以 `万`、`亿` 的部分
+    let mut total = 0u64;
+    // 当前小节中已经乘以位值的部分
+    let mut section = 0u64;
+    // 还没有乘以位值的数字
+    let mut digit = None;
+    // 当前小节中上一个位值，位值需要依次减小
+    let mut last_unit = None;
+    // 上一个小节的位值
+    let mut last_big_unit = None;
+    // 最近的一个位值，包括小节的位值
+    let mut prev_unit = 0;
+    // 最近的位值之后是否出现了 `零`，出现时之后的数字不再补全省略的位值
+    let mut zero = false;
+    for ch in text.chars() {
+        if let Some(value) = cjk_digit(ch) {
+            if digit.is_some() {
+                // 连续的数字不是合法的写法，例如 `二三十`
+                return None;
+            }
+            if value == 0 {
+                zero = true;
+            } else {
+                digit = Some(value);
+            }
+            continue;
+        }
+        let unit = cjk_unit(ch)?;
+        if unit < 10_000 {
+            if last_unit.is_some_and(|last| unit >= last) {
+                return None;
+            }
+            let value = match digit.take() {
+                Some(value) => value,
+                None if unit == 10 => 1,
+                None => return None,
+            };
+            section = section.checked_add(value * unit)?;
+            last_unit = Some(unit);
+        } else {
+            let value = section + digit.take().unwrap_or(0);
+            // 例如 `一万亿`，前面的部分都要乘以 `亿`
+            let scale_total = last_big_unit.is_some_and(|last| unit > last);
+            if (value == 0 && !scale_total) || last_big_unit == Some(unit) {
+                return None;
+            }
+            total = if scale_total {
+                total.checked_add(value)?.checked_mul(unit)?
+            } else {
+                total.checked_add(value.checked_mul(unit)?)?
+            };
+            section = 0;
+            last_unit = None;
+            last_big_unit = Some(unit);
+        }
+        prev_unit = unit;
+        zero = false;
+    }
+    let tail = match digit {
+        // 紧接在位值之后的数字补全省略的下一级位值，例如 `一百五`
+        Some(value) if !zero && prev_unit >= 100 => value * prev_unit / 10,
+        Some(value) => value,
+        None => 0,
+    };
+    Some(total.checked_add(section)?.checked_add(tail)?.to_string())
+}
+
+/// 找出文本中连续的汉字数字，返回每个数字在文本中的范围和对应的阿拉伯数字
+pub(super) fn cjk_number_spans(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    let is_numeral = |ch: char| cjk_digit(ch).is_some() || cjk_unit(ch).is_some();
+    for (index, ch) in text.char_indices().chain([(text.len(), ' ')]) {
+        match start {
+            None if is_numeral(ch) => start = Some(index),
+            Some(begin) if !is_numeral(ch) => {
+                if let Some(number) = cjk_number(&text[begin..index]) {
+                    spans.push((begin..index, number));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    spans
+}
+
+/// 解析停词表，每行一个停词，忽略空行和以 `#` 开头的注释行
+///
+/// 停词会去掉首尾的空白并转换成小写，和归一化后的单词比较
+pub(super) fn parse_stopwords(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// 运行时替换的全局停词表，为 `None` 时使用内置的停词表
+static GLOBAL_STOPWORDS: RwLock<Option<Arc<HashSet<String>>>> = RwLock::new(None);
+
+/// 替换全局的停词表，`simple` 和 `jieba` 分词器都会使用新的停词表
+///
+/// 停词会去掉首尾的空白并转换成小写。替换是线程安全的，可以在其他线程分词的同时调用：
+/// 分词器在创建时获取一次停词表，已经创建的分词器（已经打开的表）继续使用旧的停词表，
+/// 之后创建的分词器（新建的表、新打开的连接）使用新的停词表。
+/// 已经写入索引的文档不受影响，新的连接打开已有的表之前需要重建索引，否则 `delete` 和 `integrity-check` 得到的 token 和索引不一致。
+/// 创建分词器时指定了 `stopword=` 的表使用自己的停词表，不受影响
+pub fn set_global_stopwords(words: impl IntoIterator<Item = String>) {
+    let mut stopwords = HashSet::new();
+    extend_stopwords(&mut stopwords, words);
+    *GLOBAL_STOPWORDS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(stopwords));
+}
+
+/// 恢复使用内置的停词表，线程安全的保证和 [`set_global_stopwords`] 相同
+pub fn reset_global_stopwords() {
+    GLOBAL_STOPWORDS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+}
+
+/// 获取通过 [`set_global_stopwords`] 替换的全局停词表，没有替换时返回 `None`
+pub(super) fn global_stopwords() -> Option<Arc<HashSet<String>>> {
+    GLOBAL_STOPWORDS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// 观察 token 的回调，参数依次是分词器名称、token、token 在文本中的位置、是否是 colocated token
+#[cfg(feature = "token_observer")]
+type TokenObserverFn = dyn Fn(&str, &[u8], Range<usize>, bool) + Send + Sync;
+
+/// 通过 [`set_token_observer`] 设置的回调
+#[cfg(feature = "token_observer")]
+pub type TokenObserver = Box<TokenObserverFn>;
+
+#[cfg(feature = "token_observer")]
+static TOKEN_OBSERVER: RwLock<Option<Arc<TokenObserverFn>>> = RwLock::new(None);
+
+/// 设置全局的 token 回调，`simple` 和 `jieba` 分词器在写入每个 token 之前调用，传入 `None` 时取消回调
+///
+/// 回调只用于观察写入的 token，不影响分词的结果。每次分词开始时获取一次回调，
+/// 替换回调只影响之后的分词。回调可能在多个线程中同时调用，需要尽快返回，避免拖慢写入
+#[cfg(feature = "token_observer")]
+pub fn set_token_observer(observer: Option<TokenObserver>) {
+    *TOKEN_OBSERVER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = observer.map(Arc::from);
+}
+
+/// 在调用 `push_token` 之前，将 token 交给通过 [`set_token_observer`] 设置的回调
+#[cfg(feature = "token_observer")]
+pub(super) fn observe_tokens<TKF>(
+    name: &'static str,
+    mut push_token: TKF,
+) -> impl FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>
+where
+    TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>,
+{
+    let observer = TOKEN_OBSERVER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    move |token: &[u8], range: Range<usize>, colocated: bool| {
+        if let Some(observer) = &observer {
+            observer(name, token, range.clone(), colocated);
+        }
+        (push_token)(token, range, colocated)
+    }
+}
+
+/// 没有启用 `token_observer` 特性时，原样返回 `push_token`
+#[cfg(not(feature = "token_observer"))]
+pub(super) fn observe_tokens<TKF>(_name: &'static str, push_token: TKF) -> TKF
+where
+    TKF: FnMut(&[u8], Range<usize>, bool) -> Result<(), rusqlite::Error>,
+{
+    push_token
+}
+
+/// 复制一份当前的全局停词表
+pub(super) fn default_stopwords() -> HashSet<String> {
+    match global_stopwords() {
+        Some(stopwords) => stopwords.as_ref().clone(),
+        None => STOPWORD.iter().map(|word| (*word).to_owned()).collect(),
+    }
+}
+
+/// 将停词加入停词表，停词会去掉首尾的空白并转换成小写
+pub(super) fn extend_stopwords<S: AsRef<str>>(
+    stopwords: &mut HashSet<String>,
+    words: impl IntoIterator<Item = S>,
+) {
+    for word in words {
+        let word = word.as_ref().trim();
+        if !word.is_empty() {
+            stopwords.insert(word.to_lowercase());
+        }
+    }
+}
+
+/// 读取停词表文件，文件无法读取时返回 `None`，使用默认的停词表
+pub(super) fn load_stopwords(path: &str) -> Option<HashSet<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Some(parse_stopwords(&content)),
+        Err(error) => {
+            log::warn!(
+                "[sqlite-simple-tokenizer] failed to load stopwords {path}, fall back to the default stopwords: {error}"
+            );
+            None
+        }
+    }
+}
+
+/// 解析同义词表，每行是一组用逗号分隔的同义词，忽略空行和以 `#` 开头的注释行
+///
+/// 同义词会去掉首尾的空白并转换成小写，返回每个词到同一组中其余词的映射，
+/// 一个词出现在多组中时，合并这些组中的同义词
+pub(super) fn parse_synonyms(content: &str) -> HashMap<String, Vec<String>> {
+    let mut synonyms = HashMap::<String, Vec<String>>::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut group = Vec::<String>::new();
+        for word in line
+            .split(',')
+            .map(str::trim)
+            .filter(|word| !word.is_empty())
+        {
+            let word = word.to_lowercase();
+            if !group.contains(&word) {
+                group.push(word);
+            }
+        }
+        for word in &group {
+            let entry = synonyms.entry(word.clone()).or_default();
+            for synonym in &group {
+                if synonym != word && !entry.contains(synonym) {
+                    entry.push(synonym.clone());
+                }
+            }
+        }
+    }
+    synonyms.retain(|_, group| !group.is_empty());
+    synonyms
+}
+
+/// 读取同义词表文件，文件无法读取时返回 `None`，不扩展同义词
+pub(super) fn load_synonyms(path: &str) -> Option<HashMap<String, Vec<String>>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Some(parse_synonyms(&content)),
+        Err(error) => {
+            log::warn!(
+                "[sqlite-simple-tokenizer] failed to load synonyms {path}, synonyms are disabled: {error}"
+            );
+            None
+        }
     }
-    need_stem
 }
 
 fn is_diacritic(ch: char) -> bool {
     ('\u{0300}'..='\u{036f}').contains(&ch)
 }
+
+/// 汉字异体字选择符，跟在汉字后面选择字形，不改变字符本身
+fn is_ideographic_variation_selector(ch: char) -> bool {
+    ('\u{e0100}'..='\u{e01ef}').contains(&ch)
+}
+
+/// 双向文本的方向控制字符，只影响显示顺序，不影响文本的逻辑顺序
+fn is_bidi_control(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{061c}' | '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        InvalidUtf8Policy, Ligatures, LossyText, NormalizationForm, Normalizer, NumberMode,
+        OffsetTracker, PipelineOutcome, Script, TokenPipeline, alnum_parts, cjk_number,
+        cjk_number_spans, en_number_word, extend_stopwords, fold_diacritics, identifier_parts,
+        is_separator_str, is_space_or_ascii_punctuation_str, merge_grouped_numbers, need_pinyin,
+        parse_stopwords, parse_synonyms, script_runs, shingles, skip_long_tokens,
+        split_number_unit, symbol_ranges, whitespace_word_indices,
+    };
+
+    #[test]
+    fn test_offset_tracker() {
+        // 重复的 token 依次对应后面的位置
+        let text = "哈哈 ab 哈哈";
+        let mut tracker = OffsetTracker::new(text);
+        let ranges = ["哈", "哈", "ab", "哈", "哈"].map(|token| tracker.range_of(token).unwrap());
+        assert_eq!([0..3, 3..6, 7..9, 10..13, 13..16], ranges);
+        assert_eq!(None, tracker.range_of("哈"));
+        // 原始文本的切片使用切片的位置，可以重叠、不按顺序出现
+        let text = "中国人民银行";
+        let mut tracker = OffsetTracker::new(text);
+        let ranges = [&text[0..6], &text[6..12], &text[0..18], &text[12..18]]
+            .map(|token| tracker.range_of(token).unwrap());
+        assert_eq!([0..6, 6..12, 0..18, 12..18], ranges);
+        // 和文本内容相同的其他字符串，按照顺序查找
+        let owned = text.to_owned();
+        let mut tracker = OffsetTracker::new(&owned[..6]);
+        assert_eq!(None, tracker.range_of(&text[6..12]));
+    }
+
+    #[test]
+    fn test_need_pinyin() {
+        assert!(need_pinyin("国"));
+        assert!(need_pinyin("\u{20001}"));
+        assert!(!need_pinyin("\u{20001}\u{20001}"));
+        assert!(!need_pinyin("a"));
+        assert!(!need_pinyin(""));
+    }
+
+    #[test]
+    fn test_parse_stopwords() {
+        let stopwords = parse_stopwords("# 注释\n\n  SQLite  \r\nthe\n\t\n#tokenizer\n国家\n");
+        let mut stopwords = stopwords.into_iter().collect::<Vec<_>>();
+        stopwords.sort();
+        assert_eq!(["sqlite", "the", "国家"], stopwords.as_slice());
+    }
+
+    #[test]
+    fn test_extend_stopwords() {
+        let mut stopwords = parse_stopwords("the\n");
+        extend_stopwords(&mut stopwords, [" SQLite ", "", "测试", "the"]);
+        let mut stopwords = stopwords.into_iter().collect::<Vec<_>>();
+        stopwords.sort();
+        assert_eq!(["sqlite", "the", "测试"], stopwords.as_slice());
+    }
+
+    #[test]
+    fn test_parse_synonyms() {
+        let synonyms = parse_synonyms("# 注释\n\nUSA, us ,America\nus,united states,,us\nalone\n");
+        assert_eq!(["us", "america"], synonyms["usa"].as_slice());
+        assert_eq!(
+            ["usa", "america", "united states"],
+            synonyms["us"].as_slice()
+        );
+        assert_eq!(["us"], synonyms["united states"].as_slice());
+        // 只有一个词的行没有同义词
+        assert!(!synonyms.contains_key("alone"));
+    }
+
+    #[test]
+    fn test_skip_long_tokens() {
+        let mut tokens = Vec::new();
+        let mut push_token = skip_long_tokens(4, |token: &[u8], _range, colocated| {
+            tokens.push((String::from_utf8(token.to_vec()).unwrap(), colocated));
+            Ok(())
+        });
+        for (token, colocated) in [
+            ("abcdef", false),
+            ("abc", true),
+            ("ab", true),
+            ("abcd", false),
+            ("abcdef", true),
+            ("a", true),
+        ] {
+            push_token(token.as_bytes(), 0..token.len(), colocated).unwrap();
+        }
+        drop(push_token);
+        // 被跳过的 token 之后的第一个 colocated token 写入新的位置
+        assert_eq!(
+            [("abc", false), ("ab", true), ("abcd", false), ("a", true)]
+                .map(|(token, colocated)| (token.to_owned(), colocated))
+                .as_slice(),
+            tokens.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_number_mode() {
+        let apply = |mode: NumberMode, word: &str| {
+            let mut word = word.to_owned();
+            mode.apply(&mut word).then_some(word)
+        };
+        for word in ["32.3", "1,000", "007", "sqlite"] {
+            assert_eq!(Some(word.to_owned()), apply(NumberMode::Keep, word));
+        }
+        assert_eq!(None, apply(NumberMode::Drop, "32.3"));
+        assert_eq!(None, apply(NumberMode::Drop, "1,000"));
+        assert_eq!(
+            Some("3.14.15".to_owned()),
+            apply(NumberMode::Drop, "3.14.15")
+        );
+        assert_eq!(Some("sqlite".to_owned()), apply(NumberMode::Drop, "sqlite"));
+        for (word, normalized) in [
+            ("1,000", "1000"),
+            ("007", "7"),
+            ("000", "0"),
+            ("0.5", "0.5"),
+            ("00.50", "0.50"),
+            ("1,234,567.89", "1234567.89"),
+            ("1990s", "1990s"),
+        ] {
+            assert_eq!(
+                Some(normalized.to_owned()),
+                apply(NumberMode::Normalize, word),
+                "{word}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_merge_grouped_numbers() {
+        let merge = |words: &[&str]| {
+            let text = words.concat();
+            let mut start = 0;
+            let ranges = words
+                .iter()
+                .map(|word| {
+                    start += word.len();
+                    start - word.len()..start
+                })
+                .collect();
+            merge_grouped_numbers(&text, ranges)
+                .into_iter()
+                .map(|range| text[range].to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ["共", "1,000", "元"],
+            merge(&["共", "1", ",", "000", "元"]).as_slice()
+        );
+        assert_eq!(
+            ["1,234,567.5"],
+            merge(&["1", ",", "234", ",", "567.5"]).as_slice()
+        );
+        // 逗号后面不是三位数字时不合并
+        assert_eq!(["1", ",", "20"], merge(&["1", ",", "20"]).as_slice());
+        assert_eq!(["a", ",", "000"], merge(&["a", ",", "000"]).as_slice());
+    }
+
+    #[test]
+    fn test_is_separator_str() {
+        for word in [
+            "！", "，", "。", "？", "、", "《", "》", "……", "——", " ，", "!", ", ",
+        ] {
+            assert!(is_separator_str(word), "{word}");
+        }
+        for word in ["‹", "‼", "⁈"] {
+            assert!(is_separator_str(word), "{word}");
+        }
+        for word in [
+            "国", "a，", "℃", "😀", "〇", "々", "１", "‰", "′", "″", "⁄", "§", "¶",
+        ] {
+            assert!(!is_separator_str(word), "{word}");
+        }
+        // ascii 的版本不处理全角标点
+        assert!(!is_space_or_ascii_punctuation_str("！"));
+        assert!(!is_space_or_ascii_punctuation_str("，"));
+        assert!(is_space_or_ascii_punctuation_str(", "));
+    }
+
+    #[test]
+    fn test_identifier_parts() {
+        let parts = |word: &'static str| {
+            identifier_parts(word)
+                .into_iter()
+                .map(|range| &word[range])
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(["get", "User", "Name"], parts("getUserName").as_slice());
+        assert_eq!(["HTTP", "Server"], parts("HTTPServer").as_slice());
+        assert_eq!(
+            ["snake", "case", "name"],
+            parts("snake_case_name").as_slice()
+        );
+        assert_eq!(
+            ["parse", "Utf8", "Decoder"],
+            parts("_parse_Utf8Decoder_").as_slice()
+        );
+        assert_eq!(["Café", "Menu"], parts("CaféMenu").as_slice());
+        assert_eq!(["sqlite"], parts("sqlite").as_slice());
+        assert_eq!(["URL"], parts("URL").as_slice());
+        assert!(parts("__").is_empty());
+    }
+
+    #[test]
+    fn test_symbol_ranges() {
+        let text = "ok 👍👍, 👨\u{200d}👩\u{200d}👧 ©";
+        let symbols = symbol_ranges(text, 0..text.len())
+            .into_iter()
+            .map(|range| &text[range])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ["👍", "👍", "👨\u{200d}👩\u{200d}👧", "©"],
+            symbols.as_slice()
+        );
+        let text = "sqlite, ！";
+        assert!(symbol_ranges(text, 0..text.len()).is_empty());
+    }
+
+    #[test]
+    fn test_whitespace_word_indices() {
+        assert_eq!(
+            vec![(0, "C++"), (4, "C#"), (11, "国家")],
+            whitespace_word_indices("C++ C#\u{3000} \t国家 ").collect::<Vec<_>>()
+        );
+        assert_eq!(0, whitespace_word_indices(" \n ").count());
+    }
+
+    #[test]
+    fn test_alnum_parts() {
+        let parts = |word: &'static str| {
+            alnum_parts(word)
+                .into_iter()
+                .map(|range| &word[range])
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(["ABC", "123"], parts("ABC123").as_slice());
+        assert_eq!(["3", "D"], parts("3D").as_slice());
+        assert_eq!(["version", "2", "beta"], parts("version2beta").as_slice());
+        assert_eq!(["32.3", "feet"], parts("32.3feet").as_slice());
+        assert_eq!(["can't"], parts("can't").as_slice());
+        assert!(parts("").is_empty());
+    }
+
+    #[test]
+    fn test_shingles() {
+        assert_eq!(["tok", "oke", "ken"], shingles("token", 3).as_slice());
+        assert!(shingles("tok", 3).is_empty());
+        assert_eq!(["ca", "af", "fé"], shingles("café", 2).as_slice());
+    }
+
+    #[test]
+    fn test_script_runs() {
+        assert_eq!(
+            vec![
+                (Script::Latin, 0..1),
+                (Script::Han, 1..4),
+                (Script::Latin, 4..5),
+                (Script::Han, 5..8),
+                (Script::Latin, 8..9),
+            ],
+            script_runs("a国b文c")
+        );
+        assert_eq!(vec![(Script::Latin, 0..5)], script_runs("can't"));
+        assert_eq!(vec![(Script::Latin, 0..5)], script_runs("1990s"));
+        assert_eq!(vec![(Script::Latin, 0..5)], script_runs("ﬁle"));
+        assert_eq!(vec![(Script::Other, 0..4)], script_runs("32.3"));
+        assert_eq!(
+            vec![(Script::Other, 0..15), (Script::Latin, 15..18)],
+            script_runs("привет123abc")
+        );
+        // 片假名和汉字、拉丁字母是不同的片段，长音符号属于片假名
+        assert_eq!(
+            vec![(Script::Han, 0..6), (Script::Kana, 6..27)],
+            script_runs("日本コンピューター")
+        );
+        assert_eq!(
+            vec![(Script::Kana, 0..10), (Script::Latin, 10..13)],
+            script_runs("カメラ_abc")
+        );
+        assert!(script_runs("").is_empty());
+    }
+
+    #[test]
+    fn test_split_number_unit() {
+        assert_eq!(Some(4), split_number_unit("32.3feet"));
+        assert_eq!(Some(5), split_number_unit("1,000m"));
+        assert_eq!(Some(1), split_number_unit("3D"));
+        assert_eq!(None, split_number_unit("32.3"));
+        assert_eq!(None, split_number_unit("feet"));
+        assert_eq!(None, split_number_unit("ABC123"));
+        assert_eq!(None, split_number_unit("3.feet"));
+        assert_eq!(None, split_number_unit("3d2"));
+    }
+
+    #[test]
+    fn test_lossy_text() {
+        let text = LossyText::new("sqlite 国".as_bytes());
+        assert_eq!("sqlite 国", text.as_str());
+        assert_eq!(7..10, text.original_range(7..10));
+        // 无效的字节被替换成 U+FFFD 后，范围仍然对应原始的字节
+        let bytes = b"ab\xff\xfecd \xe5\x9b\xbd\xe5";
+        let text = LossyText::new(bytes);
+        assert_eq!("ab\u{fffd}\u{fffd}cd 国\u{fffd}", text.as_str());
+        assert_eq!(0..2, text.original_range(0..2));
+        assert_eq!(2..3, text.original_range(2..5));
+        assert_eq!(4..6, text.original_range(8..10));
+        assert_eq!(7..10, text.original_range(11..14));
+        assert_eq!(10..11, text.original_range(14..17));
+    }
+
+    #[test]
+    fn test_lossy_text_grapheme_range() {
+        // 范围扩展到完整的字素簇，包括后面的附加符号
+        let text = LossyText::new("cafe\u{301} 国\u{301}家".as_bytes());
+        assert_eq!(0..6, text.original_range(0..4));
+        assert_eq!(7..12, text.original_range(7..10));
+        assert_eq!(12..15, text.original_range(12..15));
+        // 起点在字素簇中间时向前扩展
+        assert_eq!(3..6, text.original_range(4..6));
+        // \r\n 是一个字素簇
+        let text = LossyText::new(b"a\r\nb");
+        assert_eq!(1..3, text.original_range(1..2));
+        // 包含无效的字节时，扩展后的范围仍然对应原始的字节
+        let text = LossyText::new(b"\xffe\xcc\x81 x");
+        assert_eq!(1..4, text.original_range(3..4));
+    }
+
+    #[test]
+    fn test_lossy_text_with_policy() {
+        // 不完整的多字节字符是一个无效的字节序列
+        let bytes = b"ab\xe5\x9bcd";
+        let text = LossyText::with_policy(bytes, InvalidUtf8Policy::Lossy).unwrap();
+        assert_eq!("ab\u{fffd}cd", text.as_str());
+        // 无效的字节序列替换成一个空白，范围仍然对应原始的字节
+        let text = LossyText::with_policy(bytes, InvalidUtf8Policy::Skip).unwrap();
+        assert_eq!("ab cd", text.as_str());
+        assert_eq!(0..2, text.original_range(0..2));
+        assert_eq!(4..6, text.original_range(3..5));
+        assert!(matches!(
+            LossyText::with_policy(bytes, InvalidUtf8Policy::Error),
+            Err(rusqlite::Error::Utf8Error(_))
+        ));
+        // 有效的 UTF-8 不受影响
+        let text = LossyText::with_policy(b"sqlite", InvalidUtf8Policy::Error).unwrap();
+        assert_eq!("sqlite", text.as_str());
+    }
+
+    #[test]
+    fn test_en_number_word() {
+        assert_eq!(Some("three".to_owned()), en_number_word("3"));
+        assert_eq!(Some("nineteen".to_owned()), en_number_word("19"));
+        assert_eq!(Some("seventy".to_owned()), en_number_word("70"));
+        assert_eq!(Some("0".to_owned()), en_number_word("zero"));
+        assert_eq!(Some("90".to_owned()), en_number_word("ninety"));
+        assert_eq!(None, en_number_word("21"));
+        assert_eq!(None, en_number_word("03"));
+        assert_eq!(None, en_number_word("100"));
+        assert_eq!(None, en_number_word("thirds"));
+    }
+
+    #[test]
+    fn test_cjk_number() {
+        for (text, number) in [
+            ("三十二", "32"),
+            ("一百零五", "105"),
+            ("二十", "20"),
+            ("十", "10"),
+            ("十二", "12"),
+            ("一百五", "150"),
+            ("一千零一十", "1010"),
+            ("三万五", "35000"),
+            ("三万零五", "30005"),
+            ("两万三千五百", "23500"),
+            ("一亿二千万", "120000000"),
+            ("一万亿", "1000000000000"),
+            ("叁佰贰拾壹", "321"),
+            ("一九八四", "1984"),
+            ("零", "0"),
+        ] {
+            assert_eq!(Some(number.to_owned()), cjk_number(text), "{text}");
+        }
+        for text in ["", "万", "百", "二三十", "十百", "一万二万", "三十二元"] {
+            assert_eq!(None, cjk_number(text), "{text}");
+        }
+        assert_eq!(
+            vec![(0..9, "32".to_owned()), (15..27, "105".to_owned())],
+            cjk_number_spans("三十二元和一百零五")
+        );
+    }
+
+    #[test]
+    fn test_make_lowercase_strip_bidi_control() {
+        let normalizer = Normalizer::default();
+        let mut buf = String::new();
+        normalizer.make_lowercase("\u{200f}עולם\u{200f}", &mut buf);
+        assert_eq!("עולם", buf);
+        normalizer.make_lowercase("\u{200e}Rust\u{2069}", &mut buf);
+        assert_eq!("rust", buf);
+        normalizer.make_lowercase("\u{200f}", &mut buf);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_make_lowercase_ascii_fast_path() {
+        let words = [
+            "",
+            "A",
+            "Running",
+            "SQLite3",
+            "ABC123",
+            "can't",
+            "get_user_name",
+            "tab\tcontrol\u{7f}",
+            "1,000.5",
+        ];
+        let forms = [
+            NormalizationForm::Nfkc,
+            NormalizationForm::Nfc,
+            NormalizationForm::None,
+        ];
+        for form in forms {
+            for casefold in [false, true] {
+                let normalizer = Normalizer {
+                    form,
+                    casefold,
+                    ligatures: Ligatures::All,
+                    fullwidth_kana: true,
+                    ..Normalizer::default()
+                };
+                for word in words {
+                    let (mut fast, mut slow) = (String::new(), String::new());
+                    let fast_stem = normalizer.make_lowercase(word, &mut fast);
+                    let slow_stem = normalizer.normalize_chars(word, &mut slow);
+                    assert_eq!((slow_stem, &slow), (fast_stem, &fast), "{form:?} {word}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_make_lowercase_with_keep_diacritics() {
+        let mut buf = String::new();
+        let mut normalizer = Normalizer {
+            form: NormalizationForm::None,
+            ..Normalizer::default()
+        };
+        normalizer.make_lowercase("re\u{301}sume\u{301}", &mut buf);
+        assert_eq!("resume", buf);
+        normalizer.keep_diacritics = true;
+        normalizer.make_lowercase("re\u{301}sume\u{301}", &mut buf);
+        assert_eq!("re\u{301}sume\u{301}", buf);
+        // NFKC 合并的字符不受影响，没有组合字符的附加符号保留
+        normalizer.form = NormalizationForm::Nfkc;
+        normalizer.make_lowercase("re\u{301}sume\u{301} q\u{301}", &mut buf);
+        assert_eq!("résumé q\u{301}", buf);
+    }
+
+    #[test]
+    fn test_make_lowercase_with_fullwidth_kana() {
+        let mut buf = String::new();
+        let mut normalizer = Normalizer {
+            form: NormalizationForm::Nfc,
+            ..Normalizer::default()
+        };
+        normalizer.make_lowercase("ｺﾝﾋﾟｭｰﾀｰ", &mut buf);
+        assert_eq!("ｺﾝﾋﾟｭｰﾀｰ", buf);
+        normalizer.fullwidth_kana = true;
+        normalizer.make_lowercase("ｺﾝﾋﾟｭｰﾀｰ", &mut buf);
+        assert_eq!("コンピューター", buf);
+        // 不做归一化时浊音符号同样和前一个假名合并，全角字母保持原样
+        normalizer.form = NormalizationForm::None;
+        normalizer.make_lowercase("ｶﾞｲﾄﾞＡ", &mut buf);
+        assert_eq!("ガイドａ", buf);
+    }
+
+    #[test]
+    fn test_make_lowercase_with_casefold() {
+        let mut buf = String::new();
+        let mut normalizer = Normalizer::default();
+        // 小写转换不改变 ß，不需要提取词干
+        assert!(!normalizer.make_lowercase("Straße", &mut buf));
+        assert_eq!("straße", buf);
+        normalizer.casefold = true;
+        // 折叠后是 ascii 的单词需要提取词干
+        assert!(normalizer.make_lowercase("Straße", &mut buf));
+        assert_eq!("strasse", buf);
+        assert!(normalizer.make_lowercase("STRASSE", &mut buf));
+        assert_eq!("strasse", buf);
+        assert!(!normalizer.make_lowercase("ΣΟΦΟΣ", &mut buf));
+        assert_eq!("σοφοσ", buf);
+        assert!(!normalizer.make_lowercase("σοφος", &mut buf));
+        assert_eq!("σοφοσ", buf);
+        // 没有特殊折叠的字符和小写转换相同
+        assert!(!normalizer.make_lowercase("Ärger", &mut buf));
+        assert_eq!("ärger", buf);
+        // 不做 Unicode 归一化时，连字同样折叠
+        normalizer.form = NormalizationForm::None;
+        assert!(normalizer.make_lowercase("ﬁle", &mut buf));
+        assert_eq!("file", buf);
+    }
+
+    #[test]
+    fn test_fold_diacritics() {
+        assert_eq!(Some("naive".to_owned()), fold_diacritics("naïve"));
+        assert_eq!(Some("cafe".to_owned()), fold_diacritics("cafe\u{301}"));
+        assert_eq!(None, fold_diacritics("naive"));
+        assert_eq!(None, fold_diacritics("øß国"));
+    }
+
+    #[test]
+    fn test_token_pipeline() {
+        let process = |pipeline: &TokenPipeline, word: &str| {
+            let mut buf = String::new();
+            match pipeline.process(word, &mut buf) {
+                PipelineOutcome::Emit => Some(buf),
+                PipelineOutcome::Skip => None,
+            }
+        };
+        let mut pipeline = TokenPipeline::default();
+        assert_eq!(Some("run".to_owned()), process(&pipeline, "Running"));
+        assert_eq!(
+            Some("sqlite".to_owned()),
+            process(&pipeline, "ＳＱＬｉｔｅ")
+        );
+        // 停词、标点和空白不写入
+        for word in ["the", "！", " ", "--"] {
+            assert_eq!(None, process(&pipeline, word), "{word}");
+        }
+        pipeline.add_stopwords(["quokka"]);
+        assert_eq!(None, process(&pipeline, "Quokka"));
+        pipeline.disable_stemming();
+        assert_eq!(Some("running".to_owned()), process(&pipeline, "Running"));
+        pipeline.disable_stopword();
+        assert_eq!(Some("the".to_owned()), process(&pipeline, "The"));
+        // 流程本身不保存分词的状态，可以在线程之间共享
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<TokenPipeline>();
+    }
+
+    #[test]
+    fn test_make_lowercase_with_ligatures() {
+        let mut buf = String::new();
+        let mut normalizer = Normalizer {
+            form: NormalizationForm::Nfc,
+            ligatures: Ligatures::Keep,
+            ..Normalizer::default()
+        };
+        // NFC 不分解连字
+        assert!(!normalizer.make_lowercase("ﬁle", &mut buf));
+        assert_eq!("ﬁle", buf);
+        normalizer.ligatures = Ligatures::Decompose;
+        assert!(normalizer.make_lowercase("ﬁle", &mut buf));
+        assert_eq!("file", buf);
+        normalizer.make_lowercase("Æsop", &mut buf);
+        assert_eq!("æsop", buf);
+        normalizer.ligatures = Ligatures::All;
+        assert!(normalizer.make_lowercase("Æsop", &mut buf));
+        assert_eq!("aesop", buf);
+        // NFKC 本身就会分解排版连字
+        let normalizer = Normalizer::default();
+        assert!(normalizer.make_lowercase("ﬁle", &mut buf));
+        assert_eq!("file", buf);
+        let normalizer = Normalizer {
+            form: NormalizationForm::None,
+            ligatures: Ligatures::Decompose,
+            ..Normalizer::default()
+        };
+        normalizer.make_lowercase("ﬂow", &mut buf);
+        assert_eq!("flow", buf);
+    }
+}
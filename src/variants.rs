@@ -0,0 +1,119 @@
+/// 常用繁体字到简体字的映射表，按照繁体字的码点排序
+///
+/// 只包含可以逐字转换的常用字，多个繁体字可以对应同一个简体字，例如 `髮` 和 `發` 都对应 `发`，
+/// 不做词语级别的转换，`乾隆` 的 `乾` 这类在简体中也保留的字不在表中
+#[rustfmt::skip]
+pub const TRADITIONAL_TO_SIMPLIFIED: &[(char, char)] = &[
+    ('亞', '亚'), ('來', '来'), ('係', '系'), ('個', '个'), ('們', '们'), ('傳', '传'), ('債', '债'),
+    ('傷', '伤'), ('價', '价'), ('儀', '仪'), ('億', '亿'), ('優', '优'), ('兒', '儿'), ('內', '内'), ('兩', '两'),
+    ('冊', '册'), ('劃', '划'), ('劇', '剧'), ('劍', '剑'), ('劑', '剂'), ('動', '动'), ('務', '务'), ('勝', '胜'),
+    ('勞', '劳'), ('勢', '势'), ('匯', '汇'), ('區', '区'), ('協', '协'), ('卻', '却'), ('厲', '厉'), ('參', '参'),
+    ('員', '员'), ('問', '问'), ('啟', '启'), ('單', '单'), ('嚴', '严'), ('國', '国'), ('圍', '围'), ('園', '园'),
+    ('圓', '圆'), ('圖', '图'), ('團', '团'), ('報', '报'), ('場', '场'), ('壓', '压'), ('壞', '坏'), ('壯', '壮'),
+    ('壽', '寿'), ('夢', '梦'), ('奪', '夺'), ('婦', '妇'), ('媽', '妈'), ('孫', '孙'), ('學', '学'), ('宮', '宫'),
+    ('實', '实'), ('寧', '宁'), ('寫', '写'), ('寶', '宝'), ('將', '将'), ('專', '专'), ('尋', '寻'), ('對', '对'),
+    ('導', '导'), ('層', '层'), ('島', '岛'), ('師', '师'), ('帶', '带'), ('幫', '帮'), ('幹', '干'), ('幾', '几'),
+    ('庫', '库'), ('廠', '厂'), ('廣', '广'), ('廳', '厅'), ('彈', '弹'), ('彙', '汇'), ('後', '后'), ('從', '从'),
+    ('徵', '征'), ('愛', '爱'), ('態', '态'), ('應', '应'), ('戀', '恋'), ('戰', '战'), ('戲', '戏'), ('擁', '拥'),
+    ('擇', '择'), ('擊', '击'), ('據', '据'), ('數', '数'), ('斷', '断'), ('時', '时'), ('暫', '暂'), ('曆', '历'),
+    ('書', '书'), ('會', '会'), ('東', '东'), ('條', '条'), ('業', '业'), ('極', '极'), ('構', '构'), ('樂', '乐'),
+    ('標', '标'), ('樣', '样'), ('樹', '树'), ('橋', '桥'), ('機', '机'), ('檢', '检'), ('檯', '台'), ('櫃', '柜'),
+    ('權', '权'), ('歐', '欧'), ('歡', '欢'), ('歲', '岁'), ('歷', '历'), ('歸', '归'), ('殺', '杀'), ('氣', '气'),
+    ('決', '决'), ('況', '况'), ('淚', '泪'), ('淺', '浅'), ('測', '测'), ('湯', '汤'), ('準', '准'), ('溫', '温'),
+    ('滅', '灭'), ('滿', '满'), ('漁', '渔'), ('漢', '汉'), ('漲', '涨'), ('潔', '洁'), ('澤', '泽'), ('濃', '浓'),
+    ('濕', '湿'), ('濟', '济'), ('灑', '洒'), ('灣', '湾'), ('災', '灾'), ('為', '为'), ('無', '无'), ('熱', '热'),
+    ('燈', '灯'), ('燒', '烧'), ('營', '营'), ('燦', '灿'), ('爐', '炉'), ('爭', '争'), ('爺', '爷'), ('爾', '尔'),
+    ('牆', '墙'), ('犧', '牺'), ('狀', '状'), ('猶', '犹'), ('獅', '狮'), ('獎', '奖'), ('獨', '独'), ('獻', '献'),
+    ('現', '现'), ('瑪', '玛'), ('環', '环'), ('瓊', '琼'), ('產', '产'), ('畢', '毕'), ('畫', '画'), ('當', '当'),
+    ('瘋', '疯'), ('療', '疗'), ('癢', '痒'), ('發', '发'), ('皺', '皱'), ('盡', '尽'), ('監', '监'), ('盤', '盘'),
+    ('眾', '众'), ('睜', '睁'), ('確', '确'), ('碼', '码'), ('礎', '础'), ('禍', '祸'), ('禪', '禅'), ('禮', '礼'),
+    ('種', '种'), ('稱', '称'), ('穀', '谷'), ('穩', '稳'), ('窮', '穷'), ('競', '竞'), ('筆', '笔'), ('筍', '笋'),
+    ('節', '节'), ('範', '范'), ('築', '筑'), ('簡', '简'), ('糧', '粮'), ('紀', '纪'), ('約', '约'), ('紅', '红'),
+    ('純', '纯'), ('紙', '纸'), ('級', '级'), ('紛', '纷'), ('紡', '纺'), ('細', '细'), ('終', '终'), ('組', '组'),
+    ('結', '结'), ('絕', '绝'), ('絡', '络'), ('給', '给'), ('統', '统'), ('絲', '丝'), ('經', '经'), ('綜', '综'),
+    ('綠', '绿'), ('維', '维'), ('網', '网'), ('緊', '紧'), ('緒', '绪'), ('線', '线'), ('緣', '缘'), ('編', '编'),
+    ('練', '练'), ('縣', '县'), ('總', '总'), ('織', '织'), ('繫', '系'), ('繼', '继'), ('續', '续'), ('罰', '罚'),
+    ('罷', '罢'), ('義', '义'), ('習', '习'), ('聖', '圣'), ('聯', '联'), ('聰', '聪'), ('聲', '声'), ('職', '职'),
+    ('聽', '听'), ('肅', '肃'), ('脫', '脱'), ('腦', '脑'), ('腳', '脚'), ('膽', '胆'), ('臉', '脸'), ('臟', '脏'),
+    ('臨', '临'), ('臺', '台'), ('與', '与'), ('興', '兴'), ('舊', '旧'), ('艦', '舰'), ('艱', '艰'), ('莊', '庄'),
+    ('華', '华'), ('萬', '万'), ('葉', '叶'), ('蓋', '盖'), ('蕭', '萧'), ('薑', '姜'), ('藍', '蓝'), ('藝', '艺'),
+    ('藥', '药'), ('蘇', '苏'), ('蘋', '苹'), ('蘭', '兰'), ('蘿', '萝'), ('處', '处'), ('號', '号'), ('蝦', '虾'),
+    ('蟲', '虫'), ('蠶', '蚕'), ('術', '术'), ('衛', '卫'), ('衝', '冲'), ('裏', '里'), ('補', '补'), ('裝', '装'),
+    ('裡', '里'), ('製', '制'), ('複', '复'), ('襲', '袭'), ('見', '见'), ('規', '规'), ('視', '视'), ('親', '亲'),
+    ('覺', '觉'), ('覽', '览'), ('觀', '观'), ('觸', '触'), ('訂', '订'), ('計', '计'), ('訊', '讯'), ('討', '讨'),
+    ('訓', '训'), ('記', '记'), ('設', '设'), ('許', '许'), ('評', '评'), ('詞', '词'), ('試', '试'), ('詩', '诗'),
+    ('話', '话'), ('詳', '详'), ('認', '认'), ('語', '语'), ('誤', '误'), ('說', '说'), ('誰', '谁'), ('課', '课'),
+    ('調', '调'), ('談', '谈'), ('請', '请'), ('論', '论'), ('諸', '诸'), ('謎', '谜'), ('講', '讲'), ('謝', '谢'),
+    ('證', '证'), ('識', '识'), ('議', '议'), ('護', '护'), ('讀', '读'), ('變', '变'), ('讓', '让'), ('豐', '丰'),
+    ('豬', '猪'), ('貓', '猫'), ('貝', '贝'), ('負', '负'), ('財', '财'), ('貧', '贫'), ('貨', '货'), ('責', '责'),
+    ('貴', '贵'), ('買', '买'), ('費', '费'), ('貼', '贴'), ('貿', '贸'), ('賀', '贺'), ('資', '资'), ('賓', '宾'),
+    ('賣', '卖'), ('質', '质'), ('賴', '赖'), ('購', '购'), ('賽', '赛'), ('贊', '赞'), ('趕', '赶'), ('車', '车'),
+    ('軍', '军'), ('軟', '软'), ('較', '较'), ('載', '载'), ('輕', '轻'), ('輛', '辆'), ('輪', '轮'), ('輸', '输'),
+    ('轉', '转'), ('辦', '办'), ('辭', '辞'), ('農', '农'), ('這', '这'), ('連', '连'), ('進', '进'), ('遊', '游'),
+    ('運', '运'), ('過', '过'), ('達', '达'), ('遠', '远'), ('適', '适'), ('遲', '迟'), ('選', '选'), ('遺', '遗'),
+    ('還', '还'), ('邊', '边'), ('邏', '逻'), ('郵', '邮'), ('鄉', '乡'), ('鄭', '郑'), ('鄰', '邻'), ('醜', '丑'),
+    ('醫', '医'), ('釋', '释'), ('針', '针'), ('銀', '银'), ('鋼', '钢'), ('錄', '录'), ('錢', '钱'), ('錯', '错'),
+    ('錶', '表'), ('鍋', '锅'), ('鍵', '键'), ('鍾', '钟'), ('鎮', '镇'), ('鏡', '镜'), ('鐘', '钟'), ('鐵', '铁'),
+    ('長', '长'), ('門', '门'), ('閃', '闪'), ('閉', '闭'), ('開', '开'), ('閒', '闲'), ('間', '间'), ('閱', '阅'),
+    ('闊', '阔'), ('關', '关'), ('陣', '阵'), ('陰', '阴'), ('陳', '陈'), ('陸', '陆'), ('陽', '阳'), ('隊', '队'),
+    ('階', '阶'), ('際', '际'), ('隨', '随'), ('險', '险'), ('隻', '只'), ('雖', '虽'), ('雙', '双'), ('雛', '雏'),
+    ('雜', '杂'), ('雞', '鸡'), ('離', '离'), ('難', '难'), ('雲', '云'), ('電', '电'), ('霧', '雾'), ('靈', '灵'),
+    ('靜', '静'), ('鞏', '巩'), ('韓', '韩'), ('韻', '韵'), ('響', '响'), ('頁', '页'), ('頂', '顶'), ('項', '项'),
+    ('順', '顺'), ('須', '须'), ('預', '预'), ('頓', '顿'), ('領', '领'), ('頭', '头'), ('頻', '频'), ('題', '题'),
+    ('顏', '颜'), ('願', '愿'), ('類', '类'), ('顧', '顾'), ('顯', '显'), ('風', '风'), ('颱', '台'), ('颳', '刮'),
+    ('飛', '飞'), ('飯', '饭'), ('飲', '饮'), ('飽', '饱'), ('餓', '饿'), ('餘', '余'), ('館', '馆'), ('馬', '马'),
+    ('駕', '驾'), ('騎', '骑'), ('騙', '骗'), ('驅', '驱'), ('驕', '骄'), ('驗', '验'), ('驚', '惊'), ('髒', '脏'),
+    ('體', '体'), ('髮', '发'), ('鬆', '松'), ('鬥', '斗'), ('鬧', '闹'), ('鬱', '郁'), ('魚', '鱼'), ('魯', '鲁'),
+    ('鮮', '鲜'), ('鳥', '鸟'), ('鳳', '凤'), ('鳴', '鸣'), ('鴨', '鸭'), ('鵝', '鹅'), ('鹽', '盐'), ('麗', '丽'),
+    ('麥', '麦'), ('麵', '面'), ('麼', '么'), ('黃', '黄'), ('點', '点'), ('黨', '党'), ('黴', '霉'), ('鼴', '鼹'),
+    ('齊', '齐'), ('齒', '齿'), ('齡', '龄'), ('龍', '龙'), ('龐', '庞'), ('龜', '龟'),
+];
+
+/// 获取繁体字对应的简体字，不在映射表中的字符原样返回
+pub fn to_simplified(ch: char) -> char {
+    match TRADITIONAL_TO_SIMPLIFIED.binary_search_by_key(&ch, |&(traditional, _)| traditional) {
+        Ok(index) => TRADITIONAL_TO_SIMPLIFIED[index].1,
+        Err(_) => ch,
+    }
+}
+
+/// 将字符串中的繁体字转换成简体字，没有需要转换的字符时返回 `None`
+pub fn fold_traditional(s: &str) -> Option<String> {
+    let folded = s.chars().map(to_simplified).collect::<String>();
+    (folded != s).then_some(folded)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::variants::{TRADITIONAL_TO_SIMPLIFIED, fold_traditional, to_simplified};
+
+    #[test]
+    fn test_traditional_to_simplified_sorted() {
+        // 映射表需要按照繁体字排序，才能使用二分查找
+        assert!(
+            TRADITIONAL_TO_SIMPLIFIED
+                .windows(2)
+                .all(|pair| pair[0].0 < pair[1].0)
+        );
+        assert!(
+            TRADITIONAL_TO_SIMPLIFIED
+                .iter()
+                .all(|&(traditional, simplified)| traditional != simplified)
+        );
+    }
+
+    #[test]
+    fn test_to_simplified() {
+        assert_eq!('国', to_simplified('國'));
+        assert_eq!('发', to_simplified('髮'));
+        assert_eq!('发', to_simplified('發'));
+        // 简体字和不在表中的字符原样返回
+        assert_eq!('国', to_simplified('国'));
+        assert_eq!('a', to_simplified('a'));
+        assert_eq!(
+            Some("国家图书馆".to_owned()),
+            fold_traditional("國家圖書館")
+        );
+        assert_eq!(None, fold_traditional("国家 sqlite"));
+    }
+}